@@ -0,0 +1,76 @@
+//! Rough timing comparisons. These are ignored by default; run them with
+//! `cargo test --release benches -- --ignored --nocapture`, optionally
+//! adding `--features simd`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::matrix::{rotation_y, scaling, translation, Matrix, Matrix44};
+use crate::renderer;
+use crate::tuples::points;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn time<F: FnOnce()>(label: &str, f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    println!("{}: {:?}", label, elapsed);
+    elapsed
+}
+
+fn transform() -> Matrix44 {
+    translation(1.0, -2.0, 3.0) * rotation_y(0.5) * scaling(2.0, 3.0, 4.0)
+}
+
+#[test]
+#[ignore]
+fn bench_tuple_multiplication() {
+    let matrix = Matrix::from(transform());
+    let matrix44 = transform();
+    let point = points::new(1.0, 2.0, 3.0);
+
+    let dynamic = time("Matrix * Point", || {
+        for _ in 0..ITERATIONS {
+            black_box(&matrix * black_box(point));
+        }
+    });
+    let fixed = time("Matrix44 * Point", || {
+        for _ in 0..ITERATIONS {
+            black_box(black_box(&matrix44) * black_box(point));
+        }
+    });
+    println!(
+        "speedup: {:.1}x",
+        dynamic.as_secs_f64() / fixed.as_secs_f64()
+    );
+}
+
+#[test]
+#[ignore]
+fn bench_inverse() {
+    let dynamic = time("Matrix::inverse", || {
+        for _ in 0..ITERATIONS / 10 {
+            // a fresh matrix, so the cached inverse isn't reused
+            black_box(Matrix::from(black_box(transform())).inverse());
+        }
+    });
+    let fixed = time("Matrix44::inverse", || {
+        for _ in 0..ITERATIONS / 10 {
+            black_box(black_box(transform()).inverse());
+        }
+    });
+    println!(
+        "speedup: {:.1}x",
+        dynamic.as_secs_f64() / fixed.as_secs_f64()
+    );
+}
+
+#[test]
+#[ignore]
+fn bench_showcase_scene() {
+    let (world, camera) = renderer::showcase(200, 120);
+    time("showcase scene (200 x 120)", || {
+        black_box(camera.render(world, false));
+    });
+}
@@ -0,0 +1,171 @@
+use crate::canvas::Canvas;
+use crate::math::Real;
+use crate::tuples::colors;
+use crate::tuples::colors::Color;
+
+/// How two images of the same size differ
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// The largest difference of a color channel
+    pub max_difference: Real,
+
+    /// The number of pixels with a channel differing by more than the tolerance
+    pub differing_pixels: usize,
+
+    /// The peak signal-to-noise ratio in dB, infinite for identical images
+    pub psnr: Real,
+
+    /// The mean structural similarity of the luminance, 1 for identical images
+    pub ssim: Real,
+
+    /// The expected image, darkened and in gray, with the differing pixels in red
+    pub diff: Canvas,
+}
+
+/// The side of the windows compared by SSIM
+const SSIM_WINDOW: usize = 8;
+
+impl Canvas {
+    /// Compares this image with the expected one. Colors are clamped to
+    /// [0, 1] first, as they would be when saved. Gives `None` when the
+    /// images have different sizes.
+    pub fn compare(&self, expected: &Canvas, tolerance: Real) -> Option<Comparison> {
+        if (self.width, self.height) != (expected.width, expected.height) {
+            return None;
+        }
+        let mut diff = Canvas::new(self.width, self.height);
+        let (mut max_difference, mut differing_pixels, mut squared) = (0.0, 0, 0.0);
+        for (i, (actual, expected)) in self.pixels.iter().zip(&expected.pixels).enumerate() {
+            let (actual, expected) = (clamped(*actual), clamped(*expected));
+            let d = actual - expected;
+            let largest = d.x.abs().max(d.y.abs()).max(d.z.abs());
+            max_difference = largest.max(max_difference);
+            squared += d.x * d.x + d.y * d.y + d.z * d.z;
+
+            diff.pixels[i] = if largest > tolerance {
+                differing_pixels += 1;
+                Color::red()
+            } else {
+                let gray = luminance(expected) * 0.3;
+                colors::new(gray, gray, gray)
+            };
+        }
+
+        let mse = squared / (self.pixels.len() * 3).max(1) as Real;
+        Some(Comparison {
+            max_difference,
+            differing_pixels,
+            psnr: 10.0 * (1.0 / mse).log10(),
+            ssim: ssim(self, expected),
+            diff,
+        })
+    }
+}
+
+fn clamped(c: Color) -> Color {
+    colors::new(
+        c.x.clamp(0.0, 1.0),
+        c.y.clamp(0.0, 1.0),
+        c.z.clamp(0.0, 1.0),
+    )
+}
+
+fn luminance(c: Color) -> Real {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+/// The mean SSIM of the luminance over non-overlapping windows. Images
+/// smaller than a window are compared as a whole.
+fn ssim(a: &Canvas, b: &Canvas) -> Real {
+    const C1: Real = 0.01 * 0.01;
+    const C2: Real = 0.03 * 0.03;
+    let luma = |canvas: &Canvas, x: usize, y: usize| luminance(clamped(canvas[(x, y)]));
+
+    let window = SSIM_WINDOW.min(a.width).min(a.height).max(1);
+    let (mut total, mut windows) = (0.0, 0);
+    for top in (0..=a.height.saturating_sub(window)).step_by(window) {
+        for left in (0..=a.width.saturating_sub(window)).step_by(window) {
+            let pixels =
+                (top..top + window).flat_map(|y| (left..left + window).map(move |x| (x, y)));
+            let values: Vec<(Real, Real)> = pixels
+                .map(|(x, y)| (luma(a, x, y), luma(b, x, y)))
+                .collect();
+            let n = values.len() as Real;
+
+            let mean_a = values.iter().map(|v| v.0).sum::<Real>() / n;
+            let mean_b = values.iter().map(|v| v.1).sum::<Real>() / n;
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for (va, vb) in &values {
+                var_a += (va - mean_a) * (va - mean_a) / n;
+                var_b += (vb - mean_b) * (vb - mean_b) / n;
+                covariance += (va - mean_a) * (vb - mean_b) / n;
+            }
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+    if windows == 0 {
+        1.0
+    } else {
+        total / windows as Real
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::tuples::colors;
+    use crate::tuples::colors::Color;
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let value = (x + y) as f64 / (width + height) as f64;
+                canvas[(x, y)] = colors::new(value, 1.0 - value, 0.5);
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_comparing_identical_images() {
+        let image = gradient(16, 16);
+        let comparison = image.compare(&image, 0.0).unwrap();
+        assert_eq!(comparison.max_difference, 0.0);
+        assert_eq!(comparison.differing_pixels, 0);
+        assert_eq!(comparison.psnr, f64::INFINITY);
+        assert!((comparison.ssim - 1.0).abs() < 1e-9);
+        assert!(comparison.diff[(3, 4)] != Color::red());
+    }
+
+    #[test]
+    fn test_comparing_different_images() {
+        let expected = gradient(16, 16);
+        let mut actual = expected.clone();
+        actual[(3, 4)] = Color::white();
+        actual[(10, 12)] = actual[(10, 12)] + colors::new(0.01, 0.0, 0.0);
+
+        let comparison = actual.compare(&expected, 0.02).unwrap();
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.diff[(3, 4)], Color::red());
+        assert_ne!(comparison.diff[(10, 12)], Color::red());
+        assert!(comparison.max_difference > 0.5);
+        assert!(comparison.psnr > 20.0 && comparison.psnr < 40.0);
+        assert!(comparison.ssim < 1.0);
+
+        // colors beyond the displayable range don't count
+        let mut bright = expected.clone();
+        bright[(0, 0)] = colors::new(5.0, 5.0, 5.0);
+        let mut white = expected.clone();
+        white[(0, 0)] = Color::white();
+        assert_eq!(bright.compare(&white, 0.0).unwrap().max_difference, 0.0);
+    }
+
+    #[test]
+    fn test_comparing_images_of_different_sizes() {
+        assert!(gradient(4, 4).compare(&gradient(4, 5), 0.1).is_none());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::canvas::Canvas;
+
+/// An uncompressed, single-part, scanline OpenEXR image.
+/// Every channel is stored as 32-bit floats, so the canvas can be
+/// saved without losing any of its dynamic range.
+pub struct Exr {
+    width: usize,
+    height: usize,
+    channels: Vec<Channel>,
+}
+
+pub struct Channel {
+    name: String,
+    values: Vec<f32>,
+}
+
+impl Channel {
+    pub fn new(name: &str, values: Vec<f32>) -> Channel {
+        Channel {
+            name: name.to_string(),
+            values,
+        }
+    }
+}
+
+impl Exr {
+    const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+    /// Version 2, single-part scanline file
+    const VERSION: [u8; 4] = [2, 0, 0, 0];
+
+    const FLOAT_PIXEL_TYPE: i32 = 2;
+
+    /// Creates an image from a list of channels. Each channel needs exactly
+    /// `width * height` values, laid out row by row.
+    pub fn new(width: usize, height: usize, mut channels: Vec<Channel>) -> Exr {
+        // the format requires the channels to be sorted by name
+        channels.sort_by(|a, b| a.name.cmp(&b.name));
+        Exr {
+            width,
+            height,
+            channels,
+        }
+    }
+
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.channels.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(&Exr::MAGIC);
+        bytes.extend(&Exr::VERSION);
+        self.write_header(&mut bytes);
+
+        // Without compression, each block holds exactly one scanline
+        let block_size = 4 + 4 + self.channels.len() * self.width * 4;
+        let table_size = self.height * 8;
+        let first_block = bytes.len() + table_size;
+        for y in 0..self.height {
+            let offset = (first_block + y * block_size) as u64;
+            bytes.extend(&offset.to_le_bytes());
+        }
+
+        for y in 0..self.height {
+            bytes.extend(&(y as i32).to_le_bytes());
+            bytes.extend(&((block_size - 8) as i32).to_le_bytes());
+            for channel in self.channels.iter() {
+                let row = &channel.values[y * self.width..(y + 1) * self.width];
+                row.iter()
+                    .for_each(|value| bytes.extend(&value.to_le_bytes()));
+            }
+        }
+
+        bytes
+    }
+
+    fn write_header(&self, bytes: &mut Vec<u8>) {
+        let mut channel_list = vec![];
+        for channel in self.channels.iter() {
+            channel_list.extend(channel.name.as_bytes());
+            channel_list.push(0);
+            channel_list.extend(&Exr::FLOAT_PIXEL_TYPE.to_le_bytes());
+            channel_list.extend(&[0, 0, 0, 0]); // pLinear and reserved bytes
+            channel_list.extend(&1_i32.to_le_bytes()); // x sampling
+            channel_list.extend(&1_i32.to_le_bytes()); // y sampling
+        }
+        channel_list.push(0);
+
+        let window: Vec<u8> = [0, 0, self.width as i32 - 1, self.height as i32 - 1]
+            .iter()
+            .flat_map(|v: &i32| v.to_le_bytes().to_vec())
+            .collect();
+
+        write_attribute(bytes, "channels", "chlist", &channel_list);
+        write_attribute(bytes, "compression", "compression", &[0]);
+        write_attribute(bytes, "dataWindow", "box2i", &window);
+        write_attribute(bytes, "displayWindow", "box2i", &window);
+        write_attribute(bytes, "lineOrder", "lineOrder", &[0]);
+        write_attribute(bytes, "pixelAspectRatio", "float", &1_f32.to_le_bytes());
+        write_attribute(bytes, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(bytes, "screenWindowWidth", "float", &1_f32.to_le_bytes());
+        bytes.push(0);
+    }
+}
+
+fn write_attribute(bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    bytes.extend(name.as_bytes());
+    bytes.push(0);
+    bytes.extend(kind.as_bytes());
+    bytes.push(0);
+    bytes.extend(&(value.len() as i32).to_le_bytes());
+    bytes.extend(value);
+}
+
+impl Canvas {
+    /// Splits the canvas into the R, G and B channels of an EXR image
+    pub fn to_exr_channels(&self, prefix: &str) -> Vec<Channel> {
+        let channel = |name: &str, f: &dyn Fn(usize) -> f64| {
+            let values = (0..self.pixels.len()).map(|i| f(i) as f32).collect();
+            Channel::new(&format!("{}{}", prefix, name), values)
+        };
+
+        vec![
+            channel("R", &|i| self.pixels[i].x),
+            channel("G", &|i| self.pixels[i].y),
+            channel("B", &|i| self.pixels[i].z),
+        ]
+    }
+
+    pub fn to_exr(&self) -> Exr {
+        Exr::new(self.width, self.height, self.to_exr_channels(""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::tuples::colors;
+
+    fn read_i32(bytes: &[u8], at: usize) -> i32 {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(&bytes[at..at + 4]);
+        i32::from_le_bytes(buf)
+    }
+
+    fn read_f32(bytes: &[u8], at: usize) -> f32 {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(&bytes[at..at + 4]);
+        f32::from_le_bytes(buf)
+    }
+
+    #[test]
+    fn test_exr_magic_and_version() {
+        let bytes = Canvas::new(2, 2).to_exr().to_bytes();
+        assert_eq!(&bytes[..4], &[0x76, 0x2f, 0x31, 0x01]);
+        assert_eq!(&bytes[4..8], &[2, 0, 0, 0]);
+        assert_eq!(&bytes[8..17], b"channels\0");
+    }
+
+    #[test]
+    fn test_exr_channels_are_sorted() {
+        let exr = Canvas::new(1, 1).to_exr();
+        assert_eq!(exr.channel_names(), vec!["B", "G", "R"]);
+    }
+
+    /// Checks that the scanlines keep values above 1.0
+    #[test]
+    fn test_exr_scanline_data() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas[(1, 0)] = colors::new(4.5, 0.25, 0.0);
+        let bytes = canvas.to_exr().to_bytes();
+
+        // the last block holds the only scanline: y, size, then B, G and R
+        let block = bytes.len() - (8 + 3 * 2 * 4);
+        assert_eq!(read_i32(&bytes, block), 0);
+        assert_eq!(read_i32(&bytes, block + 4), 3 * 2 * 4);
+
+        let blues = block + 8;
+        let greens = blues + 8;
+        let reds = greens + 8;
+        assert_eq!(read_f32(&bytes, blues + 4), 0.0);
+        assert_eq!(read_f32(&bytes, greens + 4), 0.25);
+        assert_eq!(read_f32(&bytes, reds), 0.0);
+        assert_eq!(read_f32(&bytes, reds + 4), 4.5);
+
+        // the offset table points to the block
+        let mut offset = [0; 8];
+        offset.copy_from_slice(&bytes[block - 8..block]);
+        assert_eq!(u64::from_le_bytes(offset) as usize, block);
+    }
+}
@@ -0,0 +1,355 @@
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::canvas::{invalid_data, pixel_count, Canvas};
+use crate::math::Real;
+use crate::tuples::colors;
+use crate::tuples::colors::Color;
+
+/// A Radiance RGBE (.hdr) image. Unlike PPM, this keeps the full
+/// range of the colors: each pixel is stored as three 8-bit mantissas
+/// that share one 8-bit exponent.
+pub struct Hdr {
+    width: usize,
+    height: usize,
+    scanlines: Vec<Vec<[u8; 4]>>,
+}
+
+impl Hdr {
+    const HEADER: &'static str = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n";
+
+    /// Scanlines with widths outside of this range can't be run-length encoded
+    const MIN_RLE_WIDTH: usize = 8;
+    const MAX_RLE_WIDTH: usize = 0x7fff;
+
+    /// The longest literal run a single count byte can describe
+    const MAX_DUMP: usize = 128;
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Hdr::HEADER.as_bytes().to_vec();
+        bytes.extend(format!("-Y {} +X {}\n", self.height, self.width).as_bytes());
+
+        for scanline in self.scanlines.iter() {
+            if self.width < Hdr::MIN_RLE_WIDTH || self.width > Hdr::MAX_RLE_WIDTH {
+                scanline.iter().for_each(|rgbe| bytes.extend(rgbe));
+            } else {
+                Hdr::write_rle_scanline(scanline, &mut bytes);
+            }
+        }
+
+        bytes
+    }
+
+    /// Writes a scanline in the "new" RLE format. The components are stored
+    /// separately (all the reds, then all the greens, etc.). We only emit
+    /// literal dumps, which every reader understands and which avoids the
+    /// ambiguity of flat scanlines that happen to start with `2 2`.
+    fn write_rle_scanline(scanline: &[[u8; 4]], bytes: &mut Vec<u8>) {
+        let width = scanline.len();
+        bytes.extend(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+
+        for component in 0..4 {
+            let values: Vec<u8> = scanline.iter().map(|rgbe| rgbe[component]).collect();
+            for chunk in values.chunks(Hdr::MAX_DUMP) {
+                bytes.push(chunk.len() as u8);
+                bytes.extend(chunk);
+            }
+        }
+    }
+}
+
+/// Encodes a color into its shared-exponent representation
+pub fn to_rgbe(color: Color) -> [u8; 4] {
+    let max = Real::max(color.x, Real::max(color.y, color.z));
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let (mantissa, exponent) = frexp(max);
+    let scale = mantissa * 256.0 / max;
+    let component = |value: Real| (value.max(0.0) * scale) as u8;
+
+    [
+        component(color.x),
+        component(color.y),
+        component(color.z),
+        (exponent + 128) as u8,
+    ]
+}
+
+/// Decodes a shared-exponent color
+pub fn from_rgbe(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::black();
+    }
+    let factor = 2_f64.powi(rgbe[3] as i32 - (128 + 8));
+    colors::new(
+        rgbe[0] as Real * factor,
+        rgbe[1] as Real * factor,
+        rgbe[2] as Real * factor,
+    )
+}
+
+/// Splits a positive number into a mantissa in `[0.5, 1)` and a power of two
+fn frexp(value: Real) -> (Real, i32) {
+    let mut exponent = value.log2().floor() as i32 + 1;
+    let mut mantissa = value / 2_f64.powi(exponent);
+
+    // guard against rounding errors in log2
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+
+    (mantissa, exponent)
+}
+
+/// Reads one scanline, which can either be flat or run-length encoded
+fn read_scanline<R: Read>(read: &mut R, width: usize) -> io::Result<Vec<[u8; 4]>> {
+    let mut first = [0; 4];
+    read.read_exact(&mut first)?;
+
+    let is_rle = (Hdr::MIN_RLE_WIDTH..=Hdr::MAX_RLE_WIDTH).contains(&width)
+        && first[0] == 2
+        && first[1] == 2
+        && first[2] < 128;
+
+    if !is_rle {
+        let mut scanline = vec![first];
+        for _ in 1..width {
+            let mut rgbe = [0; 4];
+            read.read_exact(&mut rgbe)?;
+            scanline.push(rgbe);
+        }
+        return Ok(scanline);
+    }
+
+    if ((first[2] as usize) << 8 | first[3] as usize) != width {
+        return Err(invalid_data("Scanline width mismatch"));
+    }
+
+    let mut scanline = vec![[0; 4]; width];
+    for component in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0; 1];
+            read.read_exact(&mut count)?;
+            let count = count[0] as usize;
+
+            if count > 128 {
+                // a run of the same value
+                let run = count - 128;
+                let mut value = [0; 1];
+                read.read_exact(&mut value)?;
+                if x + run > width {
+                    return Err(invalid_data("Run exceeds scanline width"));
+                }
+                scanline[x..x + run]
+                    .iter_mut()
+                    .for_each(|rgbe| rgbe[component] = value[0]);
+                x += run;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err(invalid_data("Invalid literal dump"));
+                }
+                let mut values = vec![0; count];
+                read.read_exact(&mut values)?;
+                for (i, value) in values.into_iter().enumerate() {
+                    scanline[x + i][component] = value;
+                }
+                x += count;
+            }
+        }
+    }
+    Ok(scanline)
+}
+
+impl Canvas {
+    /// Reads a Radiance RGBE image. Only the standard `-Y h +X w` orientation
+    /// is supported.
+    pub fn from_hdr<R: Read>(read: R) -> io::Result<Canvas> {
+        let mut read = BufReader::new(read);
+        let mut line = String::new();
+
+        read.read_line(&mut line)?;
+        if !line.starts_with("#?") {
+            return Err(invalid_data("Not a Radiance HDR file"));
+        }
+
+        // the header ends with an empty line
+        loop {
+            line.clear();
+            if read.read_line(&mut line)? == 0 {
+                return Err(invalid_data("Unexpected end of HDR header"));
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+                return Err(invalid_data("Unsupported HDR pixel format"));
+            }
+        }
+
+        line.clear();
+        read.read_line(&mut line)?;
+        let resolution: Vec<_> = line.split_whitespace().collect();
+        let (height, width) = match resolution.as_slice() {
+            ["-Y", height, "+X", width] => (height.parse::<usize>(), width.parse::<usize>()),
+            _ => return Err(invalid_data("Unsupported HDR orientation")),
+        };
+        let (height, width) = match (height, width) {
+            (Ok(height), Ok(width)) => (height, width),
+            _ => return Err(invalid_data("Invalid HDR resolution")),
+        };
+
+        pixel_count(width, height)?;
+        let mut pixels = vec![];
+        // the scanlines of an image without columns are empty
+        let scanlines = if width == 0 { 0 } else { height };
+        for _ in 0..scanlines {
+            let scanline = read_scanline(&mut read, width)?;
+            pixels.extend(scanline.into_iter().map(from_rgbe));
+        }
+        Ok(Canvas {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn to_hdr(&self) -> Hdr {
+        // the rows of a canvas without columns are empty, but still there
+        if self.width == 0 {
+            return Hdr {
+                width: 0,
+                height: self.height,
+                scanlines: vec![vec![]; self.height],
+            };
+        }
+        let scanlines = self
+            .pixels
+            .chunks(self.width)
+            .map(|row| row.iter().map(|&color| to_rgbe(color)).collect())
+            .collect();
+
+        Hdr {
+            width: self.width,
+            height: self.height,
+            scanlines,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::hdr::{frexp, from_rgbe, to_rgbe};
+    use crate::canvas::Canvas;
+    use crate::tuples::colors;
+    use crate::tuples::colors::Color;
+
+    #[test]
+    fn test_frexp() {
+        assert_eq!(frexp(1.0), (0.5, 1));
+        assert_eq!(frexp(0.75), (0.75, 0));
+        assert_eq!(frexp(6.0), (0.75, 3));
+    }
+
+    #[test]
+    fn test_rgbe_encoding() {
+        assert_eq!(to_rgbe(Color::black()), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::white()), [128, 128, 128, 129]);
+
+        // values above 1.0 survive the encoding
+        assert_eq!(to_rgbe(colors::new(6.0, 3.0, 0.0)), [192, 96, 0, 131]);
+    }
+
+    #[test]
+    fn test_hdr_header() {
+        let canvas = Canvas::new(3, 2);
+        let bytes = canvas.to_hdr().to_bytes();
+        let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 3\n";
+        assert_eq!(&bytes[..header.len()], header.as_bytes());
+
+        // narrow images are written as flat scanlines
+        assert_eq!(bytes.len(), header.len() + 3 * 2 * 4);
+    }
+
+    #[test]
+    fn test_hdr_rle_scanlines() {
+        let mut canvas = Canvas::new(10, 1);
+        canvas[(0, 0)] = Color::white();
+        let bytes = canvas.to_hdr().to_bytes();
+        let header_len = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 10\n".len();
+        let data = &bytes[header_len..];
+
+        assert_eq!(&data[..4], &[2, 2, 0, 10]);
+
+        // one literal dump of ten values for each of the four components
+        assert_eq!(data.len(), 4 + 4 * 11);
+        assert_eq!(data[4], 10);
+        assert_eq!(data[5], 128);
+        assert_eq!(data[6], 0);
+    }
+
+    #[test]
+    fn test_rgbe_round_trip() {
+        let color = colors::new(6.0, 3.0, 0.5);
+        assert_eq!(from_rgbe(to_rgbe(color)), color);
+        assert_eq!(from_rgbe([0, 0, 0, 0]), Color::black());
+    }
+
+    #[test]
+    fn test_reading_hdr() {
+        for width in [3, 10] {
+            let mut canvas = Canvas::new(width, 2);
+            canvas[(0, 0)] = colors::new(8.0, 0.25, 1.0);
+            canvas[(width - 1, 1)] = Color::white();
+
+            let bytes = canvas.to_hdr().to_bytes();
+            let loaded = Canvas::from_hdr(bytes.as_slice()).unwrap();
+            assert_eq!(loaded.width, width);
+            assert_eq!(loaded.height, 2);
+            assert_eq!(loaded[(0, 0)], colors::new(8.0, 0.25, 1.0));
+            assert_eq!(loaded[(width - 1, 1)], Color::white());
+            assert_eq!(loaded[(1, 0)], Color::black());
+        }
+    }
+
+    #[test]
+    fn test_empty_hdr() {
+        for (width, height) in [(0, 3), (3, 0), (0, 0)] {
+            let bytes = Canvas::new(width, height).to_hdr().to_bytes();
+            let loaded = Canvas::from_hdr(bytes.as_slice()).unwrap();
+            assert_eq!((loaded.width, loaded.height), (width, height));
+        }
+    }
+
+    /// Runs of identical values are expanded
+    #[test]
+    fn test_reading_hdr_runs() {
+        let mut bytes = b"#?RADIANCE\n\n-Y 1 +X 8\n".to_vec();
+        bytes.extend(&[2, 2, 0, 8]);
+        bytes.extend(&[128 + 8, 128]); // red
+        bytes.extend(&[128 + 8, 0]); // green
+        bytes.extend(&[4, 0, 0, 0, 0, 128 + 4, 64]); // blue
+        bytes.extend(&[128 + 8, 129]); // exponent
+        let canvas = Canvas::from_hdr(bytes.as_slice()).unwrap();
+        assert_eq!(canvas[(0, 0)], colors::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas[(7, 0)], colors::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_reading_invalid_hdr() {
+        assert!(Canvas::from_hdr(b"P3 1 1 255 0 0 0" as &[u8]).is_err());
+
+        // a short file with a huge size fails without allocating the pixels
+        let huge = b"#?RADIANCE\n\n-Y 100000000 +X 100000000\n" as &[u8];
+        assert!(Canvas::from_hdr(huge).is_err());
+        let overflow = b"#?RADIANCE\n\n-Y 4294967296 +X 4294967296\n" as &[u8];
+        assert!(Canvas::from_hdr(overflow).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, ErrorKind, Read};
+use std::ops::{Index, IndexMut};
+use std::path::Path;
+
+use crate::math;
+use crate::math::Real;
+use crate::tuples::colors;
+use crate::tuples::colors::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![Color::black(); width * height],
+        }
+    }
+
+    pub fn to_ppm(&self) -> Ppm {
+        let header = format!(
+            "P3\n{} {}\n{}",
+            self.width,
+            self.height,
+            Ppm::MAX_COLOR_VALUE
+        );
+
+        fn row(chunk: &[Color]) -> Vec<String> {
+            let row: Vec<_> = chunk
+                .iter()
+                .map(|color| {
+                    let Color {
+                        x: r, y: g, z: b, ..
+                    } = color;
+                    let max_value = Ppm::MAX_COLOR_VALUE as i32;
+                    let r = math::scale_to(max_value, *r);
+                    let g = math::scale_to(max_value, *g);
+                    let b = math::scale_to(max_value, *b);
+                    format!("{} {} {}", r, g, b)
+                })
+                .collect();
+            Canvas::wrap(row.join(" "))
+        }
+
+        let data: Vec<_> = self.pixels.chunks(self.width).flat_map(row).collect();
+        Ppm::new(header, data)
+    }
+
+    /// Loads an image from a file. The format is determined by the file's
+    /// extension: `.hdr` for Radiance RGBE, and `.ppm` for plain or raw PPM.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Canvas> {
+        let path = path.as_ref();
+        let file = BufReader::new(File::open(path)?);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("hdr") => Canvas::from_hdr(file),
+            Some("ppm") => Canvas::from_ppm(file),
+            _ => Err(invalid_data("Unsupported image format")),
+        }
+    }
+
+    /// Reads a PPM image, either the plain (P3) or the raw (P6) variant.
+    /// The colors are normalized using the image's maximum color value.
+    pub fn from_ppm<R: Read>(mut read: R) -> io::Result<Canvas> {
+        let mut bytes = vec![];
+        read.read_to_end(&mut bytes)?;
+
+        let mut tokens = PpmTokens {
+            bytes: &bytes,
+            pos: 0,
+        };
+        let magic = tokens.next_token()?;
+        let (width, height) = (tokens.next_number()?, tokens.next_number()?);
+        let max_value = tokens.next_number()? as Real;
+
+        let count = pixel_count(width, height)?
+            .checked_mul(3)
+            .ok_or_else(|| invalid_data("Image too large"))?;
+        let values: Vec<Real> = match magic.as_str() {
+            "P3" => (0..count)
+                .map(|_| tokens.next_number().map(|n| n as Real))
+                .collect::<io::Result<_>>()?,
+            "P6" if max_value < 256.0 => {
+                // the raster starts right after the single whitespace that
+                // follows the maximum color value
+                let start = tokens.pos;
+                let raster = bytes
+                    .get(start..)
+                    .and_then(|raster| raster.get(..count))
+                    .ok_or_else(|| invalid_data("Unexpected end of PPM data"))?;
+                raster.iter().map(|&b| b as Real).collect()
+            }
+            _ => return Err(invalid_data("Unsupported PPM variant")),
+        };
+
+        let pixels = values
+            .chunks(3)
+            .map(|rgb| colors::new(rgb[0], rgb[1], rgb[2]) / max_value)
+            .collect();
+        Ok(Canvas {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Returns the bilinearly filtered color at the given texture coordinates.
+    /// `u` wraps around horizontally, while `v` is clamped, with `v = 0`
+    /// being the top row of the image.
+    pub fn sample(&self, u: Real, v: Real) -> Color {
+        let x = (u - u.floor()) * self.width as Real - 0.5;
+        let y = v.clamp(0.0, 1.0) * self.height as Real - 0.5;
+
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let wrap_x = |x: Real| (x as i64).rem_euclid(self.width as i64) as usize;
+        let clamp_y = |y: Real| (y.max(0.0) as usize).min(self.height - 1);
+        let (left, right) = (wrap_x(x0), wrap_x(x0 + 1.0));
+        let (top, bottom) = (clamp_y(y0), clamp_y(y0 + 1.0));
+
+        let upper = self[(left, top)] * (1.0 - fx) + self[(right, top)] * fx;
+        let lower = self[(left, bottom)] * (1.0 - fx) + self[(right, bottom)] * fx;
+        upper * (1.0 - fy) + lower * fy
+    }
+
+    fn wrap(row: String) -> Vec<String> {
+        row.split(" ").fold(vec![], |mut acc, next| {
+            let next_str = next.to_string();
+            match acc.last_mut() {
+                None => vec![next_str],
+                Some(last) => {
+                    if last.len() + next.len() > 69 {
+                        acc.push(next_str);
+                        acc
+                    } else {
+                        *last = last.to_string() + " " + &next;
+                        acc
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Splits the header (and the raster of plain PPMs) into whitespace-separated
+/// tokens, skipping comments.
+struct PpmTokens<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PpmTokens<'a> {
+    fn next_token(&mut self) -> io::Result<String> {
+        let mut token = String::new();
+        while self.pos < self.bytes.len() {
+            let byte = self.bytes[self.pos];
+            self.pos += 1;
+            if byte == b'#' && token.is_empty() {
+                // comments run until the end of the line
+                while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                if !token.is_empty() {
+                    break;
+                }
+            } else {
+                token.push(byte as char);
+            }
+        }
+        if token.is_empty() {
+            Err(invalid_data("Unexpected end of PPM data"))
+        } else {
+            Ok(token)
+        }
+    }
+
+    fn next_number(&mut self) -> io::Result<usize> {
+        self.next_token()?
+            .parse::<usize>()
+            .map_err(|_| invalid_data("Invalid number in PPM data"))
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// The number of pixels of an image read from a file. The readers don't
+/// allocate the pixels from the header's size, but fill them as they are
+/// read, so a corrupt header fails on the missing data instead.
+fn pixel_count(width: usize, height: usize) -> io::Result<usize> {
+    width
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("Image too large"))
+}
+
+pub struct Ppm {
+    header: String,
+    data: Vec<String>,
+}
+
+impl Ppm {
+    const MAX_COLOR_VALUE: u8 = 255;
+
+    fn new(header: String, data: Vec<String>) -> Ppm {
+        Ppm { header, data }
+    }
+}
+
+impl Index<math::Idx> for Canvas {
+    type Output = Color;
+
+    fn index(&self, index: math::Idx) -> &Self::Output {
+        let (x, y) = index;
+        &self.pixels[math::index_of(x, y, self.width)]
+    }
+}
+
+impl IndexMut<math::Idx> for Canvas {
+    fn index_mut(&mut self, index: math::Idx) -> &mut Self::Output {
+        let (x, y) = index;
+        &mut self.pixels[math::index_of(x, y, self.width)]
+    }
+}
+
+impl ToString for Ppm {
+    fn to_string(&self) -> String {
+        format!("{}\n{}\n", self.header, self.data.join("\n"))
+    }
+}
+
+pub mod compare;
+pub mod exr;
+pub mod hdr;
+pub mod tone;
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::tuples::colors;
+    use crate::tuples::colors::Color;
+
+    #[test]
+    fn test_canvas_creation() {
+        let canvas = Canvas::new(10, 20);
+        assert_eq!(canvas.width, 10);
+        assert_eq!(canvas.height, 20);
+        canvas
+            .pixels
+            .iter()
+            .for_each(|color| assert_eq!(*color, Color::black()));
+    }
+
+    #[test]
+    fn test_pixel_update() {
+        let mut canvas = Canvas::new(10, 20);
+        let red = Color::red();
+        canvas[(2, 3)] = red;
+        assert_eq!(canvas[(2, 3)], red);
+    }
+
+    #[test]
+    fn test_ppm_header() {
+        let canvas = Canvas::new(5, 3);
+        let ppm = canvas.to_ppm();
+        assert_eq!(ppm.header, "P3\n5 3\n255");
+    }
+
+    #[test]
+    fn test_ppm_pixel_data() {
+        let mut canvas = Canvas::new(5, 3);
+        let c1 = colors::new(1.5, 0.0, 0.0);
+        let c2 = colors::new(0.0, 0.5, 0.0);
+        let c3 = colors::new(-0.5, 0.0, 1.0);
+        canvas[(0, 0)] = c1;
+        canvas[(2, 1)] = c2;
+        canvas[(4, 2)] = c3;
+        let ppm = canvas.to_ppm();
+        assert_eq!(
+            ppm.data,
+            vec![
+                "255 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 128 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 0 0 0 0 0 0 0 255"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reading_plain_ppm() {
+        let data = b"P3
+            # a comment
+            2 1
+            255
+            255 0 0  0 51 255" as &[u8];
+        let canvas = Canvas::from_ppm(data).unwrap();
+        assert_eq!(canvas.width, 2);
+        assert_eq!(canvas.height, 1);
+        assert_eq!(canvas[(0, 0)], Color::red());
+        assert_eq!(canvas[(1, 0)], colors::new(0.0, 0.2, 1.0));
+    }
+
+    #[test]
+    fn test_reading_raw_ppm() {
+        let mut data = b"P6\n1 2\n255\n".to_vec();
+        data.extend(&[255, 255, 255, 0, 0, 255]);
+        let canvas = Canvas::from_ppm(data.as_slice()).unwrap();
+        assert_eq!(canvas[(0, 0)], Color::white());
+        assert_eq!(canvas[(0, 1)], colors::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_reading_truncated_ppm() {
+        let data = b"P3 2 1 255 255 0 0" as &[u8];
+        assert!(Canvas::from_ppm(data).is_err());
+    }
+
+    /// The size in the header isn't trusted until the pixels are read
+    #[test]
+    fn test_reading_ppm_with_a_huge_size() {
+        let data = b"P3 100000000 100000000 255 0 0 0" as &[u8];
+        assert!(Canvas::from_ppm(data).is_err());
+        let data = b"P6 4294967296 4294967296 255 \0\0\0" as &[u8];
+        assert!(Canvas::from_ppm(data).is_err());
+    }
+
+    #[test]
+    fn test_bilinear_sampling() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas[(0, 0)] = Color::black();
+        canvas[(1, 0)] = Color::white();
+
+        // pixel centers return the exact colors
+        assert_eq!(canvas.sample(0.25, 0.5), Color::black());
+        assert_eq!(canvas.sample(0.75, 0.5), Color::white());
+
+        // in between, the colors are blended. u wraps around.
+        assert_eq!(canvas.sample(0.5, 0.5), colors::new(0.5, 0.5, 0.5));
+        assert_eq!(canvas.sample(1.0, 0.0), colors::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_long_lines_wrapping() {
+        let mut canvas = Canvas::new(10, 2);
+        for i in 0..10 {
+            for j in 0..2 {
+                canvas[(i, j)] = colors::new(1.0, 0.8, 0.6);
+            }
+        }
+        let ppm = canvas.to_ppm();
+        assert_eq!(
+            ppm.data,
+            vec![
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153"
+            ]
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::canvas::Canvas;
+use crate::math::Real;
+use crate::tuples::colors;
+use crate::tuples::colors::Color;
+
+/// Operators that compress the unbounded radiance stored in a canvas
+/// into the 0..1 range expected by 8-bit outputs like PPM.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    /// Leaves the colors as they are. Anything above 1.0 gets clipped
+    /// once the canvas is scaled to 8 bits.
+    Clamp,
+
+    /// The simple Reinhard operator, `c / (1 + c)`, applied per channel
+    Reinhard,
+
+    /// Reinhard with a white point: colors at or above `white` map to 1.0
+    ReinhardExtended { white: Real },
+
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl ToneMap {
+    pub fn apply(&self, color: Color) -> Color {
+        let map = |f: &dyn Fn(Real) -> Real| colors::new(f(color.x), f(color.y), f(color.z));
+
+        match *self {
+            ToneMap::Clamp => color,
+            ToneMap::Reinhard => map(&|c| c / (1.0 + c)),
+            ToneMap::ReinhardExtended { white } => {
+                let white2 = white * white;
+                map(&|c| c * (1.0 + c / white2) / (1.0 + c))
+            }
+            ToneMap::Aces => map(&|c| {
+                let (a, b, c2, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                let mapped = (c * (a * c + b)) / (c * (c2 * c + d) + e);
+                mapped.clamp(0.0, 1.0)
+            }),
+        }
+    }
+}
+
+/// Scales a color by `2^stops`, the way a camera's exposure setting would
+pub fn expose(color: Color, stops: Real) -> Color {
+    color * 2_f64.powf(stops)
+}
+
+impl Canvas {
+    /// Returns a copy of this canvas with the exposure adjustment and
+    /// the tone mapping operator applied to every pixel. This is meant to
+    /// be called right before converting the canvas into an 8-bit format.
+    pub fn tone_map(&self, tone_map: ToneMap, exposure: Real) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for (target, source) in canvas.pixels.iter_mut().zip(self.pixels.iter()) {
+            *target = tone_map.apply(expose(*source, exposure));
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::tone::{expose, ToneMap};
+    use crate::canvas::Canvas;
+    use crate::tuples::colors;
+    use crate::tuples::colors::Color;
+
+    #[test]
+    fn test_clamp_keeps_colors() {
+        let color = colors::new(1.5, 0.5, 0.0);
+        assert_eq!(ToneMap::Clamp.apply(color), color);
+    }
+
+    #[test]
+    fn test_reinhard() {
+        let color = colors::new(1.0, 3.0, 0.0);
+        assert_eq!(ToneMap::Reinhard.apply(color), colors::new(0.5, 0.75, 0.0));
+    }
+
+    /// Colors at the white point should map to pure white
+    #[test]
+    fn test_reinhard_extended_white_point() {
+        let color = colors::new(4.0, 4.0, 4.0);
+        let mapped = ToneMap::ReinhardExtended { white: 4.0 }.apply(color);
+        assert_eq!(mapped, Color::white());
+    }
+
+    #[test]
+    fn test_aces_stays_in_range() {
+        let mapped = ToneMap::Aces.apply(colors::new(100.0, 0.0, 1.0));
+        assert_eq!(mapped.x, 1.0);
+        assert_eq!(mapped.y, 0.0);
+        assert_eq!(mapped.round_items().z, 0.8038);
+    }
+
+    #[test]
+    fn test_exposure_in_stops() {
+        let color = colors::new(0.25, 0.5, 1.0);
+        assert_eq!(expose(color, 1.0), colors::new(0.5, 1.0, 2.0));
+        assert_eq!(expose(color, -1.0), colors::new(0.125, 0.25, 0.5));
+    }
+
+    #[test]
+    fn test_tone_mapping_a_canvas() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas[(0, 0)] = colors::new(0.5, 0.5, 0.5);
+        canvas[(1, 0)] = colors::new(3.0, 1.0, 0.0);
+        let mapped = canvas.tone_map(ToneMap::Reinhard, 1.0);
+        assert_eq!(mapped[(0, 0)], colors::new(0.5, 0.5, 0.5));
+        assert_eq!(mapped[(1, 0)], colors::new(6.0 / 7.0, 2.0 / 3.0, 0.0));
+    }
+}
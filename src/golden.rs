@@ -0,0 +1,182 @@
+//! Golden image tests: small scenes are rendered and compared with the
+//! reference images in `resources/golden`. When an image differs, the
+//! rendering and a diff (differing pixels in red) are written to
+//! `target/golden`. After an intended change, update the references with
+//! `ERENA_BLESS=1 cargo test golden`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::canvas::tone::ToneMap;
+use crate::canvas::Canvas;
+use crate::materials::Material;
+use crate::math;
+use crate::math::random::RandGen;
+use crate::math::sampling::Sampler;
+use crate::math::Real;
+use crate::matrix::{view_transformation, CanTransform};
+use crate::patterns::Pattern;
+use crate::rays::lights::{AreaLight, PointLight};
+use crate::renderer;
+use crate::scene::camera::Camera;
+use crate::scene::{World, World3D};
+use crate::shapes::cylinders::CylLike;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::{colors, points, vectors};
+
+const REFERENCES: &str = "resources/golden";
+const FAILURES: &str = "target/golden";
+
+/// The largest difference of a color channel, for a pixel to be the same
+const TOLERANCE: Real = 2.0 / 255.0;
+
+/// The share of the pixels that may differ, for edges that move slightly
+const DIFFERING_PIXELS: Real = 0.005;
+
+const MIN_PSNR: Real = 40.0;
+
+const MIN_SSIM: Real = 0.98;
+
+/// Saves the image the way references are stored, as a plain PPM
+fn save(canvas: &Canvas, path: &Path) {
+    let ppm = canvas.tone_map(ToneMap::Clamp, 0.0).to_ppm().to_string();
+    fs::write(path, ppm).unwrap_or_else(|e| panic!("Can't write {}: {}", path.display(), e));
+}
+
+fn check(name: &str, canvas: Canvas) {
+    let reference = PathBuf::from(REFERENCES).join(format!("{}.ppm", name));
+    if std::env::var_os("ERENA_BLESS").is_some() {
+        fs::create_dir_all(REFERENCES).unwrap();
+        save(&canvas, &reference);
+        return;
+    }
+
+    let expected = Canvas::load(&reference).unwrap_or_else(|e| {
+        panic!(
+            "Can't load {} ({}), create it with ERENA_BLESS=1",
+            reference.display(),
+            e
+        )
+    });
+    // the rendering goes through the same 8 bits as the reference
+    let actual = Canvas::from_ppm(canvas.to_ppm().to_string().as_bytes()).unwrap();
+    let comparison = actual
+        .compare(&expected, TOLERANCE)
+        .unwrap_or_else(|| panic!("{}: the reference has a different size", name));
+
+    let allowed = (actual.width * actual.height) as Real * DIFFERING_PIXELS;
+    let passed = comparison.differing_pixels as Real <= allowed
+        && comparison.psnr >= MIN_PSNR
+        && comparison.ssim >= MIN_SSIM;
+    if !passed {
+        fs::create_dir_all(FAILURES).unwrap();
+        let failures = PathBuf::from(FAILURES);
+        save(&canvas, &failures.join(format!("{}.ppm", name)));
+        save(
+            &comparison.diff,
+            &failures.join(format!("{}.diff.ppm", name)),
+        );
+        panic!(
+            "{}: {} pixels differ (max {:.4}), PSNR {:.2} dB, SSIM {:.4}. See {}",
+            name,
+            comparison.differing_pixels,
+            comparison.max_difference,
+            comparison.psnr,
+            comparison.ssim,
+            failures.display()
+        );
+    }
+}
+
+fn looking_at_origin(width: usize, height: usize, from: (Real, Real, Real)) -> Camera {
+    let mut camera = Camera::new(width, height, math::PI / 3.0);
+    camera.transformation = view_transformation(
+        points::new(from.0, from.1, from.2),
+        points::new(0.0, 0.5, 0.0),
+        vectors::new(0.0, 1.0, 0.0),
+    );
+    camera
+}
+
+#[test]
+fn golden_default_world() {
+    let camera = looking_at_origin(48, 32, (0.0, 0.0, -5.0));
+    check("default_world", camera.render(World::default(), false));
+}
+
+#[test]
+fn golden_showcase() {
+    let (world, camera) = renderer::showcase(64, 40);
+    check("showcase", camera.render(world, true));
+}
+
+#[test]
+fn golden_primitives() {
+    let floor = Shape::plane().material(
+        Material::default()
+            .pattern(Pattern::checkers(
+                Color::white(),
+                colors::new(0.3, 0.3, 0.3),
+            ))
+            .reflective(0.1),
+    );
+    let torus = Shape::torus(0.6, 0.2)
+        .rotate_x(-0.6)
+        .translate(-1.2, 0.6, 0.0)
+        .material(Material::default().color(colors::new(0.9, 0.4, 0.1)));
+    let cone =
+        CylLike::cone()
+            .min(-1.0)
+            .max(0.0)
+            .closed(true)
+            .to_shape()
+            .translate(0.0, 1.0, 0.5)
+            .material(Material::default().pattern(
+                Pattern::stripe(colors::new(0.1, 0.3, 0.9), Color::white()).scale_all(0.2),
+            ));
+    let cube = Shape::cube()
+        .scale_all(0.4)
+        .rotate_y(0.5)
+        .translate(1.2, 0.4, 0.0)
+        .material(Material::default().color(colors::new(0.2, 0.8, 0.3)));
+    let glass = Shape::sphere()
+        .scale_all(0.3)
+        .translate(0.2, 0.3, -1.2)
+        .material(Material::glass());
+
+    let mut world = World::new(vec![floor, torus, cone, cube, glass], None);
+    world.add_point_light(PointLight::new(
+        points::new(-5.0, 8.0, -6.0),
+        Color::white(),
+    ));
+    let mut camera = looking_at_origin(64, 40, (0.0, 2.0, -5.0));
+    camera.sampler = Sampler::Sobol;
+    check("primitives", camera.render(world, true));
+}
+
+#[test]
+fn golden_soft_shadows_and_depth_of_field() {
+    let mut world = World3D::default();
+    world.add_object(&Shape::plane().translate(0.0, -1.0, 0.0));
+    world.light = Some(
+        AreaLight::new(
+            points::new(-6.0, 8.0, -6.0),
+            vectors::new(3.0, 0.0, 0.0),
+            3,
+            vectors::new(0.0, 0.0, 3.0),
+            3,
+            Color::white(),
+            RandGen::seeded(1),
+        )
+        .sampler(Sampler::Halton),
+    );
+    let mut camera = looking_at_origin(48, 32, (0.0, 1.0, -5.0));
+    camera.sampler = Sampler::Halton;
+    camera.aperture = 0.15;
+    camera.focal_distance = 5.0;
+    check(
+        "soft_shadows_and_depth_of_field",
+        camera.render(world, false),
+    );
+}
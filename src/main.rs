@@ -1,6 +1,10 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+#[cfg(test)]
+mod benches;
 mod canvas;
+#[cfg(test)]
+mod golden;
 mod materials;
 mod math;
 mod matrix;
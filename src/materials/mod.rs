@@ -1,11 +1,26 @@
 use crate::math::Real;
 use crate::patterns::Pattern;
 use crate::rays::lights::{AreaLight, PointLight};
+use crate::shapes::uvs::UvMapping;
 use crate::shapes::Object;
 use crate::tuples::colors::Color;
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
 
+/// The terms of the Phong model at a point
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Lighting {
+    pub ambient: Color,
+    pub diffuse: Color,
+    pub specular: Color,
+}
+
+impl Lighting {
+    pub fn total(&self) -> Color {
+        self.ambient + self.diffuse + self.specular
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Material {
     pub color: Color,
@@ -34,7 +49,7 @@ impl Material {
         }
     }
 
-    pub fn lighting<S>(
+    pub fn lighting<S: UvMapping>(
         &self,
         object: &Object<S>,
         light: AreaLight,
@@ -43,10 +58,21 @@ impl Material {
         normal_vec: Vector,
         intensity: Real,
     ) -> Color {
-        let color = match &self.pattern {
-            None => self.color,
-            Some(pattern) => pattern.at_object(object, point),
-        };
+        self.lighting_terms(object, light, point, eye_vec, normal_vec, intensity)
+            .total()
+    }
+
+    /// Like `lighting`, but with the terms of the Phong model kept apart
+    pub fn lighting_terms<S: UvMapping>(
+        &self,
+        object: &Object<S>,
+        light: AreaLight,
+        point: Point,
+        eye_vec: Vector,
+        normal_vec: Vector,
+        intensity: Real,
+    ) -> Lighting {
+        let color = self.color_at(object, point);
 
         let in_shadow = intensity == 0.0;
 
@@ -55,44 +81,55 @@ impl Material {
 
         let ambient = effective_color * self.ambient;
 
-        let mut sum = Color::black();
+        let mut diffuse_sum = Color::black();
+        let mut specular_sum = Color::black();
 
-        for v in 0..light.v_steps {
-            for u in 0..light.u_steps {
-                let light_position = light.point_on_light(u, v);
+        for light_position in light.sample_points() {
+            // direction of the light source
+            let light_vec = (light_position - point).normalize();
 
-                // direction of the light source
-                let light_vec = (light_position - point).normalize();
+            // compute the cosine of the angle between the light vector and the normal vector.
+            let light_dot_normal = light_vec.dot(normal_vec);
 
-                // compute the cosine of the angle between the light vector and the normal vector.
-                let light_dot_normal = light_vec.dot(normal_vec);
+            // if the cosine is negative, the light is on the other side of the surface
+            if light_dot_normal >= 0.0 && !in_shadow {
+                let diffuse = effective_color * self.diffuse * light_dot_normal;
 
-                // if the cosine is negative, the light is on the other side of the surface
-                if light_dot_normal >= 0.0 && !in_shadow {
-                    let diffuse = effective_color * self.diffuse * light_dot_normal;
+                let reflect_vec = -light_vec.reflect(normal_vec);
 
-                    let reflect_vec = -light_vec.reflect(normal_vec);
+                // compute the cosine of the angle between the reflection vector and the eye vector
+                let reflect_dot_eye = reflect_vec.dot(eye_vec);
 
-                    // compute the cosine of the angle between the reflection vector and the eye vector
-                    let reflect_dot_eye = reflect_vec.dot(eye_vec);
+                // if the cosine is negative, the light reflects away from the eye
+                let specular = if reflect_dot_eye <= 0.0 {
+                    Color::black()
+                } else {
+                    let factor = reflect_dot_eye.powf(self.shininess);
+                    light.intensity * self.specular * factor
+                };
 
-                    // if the cosine is negative, the light reflects away from the eye
-                    let specular = if reflect_dot_eye <= 0.0 {
-                        Color::black()
-                    } else {
-                        let factor = reflect_dot_eye.powf(self.shininess);
-                        light.intensity * self.specular * factor
-                    };
+                diffuse_sum = diffuse_sum + diffuse;
+                specular_sum = specular_sum + specular;
+            };
+        }
 
-                    sum = sum + diffuse + specular;
-                };
-            }
+        let samples = light.get_samples() as Real;
+        Lighting {
+            ambient,
+            diffuse: (diffuse_sum / samples) * intensity,
+            specular: (specular_sum / samples) * intensity,
         }
+    }
 
-        ambient + (sum / (light.get_samples() as Real)) * intensity
+    /// The surface color at the given world point, taking the pattern into account
+    pub fn color_at<S: UvMapping>(&self, object: &Object<S>, point: Point) -> Color {
+        match &self.pattern {
+            None => self.color,
+            Some(pattern) => pattern.at_object(object, point),
+        }
     }
 
-    pub fn pl_lighting<S>(
+    pub fn pl_lighting<S: UvMapping>(
         &self,
         object: &Object<S>,
         light: PointLight,
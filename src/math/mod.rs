@@ -1,4 +1,6 @@
+pub mod polynomials;
 pub mod random;
+pub mod sampling;
 
 use std::cmp::Ordering;
 
@@ -0,0 +1,224 @@
+//! Real roots of low-degree polynomials. The coefficients are given from
+//! the constant term up, so `[c0, c1, c2]` means `c0 + c1 x + c2 x^2`.
+//! The cubic and quartic solvers follow the closed forms of Jochen
+//! Schwarze's "Cubic and Quartic Roots" (Graphics Gems I), and the quartic
+//! roots are refined with a few Newton iterations, since the closed form
+//! loses a lot of precision.
+
+use crate::math::{Real, PI};
+
+const ZERO: Real = 1e-9;
+
+fn is_zero(value: Real) -> bool {
+    value.abs() < ZERO
+}
+
+pub fn solve_quadratic(coefficients: [Real; 3]) -> Vec<Real> {
+    let [c0, c1, c2] = coefficients;
+
+    // normal form: x^2 + 2px + q = 0
+    let p = c1 / (2.0 * c2);
+    let q = c0 / c2;
+    let discriminant = p * p - q;
+
+    if is_zero(discriminant) {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        vec![]
+    } else {
+        let root = discriminant.sqrt();
+        vec![root - p, -root - p]
+    }
+}
+
+pub fn solve_cubic(coefficients: [Real; 4]) -> Vec<Real> {
+    let [c0, c1, c2, c3] = coefficients;
+
+    // normal form: x^3 + Ax^2 + Bx + C = 0
+    let a = c2 / c3;
+    let b = c1 / c3;
+    let c = c0 / c3;
+
+    // substitute x = y - A/3 to eliminate the quadratic term: y^3 + 3py + 2q = 0
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+
+    let sub = a / 3.0;
+    roots.into_iter().map(|root| root - sub).collect()
+}
+
+pub fn solve_quartic(coefficients: [Real; 5]) -> Vec<Real> {
+    let [c0, c1, c2, c3, c4] = coefficients;
+
+    // normal form: x^4 + Ax^3 + Bx^2 + Cx + D = 0
+    let a = c3 / c4;
+    let b = c2 / c4;
+    let c = c1 / c4;
+    let d = c0 / c4;
+
+    // substitute x = y - A/4 to eliminate the cubic term: y^4 + py^2 + qy + r = 0
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        // no absolute term: y(y^3 + py + q) = 0
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+        roots
+    } else {
+        // solve the resolvent cubic, and use one of its roots to split
+        // the quartic into two quadratics
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if is_zero(u) {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if is_zero(v) {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+
+        let (v1, v2) = if q < 0.0 { (-v, v) } else { (v, -v) };
+        let mut roots = solve_quadratic([z - u, v1, 1.0]);
+        roots.extend(solve_quadratic([z + u, v2, 1.0]));
+        roots
+    };
+
+    let sub = a / 4.0;
+    roots
+        .into_iter()
+        .map(|root| polish(&coefficients, root - sub))
+        .collect()
+}
+
+/// Solves a polynomial of degree up to 4, ignoring leading coefficients
+/// that are (nearly) zero
+pub fn solve(coefficients: &[Real]) -> Vec<Real> {
+    let degree = coefficients.iter().rposition(|c| !is_zero(*c)).unwrap_or(0);
+    let c = coefficients;
+    match degree {
+        0 => vec![],
+        1 => vec![-c[0] / c[1]],
+        2 => solve_quadratic([c[0], c[1], c[2]]),
+        3 => solve_cubic([c[0], c[1], c[2], c[3]]),
+        4 => solve_quartic([c[0], c[1], c[2], c[3], c[4]]),
+        _ => panic!("Polynomials of degree {} are not supported", degree),
+    }
+}
+
+/// Evaluates the polynomial using Horner's method
+pub fn evaluate(coefficients: &[Real], x: Real) -> Real {
+    coefficients.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+}
+
+fn derivative(coefficients: &[Real], x: Real) -> Real {
+    coefficients
+        .iter()
+        .enumerate()
+        .skip(1)
+        .rev()
+        .fold(0.0, |acc, (i, &c)| acc * x + c * i as Real)
+}
+
+/// Refines a root with Newton's method
+fn polish(coefficients: &[Real], root: Real) -> Real {
+    let mut root = root;
+    for _ in 0..4 {
+        let slope = derivative(coefficients, root);
+        if slope == 0.0 {
+            break;
+        }
+        root -= evaluate(coefficients, root) / slope;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math;
+    use crate::math::polynomials::{solve, solve_cubic, solve_quadratic, solve_quartic};
+    use crate::math::Real;
+
+    fn sorted(mut roots: Vec<Real>) -> Vec<Real> {
+        roots.sort_by(|a, b| math::order_reals(*a, *b));
+        roots.into_iter().map(|root| math::round(root, 5)).collect()
+    }
+
+    #[test]
+    fn test_quadratic_roots() {
+        // (x - 1)(x + 3)
+        assert_eq!(sorted(solve_quadratic([-3.0, 2.0, 1.0])), vec![-3.0, 1.0]);
+        assert_eq!(solve_quadratic([1.0, 0.0, 1.0]), vec![]);
+    }
+
+    #[test]
+    fn test_cubic_roots() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_eq!(
+            sorted(solve_cubic([6.0, -7.0, 0.0, 1.0])),
+            vec![-3.0, 1.0, 2.0]
+        );
+        // (x - 2)(x^2 + 1)
+        assert_eq!(sorted(solve_cubic([-2.0, 1.0, -2.0, 1.0])), vec![2.0]);
+    }
+
+    #[test]
+    fn test_quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_eq!(
+            sorted(solve_quartic([24.0, -50.0, 35.0, -10.0, 1.0])),
+            vec![1.0, 2.0, 3.0, 4.0]
+        );
+        // (x^2 - 4)(x^2 + 1)
+        assert_eq!(
+            sorted(solve_quartic([-4.0, 0.0, -3.0, 0.0, 1.0])),
+            vec![-2.0, 2.0]
+        );
+        // x^4 + 1
+        assert_eq!(solve_quartic([1.0, 0.0, 0.0, 0.0, 1.0]), vec![]);
+    }
+
+    #[test]
+    fn test_degenerate_polynomials() {
+        // 2x - 4, with vanishing higher terms
+        assert_eq!(solve(&[-4.0, 2.0, 0.0, 1e-12, 0.0]), vec![2.0]);
+        assert_eq!(sorted(solve(&[-4.0, 0.0, 1.0, 0.0])), vec![-2.0, 2.0]);
+        assert_eq!(solve(&[3.0, 0.0]), vec![]);
+    }
+}
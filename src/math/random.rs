@@ -4,9 +4,15 @@ use std::cell::Cell;
 pub enum RandGen {
     Seq(SeqRand),
     Live,
+    Pcg(Pcg),
 }
 
 impl RandGen {
+    /// A reproducible generator, see `Pcg`
+    pub fn seeded(seed: u64) -> RandGen {
+        RandGen::Pcg(Pcg::new(seed, 0))
+    }
+
     pub fn next(&self) -> f64 {
         match self {
             RandGen::Seq(seq_rand) => {
@@ -15,10 +21,71 @@ impl RandGen {
                 seq_rand.maybe_next().expect("Can't fetch next value")
             }
             RandGen::Live => rand::random(),
+            RandGen::Pcg(pcg) => pcg.next_real(),
+        }
+    }
+
+    /// Starts the numbers over, from the stream with the given index.
+    /// Seeded generators give the same numbers for the same stream, no
+    /// matter which streams were used before, so each pixel (or sample)
+    /// can have its own stream. The other generators carry on as before.
+    pub fn restart(&self, stream: u64) {
+        if let RandGen::Pcg(pcg) = self {
+            pcg.restart(stream)
         }
     }
 }
 
+/// A PCG32 generator (XSH-RR output on a 64 bit LCG), see
+/// https://www.pcg-random.org. The seed picks the sequence, and the
+/// stream an independent part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pcg {
+    seed: u64,
+    state: Cell<u64>,
+    increment: Cell<u64>,
+}
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+impl Pcg {
+    pub fn new(seed: u64, stream: u64) -> Pcg {
+        let pcg = Pcg {
+            seed,
+            state: Cell::new(0),
+            increment: Cell::new(0),
+        };
+        pcg.restart(stream);
+        pcg
+    }
+
+    pub fn restart(&self, stream: u64) {
+        // the increment has to be odd
+        self.increment.set((stream << 1) | 1);
+        self.state.set(0);
+        self.next_u32();
+        self.state.set(self.state.get().wrapping_add(self.seed));
+        self.next_u32();
+    }
+
+    pub fn next_u32(&self) -> u32 {
+        let state = self.state.get();
+        self.state.set(
+            state
+                .wrapping_mul(PCG_MULTIPLIER)
+                .wrapping_add(self.increment.get()),
+        );
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    /// A number in [0, 1)
+    pub fn next_real(&self) -> f64 {
+        self.next_u32() as f64 / (1u64 << 32) as f64
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SeqRand {
     seq: Vec<f64>,
@@ -45,9 +112,17 @@ impl SeqRand {
     }
 }
 
+/// Shuffles the values, drawing the swaps from the generator
+pub fn shuffle<T>(values: &mut [T], gen: &RandGen) {
+    for i in (1..values.len()).rev() {
+        let j = ((gen.next() * (i + 1) as f64) as usize).min(i);
+        values.swap(i, j);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::math::random::{RandGen, SeqRand};
+    use crate::math::random::{shuffle, Pcg, RandGen, SeqRand};
 
     /// Checks that a random number generator returns
     /// a cyclic sequence of numbers
@@ -59,4 +134,40 @@ mod tests {
         assert_eq!(gen.next(), 1.0);
         assert_eq!(gen.next(), 0.1);
     }
+
+    #[test]
+    fn test_pcg_reference_values() {
+        // the first outputs of the reference implementation's demo,
+        // seeded with 42 on stream 54
+        let pcg = Pcg::new(42, 54);
+        let values: Vec<u32> = (0..6).map(|_| pcg.next_u32()).collect();
+        assert_eq!(
+            values,
+            vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn test_restarting_a_seeded_generator() {
+        let gen = RandGen::seeded(7);
+        gen.restart(3);
+        let first: Vec<f64> = (0..4).map(|_| gen.next()).collect();
+        gen.restart(4);
+        let other: Vec<f64> = (0..4).map(|_| gen.next()).collect();
+        gen.restart(3);
+        let again: Vec<f64> = (0..4).map(|_| gen.next()).collect();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert!(first.iter().all(|&x| (0.0..1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_shuffling() {
+        let mut values: Vec<usize> = (0..10).collect();
+        shuffle(&mut values, &RandGen::seeded(1));
+        assert_ne!(values, (0..10).collect::<Vec<_>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+    }
 }
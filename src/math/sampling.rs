@@ -0,0 +1,171 @@
+use crate::math::random::RandGen;
+use crate::math::Real;
+
+/// How the samples of a pixel, a lens or an area light are spread over
+/// the unit square
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Sampler {
+    /// Independent uniform samples
+    Random,
+
+    /// One jittered sample in each cell of the grid
+    #[default]
+    Stratified,
+
+    /// The Halton sequence in bases 2 and 3, shifted by a random offset
+    Halton,
+
+    /// The first two dimensions of the Sobol sequence, scrambled with
+    /// random bits
+    Sobol,
+}
+
+impl Sampler {
+    /// All the samples for a grid of `columns` x `rows` cells, in the unit
+    /// square. The numbers drawn from `gen` jitter or scramble the whole
+    /// pattern, so each pixel should use its own stream. Stratified
+    /// samples come row by row, the others only fill the square evenly
+    /// as a whole.
+    pub fn pattern(&self, (columns, rows): (usize, usize), gen: &RandGen) -> Vec<(Real, Real)> {
+        let count = columns * rows;
+        match self {
+            Sampler::Random => (0..count).map(|_| (gen.next(), gen.next())).collect(),
+            Sampler::Stratified => (0..count)
+                .map(|i| {
+                    let (col, row) = (i % columns, i / columns);
+                    let u = (col as Real + gen.next()) / columns as Real;
+                    let v = (row as Real + gen.next()) / rows as Real;
+                    (u, v)
+                })
+                .collect(),
+            Sampler::Halton => {
+                // a Cranley-Patterson rotation keeps the spacing of the points
+                let offset = (gen.next(), gen.next());
+                (0..count)
+                    .map(|i| {
+                        let u = radical_inverse(2, i) + offset.0;
+                        let v = radical_inverse(3, i) + offset.1;
+                        (u.fract(), v.fract())
+                    })
+                    .collect()
+            }
+            Sampler::Sobol => {
+                let scramble = (random_bits(gen), random_bits(gen));
+                (0..count as u32)
+                    .map(|i| {
+                        let u = i.reverse_bits() ^ scramble.0;
+                        let v = sobol_second(i) ^ scramble.1;
+                        (to_unit(u), to_unit(v))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Mirrors the digits of `index` in the given base around the point
+pub(crate) fn radical_inverse(base: usize, mut index: usize) -> Real {
+    let mut result = 0.0;
+    let mut scale = 1.0 / base as Real;
+    while index > 0 {
+        result += (index % base) as Real * scale;
+        index /= base;
+        scale /= base as Real;
+    }
+    result
+}
+
+/// The second dimension of the Sobol sequence, where each bit of the
+/// index flips a direction number
+fn sobol_second(mut index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1 << 31;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn random_bits(gen: &RandGen) -> u32 {
+    (gen.next() * (1u64 << 32) as Real) as u32
+}
+
+fn to_unit(bits: u32) -> Real {
+    bits as Real / (1u64 << 32) as Real
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::random::{RandGen, SeqRand};
+    use crate::math::sampling::{radical_inverse, Sampler};
+    use crate::math::Real;
+
+    /// Counts the samples in each cell of a grid of the given size
+    fn cells(samples: &[(Real, Real)], size: usize) -> Vec<usize> {
+        let mut counts = vec![0; size * size];
+        for (u, v) in samples {
+            let (col, row) = ((u * size as Real) as usize, (v * size as Real) as usize);
+            counts[row * size + col] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_radical_inverse() {
+        let base2: Vec<Real> = (0..5).map(|i| radical_inverse(2, i)).collect();
+        assert_eq!(base2, vec![0.0, 0.5, 0.25, 0.75, 0.125]);
+        assert_eq!(radical_inverse(3, 5), 2.0 / 3.0 + 1.0 / 9.0);
+    }
+
+    #[test]
+    fn test_stratified_samples_are_jittered_in_their_cells() {
+        let gen = RandGen::Seq(SeqRand::new(vec![0.3, 0.7]));
+        let samples = Sampler::Stratified.pattern((2, 2), &gen);
+        assert_eq!(
+            samples,
+            vec![(0.15, 0.35), (0.65, 0.35), (0.15, 0.85), (0.65, 0.85)]
+        );
+    }
+
+    #[test]
+    fn test_low_discrepancy_samples_fill_every_cell() {
+        for sampler in [Sampler::Stratified, Sampler::Halton, Sampler::Sobol] {
+            let gen = RandGen::seeded(5);
+            let samples = sampler.pattern((4, 4), &gen);
+            assert_eq!(samples.len(), 16);
+            assert!(samples
+                .iter()
+                .all(|&(u, v)| (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v)));
+
+            // Halton and Sobol samples have one sample in each of 16 columns
+            if sampler != Sampler::Stratified {
+                let columns: Vec<_> = samples.iter().map(|&(u, _)| (u, 0.0)).collect();
+                assert!(cells(&columns, 16)[..16].iter().all(|&count| count == 1));
+            }
+            // Sobol's (0, 2) sequence is stratified on the 4 x 4 grid as well
+            if sampler != Sampler::Halton {
+                assert!(cells(&samples, 4).iter().all(|&count| count == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_seeded_patterns_are_reproducible() {
+        for sampler in [
+            Sampler::Random,
+            Sampler::Stratified,
+            Sampler::Halton,
+            Sampler::Sobol,
+        ] {
+            let first = sampler.pattern((3, 3), &RandGen::seeded(11));
+            let second = sampler.pattern((3, 3), &RandGen::seeded(11));
+            let other = sampler.pattern((3, 3), &RandGen::seeded(12));
+            assert_eq!(first, second);
+            assert_ne!(first, other);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::ops::{Index, IndexMut, Mul};
+
+use crate::math;
+use crate::math::Real;
+use crate::matrix::{Matrix, SingularMatrix};
+use crate::tuples::TupleLike;
+
+/// A 4 x 4 matrix stored inline, without any heap allocation.
+/// This is the type used for all the transformations, since it can be
+/// freely copied and its inverse has a closed form.
+#[derive(Debug, Copy, Clone)]
+pub struct Matrix44 {
+    rows: [[Real; 4]; 4],
+}
+
+impl Matrix44 {
+    #[rustfmt::skip]
+    pub(crate) fn new(elements: &[Real; 16]) -> Matrix44 {
+        let e = elements;
+        Matrix44::from_rows([
+            [e[0], e[1], e[2], e[3]],
+            [e[4], e[5], e[6], e[7]],
+            [e[8], e[9], e[10], e[11]],
+            [e[12], e[13], e[14], e[15]],
+        ])
+    }
+
+    pub(crate) fn from_rows(rows: [[Real; 4]; 4]) -> Matrix44 {
+        Matrix44 { rows }
+    }
+
+    #[rustfmt::skip]
+    pub(crate) fn id() -> Matrix44 {
+        Matrix44::new(&[
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    pub(crate) fn transpose(&self) -> Matrix44 {
+        let mut matrix = Matrix44::id();
+        for r in 0..4 {
+            for c in 0..4 {
+                matrix.rows[r][c] = self.rows[c][r];
+            }
+        }
+        matrix
+    }
+
+    /// Expands the determinant in terms of the 2 x 2 minors of the top and
+    /// bottom halves of the matrix. The same minors are shared with `inverse`.
+    fn minors(&self) -> ([Real; 6], [Real; 6]) {
+        let [a0, a1, a2, a3] = self.rows;
+        let top = [
+            a0[0] * a1[1] - a1[0] * a0[1],
+            a0[0] * a1[2] - a1[0] * a0[2],
+            a0[0] * a1[3] - a1[0] * a0[3],
+            a0[1] * a1[2] - a1[1] * a0[2],
+            a0[1] * a1[3] - a1[1] * a0[3],
+            a0[2] * a1[3] - a1[2] * a0[3],
+        ];
+        let bottom = [
+            a2[0] * a3[1] - a3[0] * a2[1],
+            a2[0] * a3[2] - a3[0] * a2[2],
+            a2[0] * a3[3] - a3[0] * a2[3],
+            a2[1] * a3[2] - a3[1] * a2[2],
+            a2[1] * a3[3] - a3[1] * a2[3],
+            a2[2] * a3[3] - a3[2] * a2[3],
+        ];
+        (top, bottom)
+    }
+
+    pub(crate) fn determinant(&self) -> Real {
+        let (s, c) = self.minors();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// Computes the inverse in closed form. Returns `None` if the
+    /// matrix is singular.
+    #[rustfmt::skip]
+    pub fn inverse(&self) -> Option<Matrix44> {
+        let (s, c) = self.minors();
+        let determinant =
+            s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let [a0, a1, a2, a3] = self.rows;
+        let inverse = Matrix44::new(&[
+            a1[1] * c[5] - a1[2] * c[4] + a1[3] * c[3],
+            -a0[1] * c[5] + a0[2] * c[4] - a0[3] * c[3],
+            a3[1] * s[5] - a3[2] * s[4] + a3[3] * s[3],
+            -a2[1] * s[5] + a2[2] * s[4] - a2[3] * s[3],
+
+            -a1[0] * c[5] + a1[2] * c[2] - a1[3] * c[1],
+            a0[0] * c[5] - a0[2] * c[2] + a0[3] * c[1],
+            -a3[0] * s[5] + a3[2] * s[2] - a3[3] * s[1],
+            a2[0] * s[5] - a2[2] * s[2] + a2[3] * s[1],
+
+            a1[0] * c[4] - a1[1] * c[2] + a1[3] * c[0],
+            -a0[0] * c[4] + a0[1] * c[2] - a0[3] * c[0],
+            a3[0] * s[4] - a3[1] * s[2] + a3[3] * s[0],
+            -a2[0] * s[4] + a2[1] * s[2] - a2[3] * s[0],
+
+            -a1[0] * c[3] + a1[1] * c[1] - a1[2] * c[0],
+            a0[0] * c[3] - a0[1] * c[1] + a0[2] * c[0],
+            -a3[0] * s[3] + a3[1] * s[1] - a3[2] * s[0],
+            a2[0] * s[3] - a2[1] * s[1] + a2[2] * s[0],
+        ]);
+        Some(inverse.map(|e| e / determinant))
+    }
+
+    pub fn try_inverse(&self) -> Result<Matrix44, SingularMatrix> {
+        self.inverse()
+            .ok_or_else(|| SingularMatrix(Box::new(*self)))
+    }
+
+    pub fn inverse_or_id(&self) -> Matrix44 {
+        self.inverse().unwrap_or_else(Matrix44::id)
+    }
+
+    fn map<F: Fn(Real) -> Real>(&self, f: F) -> Matrix44 {
+        Matrix44::from_rows(self.rows.map(|row| row.map(&f)))
+    }
+
+    /// This is used mainly for testing purposes
+    pub(crate) fn round_items(&self, limit: u32) -> Matrix44 {
+        self.map(|e| math::round(e, limit))
+    }
+
+    fn mul_array(&self, tuple: [Real; 4]) -> [Real; 4] {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::mul_array(&self.rows, tuple)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            self.rows.map(|row| {
+                row[0] * tuple[0] + row[1] * tuple[1] + row[2] * tuple[2] + row[3] * tuple[3]
+            })
+        }
+    }
+}
+
+/// SSE2 (always available on x86_64) version of the matrix-tuple product
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd {
+    use crate::math::Real;
+    use std::arch::x86_64::*;
+
+    pub(super) fn mul_array(rows: &[[Real; 4]; 4], tuple: [Real; 4]) -> [Real; 4] {
+        // Safety: SSE2 is part of the x86_64 baseline, and the unaligned loads
+        // only read within the bounds of the 4-element arrays.
+        unsafe {
+            let xy = _mm_loadu_pd(tuple.as_ptr());
+            let zw = _mm_loadu_pd(tuple.as_ptr().add(2));
+            rows.map(|row| {
+                let low = _mm_mul_pd(_mm_loadu_pd(row.as_ptr()), xy);
+                let high = _mm_mul_pd(_mm_loadu_pd(row.as_ptr().add(2)), zw);
+                let sum = _mm_add_pd(low, high);
+                _mm_cvtsd_f64(_mm_add_sd(sum, _mm_unpackhi_pd(sum, sum)))
+            })
+        }
+    }
+}
+
+impl Index<math::Idx> for Matrix44 {
+    type Output = Real;
+
+    fn index(&self, (row, col): math::Idx) -> &Self::Output {
+        &self.rows[row][col]
+    }
+}
+
+impl IndexMut<math::Idx> for Matrix44 {
+    fn index_mut(&mut self, (row, col): math::Idx) -> &mut Self::Output {
+        &mut self.rows[row][col]
+    }
+}
+
+impl PartialEq for Matrix44 {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows
+            .iter()
+            .flatten()
+            .zip(other.rows.iter().flatten())
+            .all(|(&a, &b)| math::compare_reals(a, b))
+    }
+}
+
+impl Mul for Matrix44 {
+    type Output = Matrix44;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut matrix = Matrix44::id();
+        for r in 0..4 {
+            for c in 0..4 {
+                matrix.rows[r][c] = (0..4).map(|i| self.rows[r][i] * other.rows[i][c]).sum();
+            }
+        }
+        matrix
+    }
+}
+
+impl<T> Mul<TupleLike<T>> for Matrix44 {
+    type Output = TupleLike<T>;
+
+    fn mul(self, rhs: TupleLike<T>) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl<T> Mul<TupleLike<T>> for &Matrix44 {
+    type Output = TupleLike<T>;
+
+    fn mul(self, rhs: TupleLike<T>) -> Self::Output {
+        TupleLike::from_array(&self.mul_array([rhs.x, rhs.y, rhs.z, rhs.w]))
+    }
+}
+
+impl<T: Copy> Mul<&TupleLike<T>> for Matrix44 {
+    type Output = TupleLike<T>;
+
+    fn mul(self, rhs: &TupleLike<T>) -> Self::Output {
+        self * *rhs
+    }
+}
+
+impl From<Matrix44> for Matrix {
+    fn from(matrix: Matrix44) -> Self {
+        let elements: Vec<Real> = matrix.rows.iter().flatten().copied().collect();
+        Matrix::new_nxn(4, &elements)
+    }
+}
+
+impl From<&Matrix> for Matrix44 {
+    fn from(matrix: &Matrix) -> Self {
+        let mut matrix44 = Matrix44::id();
+        for r in 0..4 {
+            for c in 0..4 {
+                matrix44.rows[r][c] = matrix[(r, c)];
+            }
+        }
+        matrix44
+    }
+}
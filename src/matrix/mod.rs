@@ -1,5 +1,9 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut, Mul};
 
+pub use matrix44::Matrix44;
 pub use transformations::*;
 
 use crate::math::Real;
@@ -7,6 +11,7 @@ use crate::tuples::TupleLike;
 use crate::{math, tuples};
 use std::cell::RefCell;
 
+mod matrix44;
 mod transformations;
 
 #[derive(Debug, Clone)]
@@ -295,6 +300,19 @@ impl<T: Copy> Mul<&TupleLike<T>> for Matrix {
     }
 }
 
+/// A transformation that can't be inverted (e.g. a scaling by 0), and
+/// therefore can't be used to transform rays into object space
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingularMatrix(pub Box<Matrix44>);
+
+impl Display for SingularMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Singular transformation: {:?}", self.0)
+    }
+}
+
+impl Error for SingularMatrix {}
+
 pub fn no_inverse() {
     panic!("No inverse");
 }
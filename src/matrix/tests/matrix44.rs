@@ -0,0 +1,112 @@
+use crate::matrix::{no_inverse, Matrix, Matrix44};
+use crate::tuples;
+
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_multiplication() {
+    let m1 = Matrix44::new(&[
+        1.0, 2.0, 3.0, 4.0,
+        5.0, 6.0, 7.0, 8.0,
+        9.0, 8.0, 7.0, 6.0,
+        5.0, 4.0, 3.0, 2.0,
+    ]);
+    let m2 = Matrix44::new(&[
+        -2.0, 1.0, 2.0, 3.0,
+        3.0, 2.0, 1.0, -1.0,
+        4.0, 3.0, 6.0, 5.0,
+        1.0, 2.0, 7.0, 8.0,
+    ]);
+    assert_eq!(
+        m1 * m2,
+        Matrix44::new(&[
+            20.0, 22.0, 50.0, 48.0,
+            44.0, 54.0, 114.0, 108.0,
+            40.0, 58.0, 110.0, 102.0,
+            16.0, 26.0, 46.0, 42.0,
+        ])
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_tuple_multiplication() {
+    let matrix = Matrix44::new(&[
+        1.0, 2.0, 3.0, 4.0,
+        2.0, 4.0, 4.0, 2.0,
+        8.0, 6.0, 4.0, 1.0,
+        0.0, 0.0, 0.0, 1.0,
+    ]);
+    assert_eq!(
+        matrix * tuples::new(1.0, 2.0, 3.0, 1.0),
+        tuples::new(18.0, 24.0, 33.0, 1.0)
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_determinant() {
+    let matrix = Matrix44::new(&[
+        -2.0, -8.0, 3.0, 5.0,
+        -3.0, 1.0, 7.0, 3.0,
+        1.0, 2.0, -9.0, 6.0,
+        -6.0, 7.0, 7.0, -9.0,
+    ]);
+    assert_eq!(matrix.determinant(), -4071.0);
+}
+
+/// The closed-form inverse should agree with the cofactor expansion
+/// of the general matrix
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_inverse() {
+    let cases = [
+        [-5, 2, 6, -8, 1, -5, 1, 8, 7, 7, -6, -7, 1, -3, 7, 4],
+        [8, -5, 9, 2, 7, 5, 6, 1, -6, 0, 9, 6, -3, 0, -9, -4],
+        [9, 3, 0, 9, -5, -2, -6, -3, -4, 9, 6, 4, -7, 6, 6, 2],
+    ];
+    for elements in cases.iter() {
+        let matrix = Matrix::new44i(elements);
+        let matrix44 = Matrix44::from(&matrix);
+        match (matrix.inverse(), matrix44.inverse()) {
+            (Some(inverse), Some(inverse44)) => {
+                assert_eq!(inverse44, Matrix44::from(&inverse));
+                assert_eq!(matrix44 * inverse44, Matrix44::id());
+            }
+            _ => no_inverse(),
+        }
+    }
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_not_invertible() {
+    let matrix = Matrix44::new(&[
+        -4.0, 2.0, -2.0, -3.0,
+        9.0, 6.0, 2.0, 6.0,
+        0.0, -5.0, 1.0, -5.0,
+        0.0, 0.0, 0.0, 0.0,
+    ]);
+    assert_eq!(matrix.determinant(), 0.0);
+    assert_eq!(matrix.inverse(), None);
+    assert_eq!(matrix.inverse_or_id(), Matrix44::id());
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_matrix44_transposition() {
+    let matrix = Matrix44::new(&[
+        0.0, 9.0, 3.0, 0.0,
+        9.0, 8.0, 0.0, 8.0,
+        1.0, 8.0, 5.0, 3.0,
+        0.0, 0.0, 5.0, 8.0,
+    ]);
+    assert_eq!(
+        matrix.transpose(),
+        Matrix44::new(&[
+            0.0, 9.0, 1.0, 0.0,
+            9.0, 8.0, 8.0, 0.0,
+            3.0, 0.0, 5.0, 5.0,
+            0.0, 8.0, 3.0, 8.0,
+        ])
+    );
+}
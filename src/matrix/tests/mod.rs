@@ -1,6 +1,7 @@
 use crate::matrix::{no_inverse, Matrix};
 use crate::tuples::Tuple;
 
+mod matrix44;
 mod transformations;
 
 #[rustfmt::skip]
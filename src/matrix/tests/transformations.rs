@@ -1,6 +1,7 @@
 use crate::math;
 use crate::matrix::{
-    rotation_x, rotation_y, rotation_z, scaling, shearing, translation, view_transformation, Matrix,
+    rotation_x, rotation_y, rotation_z, scaling, shearing, translation, view_transformation,
+    Matrix44,
 };
 use crate::tuples::points::Point;
 use crate::tuples::{points, vectors};
@@ -175,7 +176,7 @@ fn test_view_default_orientation() {
     let to = points::new(0.0, 0.0, -1.0);
     let up = vectors::new(0.0, 1.0, 0.0);
     let t = view_transformation(from, to, up);
-    assert_eq!(t, Matrix::id44());
+    assert_eq!(t, Matrix44::id());
 }
 
 /// Tests a view transformation matrix looking in positive z direction
@@ -205,7 +206,7 @@ fn test_an_arbitrary_view_transformation() {
     let t = view_transformation(from, to, up);
     assert_eq!(
         t.round_items(5),
-        Matrix::new44(&[
+        Matrix44::new(&[
             -0.50709, 0.50709, 0.67612, -2.36643, 0.76772, 0.60609, 0.12122, -2.82843, -0.35857,
             0.59761, -0.71714, 0.00000, 0.00000, 0.00000, 0.00000, 1.00000,
         ])
@@ -1,5 +1,5 @@
 use crate::math::Real;
-use crate::matrix::Matrix;
+use crate::matrix::{Matrix44, SingularMatrix};
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
 
@@ -13,8 +13,8 @@ use crate::tuples::vectors::Vector;
 ///
 #[rustfmt::skip]
 #[inline(always)]
-pub fn translation(x: Real, y: Real, z: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn translation(x: Real, y: Real, z: Real) -> Matrix44 {
+    Matrix44::new(&[
         1.0, 0.0, 0.0, x,
         0.0, 1.0, 0.0, y,
         0.0, 0.0, 1.0, z,
@@ -29,8 +29,8 @@ pub fn translation(x: Real, y: Real, z: Real) -> Matrix {
 /// This works for both point and vector.
 #[rustfmt::skip]
 #[inline(always)]
-pub fn scaling(x: Real, y: Real, z: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn scaling(x: Real, y: Real, z: Real) -> Matrix44 {
+    Matrix44::new(&[
         x, 0.0, 0.0, 0.0,
         0.0, y, 0.0, 0.0,
         0.0, 0.0, z, 0.0,
@@ -42,8 +42,8 @@ pub fn scaling(x: Real, y: Real, z: Real) -> Matrix {
 /// Note: This rotation is based on the lef-hand rule.
 #[rustfmt::skip]
 #[inline(always)]
-pub fn rotation_x(rad: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn rotation_x(rad: Real) -> Matrix44 {
+    Matrix44::new(&[
         1.0, 0.0, 0.0, 0.0,
         0.0, rad.cos(), -rad.sin(), 0.0,
         0.0, rad.sin(), rad.cos(),
@@ -55,8 +55,8 @@ pub fn rotation_x(rad: Real) -> Matrix {
 /// Note: This rotation is based on the lef-hand rule.
 #[rustfmt::skip]
 #[inline(always)]
-pub fn rotation_y(rad: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn rotation_y(rad: Real) -> Matrix44 {
+    Matrix44::new(&[
         rad.cos(), 0.0, rad.sin(), 0.0,
         0.0, 1.0, 0.0, 0.0,
         -rad.sin(), 0.0, rad.cos(), 0.0,
@@ -68,8 +68,8 @@ pub fn rotation_y(rad: Real) -> Matrix {
 /// Note: This rotation is based on the lef-hand rule.
 #[rustfmt::skip]
 #[inline(always)]
-pub fn rotation_z(rad: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn rotation_z(rad: Real) -> Matrix44 {
+    Matrix44::new(&[
         rad.cos(), -rad.sin(), 0.0, 0.0,
         rad.sin(), rad.cos(), 0.0, 0.0,
         0.0, 0.0, 1.0, 0.0,
@@ -79,8 +79,8 @@ pub fn rotation_z(rad: Real) -> Matrix {
 
 #[rustfmt::skip]
 #[inline(always)]
-pub fn shearing(xy: Real, xz: Real, yx: Real, yz: Real, zx: Real, zy: Real) -> Matrix {
-    Matrix::new44(&[
+pub fn shearing(xy: Real, xz: Real, yx: Real, yz: Real, zx: Real, zy: Real) -> Matrix44 {
+    Matrix44::new(&[
         1.0, xy, xz, 0.0,
         yx, 1.0, yz, 0.0,
         zx, zy, 1.0, 0.0,
@@ -88,11 +88,11 @@ pub fn shearing(xy: Real, xz: Real, yx: Real, yz: Real, zx: Real, zy: Real) -> M
     ])
 }
 
-pub fn view_transformation(from: Point, to: Point, up: Vector) -> Matrix {
+pub fn view_transformation(from: Point, to: Point, up: Vector) -> Matrix44 {
     let forward = (to - from).normalize();
     let left = forward.cross(up.normalize());
     let true_up = left.cross(forward);
-    let orientation = Matrix::new44(&[
+    let orientation = Matrix44::new(&[
         left.x, left.y, left.z, 0.0, true_up.x, true_up.y, true_up.z, 0.0, -forward.x, -forward.y,
         -forward.z, 0.0, 0.0, 0.0, 0.0, 1.0,
     ]);
@@ -100,12 +100,26 @@ pub fn view_transformation(from: Point, to: Point, up: Vector) -> Matrix {
 }
 
 pub trait CanTransform: Sized {
-    fn get_transformation(&self) -> &Matrix;
+    fn get_transformation(&self) -> &Matrix44;
 
-    fn set_transformation(self, transformation: Matrix) -> Self;
+    /// Sets the transformation, precomputing the inverse matrices needed
+    /// when rendering. Fails if the transformation can't be inverted.
+    fn try_set_transformation(self, transformation: Matrix44) -> Result<Self, SingularMatrix>;
 
-    fn transform(self, transformation: Matrix) -> Self {
-        let transformation = &transformation * self.get_transformation();
+    /// Like `try_set_transformation`, but panics on singular transformations
+    /// instead of letting them render garbage.
+    fn set_transformation(self, transformation: Matrix44) -> Self {
+        self.try_set_transformation(transformation)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_transform(self, transformation: Matrix44) -> Result<Self, SingularMatrix> {
+        let transformation = transformation * *self.get_transformation();
+        self.try_set_transformation(transformation)
+    }
+
+    fn transform(self, transformation: Matrix44) -> Self {
+        let transformation = transformation * *self.get_transformation();
         self.set_transformation(transformation)
     }
 
@@ -0,0 +1,59 @@
+use std::io;
+use std::io::Read;
+use std::rc::Rc;
+
+use crate::math::Real;
+use crate::parsers;
+use crate::scene::stats;
+use crate::shapes::patches;
+use crate::shapes::patches::BezierPatch;
+use crate::shapes::Shape;
+use crate::tuples::points;
+
+/// Loads Bezier patches in the `.bpt` format (used for the Utah teapot) and
+/// tessellates each patch into a `level` x `level` grid of smooth triangles.
+pub fn parse_bpt<R: Read>(read: R, level: usize) -> io::Result<Rc<Shape>> {
+    stats::timed("parse", || {
+        let patches = parse_patches(read)?;
+        Ok(patches::to_group(&patches, level))
+    })
+}
+
+/// Reads the patches without tessellating them. The file starts with the
+/// number of patches. Each patch then has its degrees in `u` and `v`,
+/// followed by its control points, one per line.
+pub fn parse_patches<R: Read>(mut read: R) -> io::Result<Vec<BezierPatch>> {
+    let mut text = String::new();
+    read.read_to_string(&mut text)?;
+    let mut numbers = text.split_whitespace().map(|word| {
+        word.parse::<Real>()
+            .map_err(|_| parsers::invalid_data("Malformed number in BPT file"))
+    });
+    let mut next = || {
+        numbers
+            .next()
+            .unwrap_or_else(|| Err(parsers::invalid_data("Unexpected end of BPT data")))
+    };
+
+    let count = next()?;
+    if count < 0.0 || count.fract() != 0.0 {
+        return Err(parsers::invalid_data("Invalid number of BPT patches"));
+    }
+    // the count comes from the file, so the patches aren't preallocated
+    let mut patches = vec![];
+    for _ in 0..count as usize {
+        let (u_degree, v_degree) = (next()?, next()?);
+        if u_degree != 3.0 || v_degree != 3.0 {
+            return Err(parsers::invalid_data("Only bicubic patches are supported"));
+        }
+
+        let mut control_points = [[points::new(0.0, 0.0, 0.0); 4]; 4];
+        for row in control_points.iter_mut() {
+            for point in row.iter_mut() {
+                *point = points::new(next()?, next()?, next()?);
+            }
+        }
+        patches.push(BezierPatch::new(control_points));
+    }
+    Ok(patches)
+}
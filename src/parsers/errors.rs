@@ -0,0 +1,89 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+/// A problem found on a specific line of an input file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// The line couldn't be read (e.g. it isn't valid UTF-8)
+    Unreadable(String),
+    BadNumber(String),
+    MissingComponents {
+        expected: usize,
+        found: usize,
+    },
+    IndexOutOfRange(i64),
+    TooFewVertices(usize),
+
+    /// An `mtllib` that couldn't be loaded, with the reason
+    MaterialLibrary {
+        name: String,
+        reason: String,
+    },
+
+    /// A `usemtl` naming a material none of the libraries define
+    UnknownMaterial(String),
+
+    /// A `map_Kd` texture of a material library that couldn't be loaded,
+    /// with the reason
+    Texture {
+        name: String,
+        reason: String,
+    },
+}
+
+impl ParseError {
+    pub fn new(line: usize, text: &str, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line,
+            text: text.to_string(),
+            kind,
+        }
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Unreadable(reason) => write!(f, "unreadable line ({})", reason),
+            ParseErrorKind::BadNumber(word) => write!(f, "bad number \"{}\"", word),
+            ParseErrorKind::MissingComponents { expected, found } => {
+                write!(f, "expected {} components, found {}", expected, found)
+            }
+            ParseErrorKind::IndexOutOfRange(index) => write!(f, "index {} is out of range", index),
+            ParseErrorKind::TooFewVertices(count) => {
+                write!(f, "faces need at least 3 vertices, found {}", count)
+            }
+            ParseErrorKind::MaterialLibrary { name, reason } => {
+                write!(f, "can't load material library \"{}\" ({})", name, reason)
+            }
+            ParseErrorKind::UnknownMaterial(name) => write!(f, "unknown material \"{}\"", name),
+            ParseErrorKind::Texture { name, reason } => {
+                write!(f, "can't load texture \"{}\" ({})", name, reason)
+            }
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.kind, self.text.trim())
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(error: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
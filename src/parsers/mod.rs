@@ -1,9 +1,19 @@
-use std::io::{BufRead, BufReader, Read};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::path::{Path, PathBuf};
 
 use crate::math::Real;
 
-use crate::shapes::groups::Group;
+use crate::materials::Material;
+use crate::parsers::errors::{ParseError, ParseErrorKind};
+use crate::parsers::normals::SmoothNormals;
+use crate::scene::stats;
+use crate::shapes::groups::{Group, GroupBuilder};
+use crate::shapes::meshes::{Mesh, MeshTriangle};
+use crate::shapes::subdivision::PolyMesh;
 use crate::shapes::triangles::Triangle;
+use crate::shapes::uvs::Uv;
 use crate::shapes::{Geo, Shape};
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
@@ -14,31 +24,57 @@ use std::str::SplitWhitespace;
 
 /// Contains information about an input OBJ, which can be represented
 /// as a file, a byte slice or any data that implements Read.
-/// Note: vertices and normals are both 1-based
+/// Note: vertices, texture vertices and normals are all 1-based
 pub struct Parser {
     vertices: Vec<Point>,
     normals: Vec<Vector>,
+    uvs: Vec<Uv>,
     faces: Vec<FaceData>,
     groups: HashMap<String, Rc<Shape>>,
+    materials: HashMap<String, Material>,
+    base_dir: Option<PathBuf>,
+    warnings: Vec<ParseError>,
 }
 
 pub enum Statement {
     Vertex(Point),
     Normal(Vector),
-    Face(FaceData),
+    TextureVertex(Uv),
+    Face(FaceIndices),
     Group(String),
+    Object(String),
+    MaterialLib(Vec<String>),
+    UseMaterial(String),
     None,
 }
 
+/// The indices of a face as they appear in the file. They can be
+/// negative, in which case they are relative to the end of their lists.
+#[derive(Debug, Clone)]
+pub struct FaceIndices {
+    vs: Vec<i64>,
+    ts: Vec<i64>,
+    ns: Vec<i64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FaceData {
     vs: Vec<usize>,
+    ts: Vec<usize>,
     ns: Vec<usize>,
+    material: Option<String>,
+    group: Option<String>,
 }
 
 impl FaceData {
-    fn new(vs: Vec<usize>, ns: Vec<usize>) -> FaceData {
-        FaceData { vs, ns }
+    fn new(vs: Vec<usize>, ts: Vec<usize>, ns: Vec<usize>, material: Option<String>) -> FaceData {
+        FaceData {
+            vs,
+            ts,
+            ns,
+            material,
+            group: None,
+        }
     }
 }
 
@@ -49,12 +85,17 @@ impl Parser {
         // this is a quick way to make the indices 1-based
         let vertices = vec![Point::origin()];
         let normals = vec![Vector::zero()];
+        let uvs = vec![(0.0, 0.0)];
 
         Parser {
             vertices,
             normals,
+            uvs,
             faces: vec![],
             groups: HashMap::new(),
+            materials: HashMap::new(),
+            base_dir: None,
+            warnings: vec![],
         }
     }
 
@@ -70,10 +111,23 @@ impl Parser {
         self.normals.clone()
     }
 
+    pub fn get_uvs(&self) -> Vec<Uv> {
+        self.uvs.clone()
+    }
+
+    pub fn get_material(&self, name: &str) -> Option<&Material> {
+        self.materials.get(name)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.vertices.len() == 1
     }
 
+    /// The issues that were skipped over while parsing in lenient mode
+    pub fn get_warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
     pub fn get_default_group(&mut self) -> Rc<Shape> {
         self.group_default();
         self.groups
@@ -98,44 +152,182 @@ impl Parser {
             Some(group) => group.clone(),
         };
 
-        let triangles = self.fan_triangulation(data);
+        let material = data
+            .material
+            .as_ref()
+            .and_then(|name| self.materials.get(name));
+        let triangles = self.triangulation(data);
         for triangle in triangles {
-            let triangle = Rc::new(Shape::new(Geo::Triangle(triangle)));
-            if let Geo::Group(g) = &group.geo {
-                g.add_child(Rc::downgrade(&group), Rc::clone(&triangle));
+            let mut triangle = Shape::new(Geo::Triangle(triangle));
+            if let Some(material) = material {
+                triangle = triangle.material_ref(material);
             }
+            group.add_child(Rc::new(triangle));
         }
 
         self.groups.insert(name.to_string(), group)
     }
 
     /// Converts polygons into triangles
-    fn fan_triangulation(&self, data: &FaceData) -> Vec<Triangle> {
-        let FaceData { vs, ns } = data;
+    fn triangulation(&self, data: &FaceData) -> Vec<Triangle> {
+        let FaceData { vs, ts, ns, .. } = data;
         let mut triangles: Vec<Triangle> = vec![];
-        for i in 1..vs.len() - 1 {
+        for [a, b, c] in self.triangulate_face(data) {
             let (v1, v2, v3) = (
-                self.vertices[vs[0]],
-                self.vertices[vs[i]],
-                self.vertices[vs[i + 1]],
+                self.vertices[vs[a]],
+                self.vertices[vs[b]],
+                self.vertices[vs[c]],
             );
-            let triangle = if ns.is_empty() {
+            let mut triangle = if ns.is_empty() {
                 Triangle::regular(v1, v2, v3)
             } else {
                 Triangle::smooth(
                     v1,
                     v2,
                     v3,
-                    self.normals[ns[0]],
-                    self.normals[ns[i]],
-                    self.normals[ns[i + 1]],
+                    self.normals[ns[a]],
+                    self.normals[ns[b]],
+                    self.normals[ns[c]],
                 )
             };
+            if !ts.is_empty() {
+                triangle = triangle.with_uvs([self.uvs[ts[a]], self.uvs[ts[b]], self.uvs[ts[c]]]);
+            }
             triangles.push(triangle)
         }
         triangles
     }
 
+    /// The triangles of a face, as positions within the face
+    fn triangulate_face(&self, data: &FaceData) -> Vec<[usize; 3]> {
+        let corners: Vec<Point> = data.vs.iter().map(|&v| self.vertices[v]).collect();
+        polygons::triangulate(&corners)
+    }
+
+    /// Turns the raw indices of a face into valid 1-based indices.
+    /// Faces with invalid vertex indices are dropped. Texture and normal
+    /// indices are only kept if every one of them is valid.
+    fn resolve_face(
+        &self,
+        indices: FaceIndices,
+        material: Option<String>,
+        issues: &mut Vec<ParseErrorKind>,
+    ) -> Option<FaceData> {
+        let mut resolve = |indices: &[i64], len: usize| -> Option<Vec<usize>> {
+            let mut resolved = vec![];
+            for &index in indices {
+                // `len` includes the placeholder at index 0
+                let i = if index < 0 { len as i64 + index } else { index };
+                if i > 0 && i < len as i64 {
+                    resolved.push(i as usize);
+                } else {
+                    issues.push(ParseErrorKind::IndexOutOfRange(index));
+                }
+            }
+            (resolved.len() == indices.len()).then_some(resolved)
+        };
+
+        let FaceIndices { vs, ts, ns } = indices;
+        let vs = resolve(&vs, self.vertices.len())?;
+        let ts = resolve(&ts, self.uvs.len()).filter(|ts| ts.len() == vs.len());
+        let ns = resolve(&ns, self.normals.len()).filter(|ns| ns.len() == vs.len());
+
+        if vs.len() < 3 {
+            issues.push(ParseErrorKind::TooFewVertices(vs.len()));
+            return None;
+        }
+
+        Some(FaceData::new(
+            vs,
+            ts.unwrap_or_default(),
+            ns.unwrap_or_default(),
+            material,
+        ))
+    }
+
+    /// Gives each face without normals a normal per corner, computed from
+    /// the faces around it. The new normals are added to the normals list,
+    /// so the faces are triangulated like the ones with `vn` data.
+    fn generate_normals(&mut self, options: SmoothNormals) {
+        let faces: Vec<Vec<usize>> = self.faces.iter().map(|face| face.vs.clone()).collect();
+        let generated = normals::generate(&self.vertices, &faces, options);
+
+        for (face, corners) in self.faces.iter_mut().zip(generated) {
+            if let (true, Some(corners)) = (face.ns.is_empty(), corners) {
+                let start = self.normals.len();
+                self.normals.extend(corners);
+                face.ns = (start..self.normals.len()).collect();
+            }
+        }
+    }
+
+    /// Loads the material libraries. The ones that can't be loaded are
+    /// reported, and the faces using their materials keep the default one.
+    /// So are the textures of the libraries that can't be loaded.
+    fn load_materials(&mut self, libraries: Vec<String>, issues: &mut Vec<ParseErrorKind>) {
+        for library in libraries {
+            let path = match &self.base_dir {
+                Some(dir) => dir.join(&library),
+                None => PathBuf::from(&library),
+            };
+            match mtl::parse_mtl_file(path) {
+                Ok((materials, textures)) => {
+                    self.materials.extend(materials);
+                    issues.extend(textures);
+                }
+                Err(error) => issues.push(ParseErrorKind::MaterialLibrary {
+                    name: library,
+                    reason: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    /// All the faces as a polygon mesh with shared vertices, for example
+    /// to subdivide it before rendering. Normals and texture coordinates
+    /// are left out.
+    pub fn to_mesh(&self) -> PolyMesh {
+        let faces = self
+            .faces
+            .iter()
+            .map(|face| face.vs.iter().map(|v| v - 1).collect())
+            .collect();
+        PolyMesh::new(self.vertices[1..].to_vec(), faces)
+    }
+
+    /// All the faces as a single `Mesh`, with their normals and texture
+    /// coordinates. Polygons are triangulated like in the groups.
+    /// The mesh is one shape, so the faces' materials are left out.
+    pub fn to_indexed_mesh(&self) -> Mesh {
+        let triangles = self
+            .faces
+            .iter()
+            .flat_map(|face| {
+                let FaceData { vs, ts, ns, .. } = face;
+                self.triangulate_face(face)
+                    .into_iter()
+                    .map(move |[a, b, c]| {
+                        let corners =
+                            |indices: &[usize]| [indices[a] - 1, indices[b] - 1, indices[c] - 1];
+                        let mut triangle = MeshTriangle::new(corners(vs));
+                        if !ns.is_empty() {
+                            triangle = triangle.normals(corners(ns));
+                        }
+                        if !ts.is_empty() {
+                            triangle = triangle.uvs(corners(ts));
+                        }
+                        triangle
+                    })
+            })
+            .collect();
+        Mesh::new(
+            self.vertices[1..].to_vec(),
+            self.normals[1..].to_vec(),
+            self.uvs[1..].to_vec(),
+            triangles,
+        )
+    }
+
     pub fn get_group(&self, name: &str) -> Option<&Rc<Shape>> {
         self.groups.get(name)
     }
@@ -166,115 +358,268 @@ impl Parser {
     }
 }
 
+/// Parses an OBJ in lenient mode: lines that can't be parsed are skipped
+/// (or partially used) and the problems are recorded as warnings.
 pub fn parse_obj<R: Read>(read: R) -> Parser {
+    // lenient parsing never fails
+    ObjOptions::new()
+        .parse(read)
+        .unwrap_or_else(|_| Parser::new())
+}
+
+/// Parses an OBJ in strict mode, failing at the first problematic line
+pub fn try_parse_obj<R: Read>(read: R) -> Result<Parser, ParseError> {
+    ObjOptions::new().strict(true).parse(read)
+}
+
+/// Parses an OBJ file in lenient mode. Material libraries are resolved
+/// relative to the directory of the file.
+pub fn parse_obj_file<P: AsRef<Path>>(path: P) -> io::Result<Parser> {
+    ObjOptions::new().parse_file(path)
+}
+
+/// Parses an OBJ file in strict mode
+pub fn try_parse_obj_file<P: AsRef<Path>>(path: P) -> io::Result<Parser> {
+    ObjOptions::new().strict(true).parse_file(path)
+}
+
+/// Settings for loading OBJ files. By default, the parsing is lenient
+/// and faces without normals become flat triangles.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ObjOptions {
+    strict: bool,
+    smooth_normals: Option<SmoothNormals>,
+}
+
+impl ObjOptions {
+    pub fn new() -> ObjOptions {
+        ObjOptions::default()
+    }
+
+    /// Whether to fail at the first problematic line
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Computes vertex normals for the faces that don't have any,
+    /// making smooth triangles out of them
+    pub fn smooth_normals(mut self, smooth_normals: SmoothNormals) -> Self {
+        self.smooth_normals = Some(smooth_normals);
+        self
+    }
+
+    pub fn parse<R: Read>(&self, read: R) -> Result<Parser, ParseError> {
+        stats::timed("parse", || parse_obj_in(read, None, *self))
+    }
+
+    /// Parses an OBJ file. Material libraries are resolved relative to
+    /// the directory of the file.
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> io::Result<Parser> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        Ok(stats::timed("parse", || {
+            parse_obj_in(file, path.parent(), *self)
+        })?)
+    }
+}
+
+fn parse_obj_in<R: Read>(
+    read: R,
+    base_dir: Option<&Path>,
+    options: ObjOptions,
+) -> Result<Parser, ParseError> {
     let lines = BufReader::new(read).lines();
     let mut current_group: Option<String> = None;
+    let mut current_material: Option<String> = None;
     let mut parser = Parser::new();
-
-    for line in lines {
-        if let Ok(line) = line {
-            match parse_statement(line) {
-                Statement::Vertex(point) => parser.vertices.push(point),
-                Statement::Normal(vector) => parser.normals.push(vector),
-                Statement::Face(data) => {
-                    if let Some(ref group) = current_group {
-                        parser.add_face_to_group(group, &data);
-                    }
+    parser.base_dir = base_dir.map(Path::to_path_buf);
+
+    for (i, line) in lines.enumerate() {
+        let mut issues = vec![];
+        let line = line.unwrap_or_else(|error| {
+            issues.push(ParseErrorKind::Unreadable(error.to_string()));
+            String::new()
+        });
+
+        match parse_statement(&line, &mut issues) {
+            Statement::Vertex(point) => parser.vertices.push(point),
+            Statement::Normal(vector) => parser.normals.push(vector),
+            Statement::TextureVertex(uv) => parser.uvs.push(uv),
+            Statement::Face(indices) => {
+                let material = current_material.clone();
+                if let Some(mut data) = parser.resolve_face(indices, material, &mut issues) {
+                    data.group = current_group.clone();
                     parser.faces.push(data)
                 }
-                Statement::Group(name) => current_group = Some(name),
-                Statement::None => (),
             }
+            Statement::Group(name) | Statement::Object(name) => current_group = Some(name),
+            Statement::MaterialLib(libraries) => parser.load_materials(libraries, &mut issues),
+            Statement::UseMaterial(name) => {
+                if !parser.materials.contains_key(&name) {
+                    issues.push(ParseErrorKind::UnknownMaterial(name.clone()));
+                }
+                current_material = Some(name)
+            }
+            Statement::None => (),
+        }
+
+        let mut errors = issues
+            .into_iter()
+            .map(|kind| ParseError::new(i + 1, &line, kind));
+        if options.strict {
+            if let Some(error) = errors.next() {
+                return Err(error);
+            }
+        } else {
+            parser.warnings.extend(errors);
+        }
+    }
+
+    // the groups are made once all the faces are known, since the
+    // generated normals depend on the faces that come later
+    if let Some(smooth_normals) = options.smooth_normals {
+        parser.generate_normals(smooth_normals);
+    }
+    for face in parser.faces.clone() {
+        if let Some(ref group) = face.group {
+            parser.add_face_to_group(group, &face);
         }
     }
 
-    parser
+    Ok(parser)
 }
 
-fn parse_statement(line: String) -> Statement {
+fn parse_statement(line: &str, issues: &mut Vec<ParseErrorKind>) -> Statement {
     let mut line = line.split_whitespace();
 
     match line.next() {
-        Some("v") => parse_vertex(line),
-        Some("vn") => parse_normal(line),
-        Some("f") => parse_face(line),
-        Some("g") => parse_group(line),
+        Some("v") => parse_vertex(line, issues),
+        Some("vn") => parse_normal(line, issues),
+        Some("vt") => parse_texture_vertex(line, issues),
+        Some("f") => parse_face(line, issues),
+        Some("g") => parse_name(line, Statement::Group),
+        Some("o") => parse_name(line, Statement::Object),
+        Some("usemtl") => parse_name(line, Statement::UseMaterial),
+        Some("mtllib") => parse_material_lib(line),
         _ => Statement::None,
     }
 }
 
-fn parse_vertex(line: SplitWhitespace) -> Statement {
-    parse_tuple(line, |ps| {
+fn parse_vertex(line: SplitWhitespace, issues: &mut Vec<ParseErrorKind>) -> Statement {
+    parse_tuple(line, 3, issues, |ps| {
         Statement::Vertex(points::new(ps[0], ps[1], ps[2]))
     })
 }
 
-fn parse_normal(line: SplitWhitespace) -> Statement {
-    parse_tuple(line, |ns| {
+fn parse_normal(line: SplitWhitespace, issues: &mut Vec<ParseErrorKind>) -> Statement {
+    parse_tuple(line, 3, issues, |ns| {
         Statement::Normal(vectors::new(ns[0], ns[1], ns[2]))
     })
 }
 
-fn parse_tuple<F>(line: SplitWhitespace, f: F) -> Statement
+/// Parses the numbers in a line. Malformed numbers are skipped, and
+/// lines with less than `min` valid components are ignored.
+fn parse_tuple<F>(
+    line: SplitWhitespace,
+    min: usize,
+    issues: &mut Vec<ParseErrorKind>,
+    f: F,
+) -> Statement
 where
     F: FnOnce(Vec<Real>) -> Statement,
 {
     let mut components: Vec<Real> = vec![];
 
     for word in line {
-        if let Ok(component) = word.parse::<Real>() {
-            components.push(component);
+        match word.parse::<Real>() {
+            Ok(component) => components.push(component),
+            Err(_) => issues.push(ParseErrorKind::BadNumber(word.to_string())),
         }
     }
 
-    if components.is_empty() {
+    if components.len() < min {
+        issues.push(ParseErrorKind::MissingComponents {
+            expected: min,
+            found: components.len(),
+        });
         Statement::None
     } else {
         f(components)
     }
 }
 
-fn parse_face(line: SplitWhitespace) -> Statement {
-    let mut vs: Vec<usize> = vec![];
-    let mut ns: Vec<usize> = vec![];
-
-    fn parse_part<F>(part: &str, f: F)
-    where
-        F: FnOnce(usize) -> (),
-    {
-        if let Ok(index) = part.parse::<usize>() {
-            f(index)
-        }
-    }
-
-    for word in line {
-        let mut parts = word.split("/");
+fn parse_texture_vertex(line: SplitWhitespace, issues: &mut Vec<ParseErrorKind>) -> Statement {
+    // the optional v and w components default to 0
+    parse_tuple(line, 1, issues, |ts| {
+        Statement::TextureVertex((ts[0], ts.get(1).copied().unwrap_or(0.0)))
+    })
+}
 
-        if let Some(part) = parts.next() {
-            parse_part(part, |i| vs.push(i));
-        }
+fn parse_face(line: SplitWhitespace, issues: &mut Vec<ParseErrorKind>) -> Statement {
+    let mut vs: Vec<i64> = vec![];
+    let mut ts: Vec<i64> = vec![];
+    let mut ns: Vec<i64> = vec![];
 
-        // Ignores texture vertex
-        parts.next();
+    let mut parse_part = |part: Option<&str>, indices: &mut Vec<i64>| match part {
+        // empty parts, like the texture index in `1//3`, are allowed
+        None | Some("") => (),
+        Some(part) => match part.parse::<i64>() {
+            Ok(index) => indices.push(index),
+            Err(_) => issues.push(ParseErrorKind::BadNumber(part.to_string())),
+        },
+    };
 
-        if let Some(part) = parts.next() {
-            parse_part(part, |i| ns.push(i));
-        }
+    for word in line {
+        let mut parts = word.split('/');
+        parse_part(parts.next(), &mut vs);
+        parse_part(parts.next(), &mut ts);
+        parse_part(parts.next(), &mut ns);
     }
 
     if vs.is_empty() {
+        issues.push(ParseErrorKind::TooFewVertices(0));
         Statement::None
     } else {
-        Statement::Face(FaceData::new(vs, ns))
+        Statement::Face(FaceIndices { vs, ts, ns })
     }
 }
 
-fn parse_group(mut line: SplitWhitespace) -> Statement {
+fn parse_name<F>(mut line: SplitWhitespace, f: F) -> Statement
+where
+    F: FnOnce(String) -> Statement,
+{
     match line.next() {
         None => Statement::None,
-        Some(name) => Statement::Group(name.to_string()),
+        Some(name) => f(name.to_string()),
+    }
+}
+
+fn parse_material_lib(line: SplitWhitespace) -> Statement {
+    let libraries: Vec<String> = line.map(str::to_string).collect();
+    if libraries.is_empty() {
+        Statement::None
+    } else {
+        Statement::MaterialLib(libraries)
     }
 }
 
+/// Wraps the given shapes in a group, linking each of them to the group
+pub(crate) fn to_group(children: Vec<Shape>) -> Rc<Shape> {
+    GroupBuilder::new().children(children).build()
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+pub mod bpt;
+pub mod errors;
+pub mod mtl;
+pub mod normals;
+pub mod ply;
+mod polygons;
+pub mod stl;
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::materials::Material;
+use crate::math::Real;
+use crate::parsers;
+use crate::parsers::errors::ParseErrorKind;
+use crate::patterns::Pattern;
+use crate::tuples::colors;
+
+/// Reads a Wavefront material library (.mtl) and maps each material onto
+/// a `Material`:
+/// - `Kd` becomes the color
+/// - `Ks` becomes the specular value (the average of its components)
+/// - `Ns` becomes the shininess
+/// - `d` (dissolve) and `Tr` become the transparency
+/// - `Ni` becomes the refractive index
+/// - `map_Kd` becomes a texture pattern, resolved relative to `base_dir`.
+///   Textures that can't be loaded are returned as issues, and their
+///   materials keep their flat color.
+pub fn parse_mtl<R: Read>(
+    read: R,
+    base_dir: Option<&Path>,
+) -> (HashMap<String, Material>, Vec<ParseErrorKind>) {
+    let mut materials = HashMap::new();
+    let mut issues = vec![];
+    let mut current: Option<(String, Material)> = None;
+
+    for line in BufReader::new(read).lines().map_while(Result::ok) {
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            current = args
+                .first()
+                .map(|name| (name.to_string(), Material::default()));
+            continue;
+        }
+
+        if let Some((_, material)) = current.as_mut() {
+            *material = apply_statement(material.clone(), keyword, &args, base_dir, &mut issues);
+        }
+    }
+
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+    (materials, issues)
+}
+
+/// Reads a material library from a file. Textures are resolved relative
+/// to the directory of the library.
+pub fn parse_mtl_file<P: AsRef<Path>>(
+    path: P,
+) -> io::Result<(HashMap<String, Material>, Vec<ParseErrorKind>)> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    Ok(parse_mtl(file, path.parent()))
+}
+
+fn apply_statement(
+    material: Material,
+    keyword: &str,
+    args: &[&str],
+    base_dir: Option<&Path>,
+    issues: &mut Vec<ParseErrorKind>,
+) -> Material {
+    let numbers: Vec<Real> = args.iter().filter_map(|a| a.parse().ok()).collect();
+    let first = numbers.first().copied();
+
+    match (keyword, numbers.as_slice()) {
+        ("Kd", [r, g, b, ..]) => material.color(colors::new(*r, *g, *b)),
+        ("Ks", [r, g, b, ..]) => material.specular((r + g + b) / 3.0),
+        ("Ns", _) if first.is_some() => material.shininess(first.unwrap()),
+        ("d", _) if first.is_some() => material.transparency(1.0 - first.unwrap()),
+        ("Tr", _) if first.is_some() => material.transparency(first.unwrap()),
+        ("Ni", _) if first.is_some() => material.refractive_index(first.unwrap()),
+        ("map_Kd", _) => match load_texture(args, base_dir) {
+            Ok(texture) => material.pattern(texture),
+            Err(error) => {
+                issues.push(ParseErrorKind::Texture {
+                    name: args.last().unwrap_or(&"").to_string(),
+                    reason: error.to_string(),
+                });
+                material
+            }
+        },
+        _ => material,
+    }
+}
+
+fn load_texture(args: &[&str], base_dir: Option<&Path>) -> io::Result<Pattern> {
+    // the file name comes after the options (e.g. `-s 1 1 1`)
+    let file_name = args
+        .last()
+        .ok_or_else(|| parsers::invalid_data("Missing texture file name"))?;
+    let path = match base_dir {
+        Some(dir) => dir.join(file_name),
+        None => Path::new(file_name).to_path_buf(),
+    };
+    Canvas::load(path).map(Pattern::texture)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::errors::ParseErrorKind;
+    use crate::parsers::mtl::{parse_mtl, parse_mtl_file};
+    use crate::patterns::PatternType;
+    use crate::tuples::colors;
+    use std::io;
+
+    #[test]
+    fn test_parsing_materials() {
+        let file = b"
+            # two materials
+            newmtl red
+            Kd 1 0 0
+            Ks 0.5 0.25 0.75
+            Ns 50
+
+            newmtl glass
+            d 0.25
+            Ni 1.5
+            illum 7" as &[u8];
+        let (materials, issues) = parse_mtl(file, None);
+        assert!(issues.is_empty());
+        assert_eq!(materials.len(), 2);
+
+        let red = &materials["red"];
+        assert_eq!(red.color, colors::new(1.0, 0.0, 0.0));
+        assert_eq!(red.specular, 0.5);
+        assert_eq!(red.shininess, 50.0);
+
+        let glass = &materials["glass"];
+        assert_eq!(glass.transparency, 0.75);
+        assert_eq!(glass.refractive_index, 1.5);
+    }
+
+    #[test]
+    fn test_texture_maps() -> io::Result<()> {
+        let (materials, issues) = parse_mtl_file("resources/textured.mtl")?;
+        assert!(issues.is_empty());
+        let checker = &materials["checker"];
+        match &checker.pattern {
+            Some(pattern) => match &pattern.pattern_type {
+                PatternType::Texture(image) => assert_eq!(image.width, 2),
+                _ => panic!("Not a texture"),
+            },
+            None => panic!("No texture"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_texture() {
+        let file = b"
+            newmtl wood
+            Kd 0.5 0.25 0
+            map_Kd -s 1 1 1 missing.ppm
+            map_Kd" as &[u8];
+        let (materials, issues) = parse_mtl(file, None);
+        let wood = &materials["wood"];
+        assert_eq!(wood.color, colors::new(0.5, 0.25, 0.0));
+        assert!(wood.pattern.is_none());
+
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(
+            &issues[0],
+            ParseErrorKind::Texture { name, .. } if name == "missing.ppm"
+        ));
+        assert!(matches!(
+            &issues[1],
+            ParseErrorKind::Texture { name, .. } if name.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::math::Real;
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+
+/// How the normals of the faces around a vertex are averaged
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NormalWeighting {
+    /// Larger faces contribute more
+    Area,
+
+    /// Faces contribute by the angle of their corner at the vertex,
+    /// which doesn't depend on how the faces were triangulated
+    Angle,
+}
+
+/// Settings for computing vertex normals of meshes that don't have any
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SmoothNormals {
+    pub weighting: NormalWeighting,
+
+    /// Faces meeting at a larger angle (in radians) than this don't share
+    /// their normals, so the edge between them stays sharp
+    pub crease_angle: Real,
+}
+
+impl SmoothNormals {
+    pub fn new(weighting: NormalWeighting, crease_angle: Real) -> SmoothNormals {
+        SmoothNormals {
+            weighting,
+            crease_angle,
+        }
+    }
+}
+
+/// The normal of a polygon, following the winding of `Triangle::regular`.
+/// Its length is twice the area of the polygon.
+fn face_normal(vertices: &[Point], face: &[usize]) -> Vector {
+    let p1 = vertices[face[0]];
+    (1..face.len() - 1).fold(Vector::zero(), |normal, i| {
+        let edge1 = (vertices[face[i]] - p1).to_vector();
+        let edge2 = (vertices[face[i + 1]] - p1).to_vector();
+        normal + edge2.cross(edge1)
+    })
+}
+
+/// The angle of the face's corner at its `i`th vertex
+fn corner_angle(vertices: &[Point], face: &[usize], i: usize) -> Real {
+    let n = face.len();
+    let point = vertices[face[i]];
+    let prev = (vertices[face[(i + n - 1) % n]] - point).to_vector();
+    let next = (vertices[face[(i + 1) % n]] - point).to_vector();
+    let cos = prev.dot(next) / (prev.magnitude() * next.magnitude());
+    if cos.is_nan() {
+        0.0
+    } else {
+        cos.clamp(-1.0, 1.0).acos()
+    }
+}
+
+/// Computes a normal for each corner of each face. A corner's normal
+/// averages the normals of the faces around its vertex, leaving out the
+/// faces beyond the crease angle. Degenerate faces get `None`.
+pub(crate) fn generate(
+    vertices: &[Point],
+    faces: &[Vec<usize>],
+    options: SmoothNormals,
+) -> Vec<Option<Vec<Vector>>> {
+    let normals: Vec<Vector> = faces
+        .iter()
+        .map(|face| face_normal(vertices, face))
+        .collect();
+    let units: Vec<Option<Vector>> = normals
+        .iter()
+        .map(|normal| (normal.magnitude() > 0.0).then(|| normal.normalize()))
+        .collect();
+
+    // the faces around each vertex, with their weights at that vertex
+    let mut around: Vec<Vec<(usize, Real)>> = vec![vec![]; vertices.len()];
+    for (f, face) in faces.iter().enumerate() {
+        for (i, &v) in face.iter().enumerate() {
+            let weight = match options.weighting {
+                NormalWeighting::Area => normals[f].magnitude(),
+                NormalWeighting::Angle => corner_angle(vertices, face, i),
+            };
+            around[v].push((f, weight));
+        }
+    }
+
+    let min_cos = options.crease_angle.cos();
+    faces
+        .iter()
+        .enumerate()
+        .map(|(f, face)| {
+            let unit = units[f]?;
+            let corners = face
+                .iter()
+                .map(|&v| {
+                    let sum = around[v]
+                        .iter()
+                        .filter_map(|&(g, weight)| units[g].map(|other| (other, weight)))
+                        .filter(|(other, _)| unit.dot(*other) >= min_cos - 1e-9)
+                        .fold(Vector::zero(), |sum, (other, weight)| sum + other * weight);
+                    if sum.magnitude() > 0.0 {
+                        sum.normalize()
+                    } else {
+                        unit
+                    }
+                })
+                .collect();
+            Some(corners)
+        })
+        .collect()
+}
@@ -0,0 +1,418 @@
+use std::io;
+use std::io::Read;
+use std::rc::Rc;
+
+use crate::materials::Material;
+use crate::math::Real;
+use crate::parsers;
+use crate::parsers::polygons;
+use crate::scene::stats;
+use crate::shapes::triangles::Triangle;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::colors::Color;
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use crate::tuples::{colors, points, vectors};
+
+/// Loads a Stanford PLY mesh (ASCII or binary little-endian) into a group
+/// of triangles. Per-vertex normals produce smooth triangles, and per-vertex
+/// (or per-face) colors become the colors of the triangles' materials.
+pub fn parse_ply<R: Read>(read: R) -> io::Result<Rc<Shape>> {
+    stats::timed("parse", || read_ply(read))
+}
+
+fn read_ply<R: Read>(mut read: R) -> io::Result<Rc<Shape>> {
+    let mut bytes = vec![];
+    read.read_to_end(&mut bytes)?;
+
+    let (header, body_start) = Header::parse(&bytes)?;
+    let mut values: Box<dyn ValueReader> = match header.format {
+        Format::Ascii => Box::new(AsciiReader::new(&bytes[body_start..])?),
+        Format::BinaryLittleEndian => Box::new(BinaryReader {
+            bytes: &bytes[body_start..],
+            pos: 0,
+        }),
+    };
+
+    let mut vertices: Vec<Vertex> = vec![];
+    let mut faces: Vec<Face> = vec![];
+
+    for element in header.elements.iter() {
+        for _ in 0..element.count {
+            let record = element.read_record(values.as_mut())?;
+            match element.name.as_str() {
+                "vertex" => vertices.push(Vertex::from_record(&record)?),
+                "face" => faces.push(Face::from_record(&record)?),
+                _ => (), // other elements, like edges, are skipped
+            }
+        }
+    }
+
+    let mut triangles = vec![];
+    for face in faces.iter() {
+        triangles.append(&mut face.to_triangles(&vertices)?);
+    }
+    Ok(parsers::to_group(triangles))
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Scalar {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> io::Result<Scalar> {
+        match name {
+            "char" | "int8" => Ok(Scalar::Char),
+            "uchar" | "uint8" => Ok(Scalar::UChar),
+            "short" | "int16" => Ok(Scalar::Short),
+            "ushort" | "uint16" => Ok(Scalar::UShort),
+            "int" | "int32" => Ok(Scalar::Int),
+            "uint" | "uint32" => Ok(Scalar::UInt),
+            "float" | "float32" => Ok(Scalar::Float),
+            "double" | "float64" => Ok(Scalar::Double),
+            _ => Err(parsers::invalid_data(&format!(
+                "Unknown PLY property type: {}",
+                name
+            ))),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Scalar::Char | Scalar::UChar => 1,
+            Scalar::Short | Scalar::UShort => 2,
+            Scalar::Int | Scalar::UInt | Scalar::Float => 4,
+            Scalar::Double => 8,
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        !matches!(self, Scalar::Float | Scalar::Double)
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar(String, Scalar),
+    List {
+        name: String,
+        count: Scalar,
+        item: Scalar,
+    },
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// The values of a single element, e.g. one vertex or one face
+struct Record<'a> {
+    element: &'a Element,
+    values: Vec<Vec<Real>>,
+}
+
+impl<'a> Record<'a> {
+    fn scalar(&self, name: &str) -> Option<Real> {
+        self.position(name).map(|i| self.values[i][0])
+    }
+
+    fn list(&self, names: &[&str]) -> Option<&Vec<Real>> {
+        names
+            .iter()
+            .find_map(|name| self.position(name))
+            .map(|i| &self.values[i])
+    }
+
+    /// Reads a color component. Integer components are scaled to 0..1.
+    fn color_component(&self, name: &str) -> Option<Real> {
+        let i = self.position(name)?;
+        match &self.element.properties[i] {
+            Property::Scalar(_, ty) if ty.is_integer() => Some(self.values[i][0] / 255.0),
+            _ => Some(self.values[i][0]),
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        let r = self.color_component("red")?;
+        let g = self.color_component("green")?;
+        let b = self.color_component("blue")?;
+        Some(colors::new(r, g, b))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.element.properties.iter().position(|p| match p {
+            Property::Scalar(n, _) | Property::List { name: n, .. } => n == name,
+        })
+    }
+}
+
+impl Element {
+    fn read_record(&self, values: &mut dyn ValueReader) -> io::Result<Record<'_>> {
+        let mut record = vec![];
+        for property in self.properties.iter() {
+            match property {
+                Property::Scalar(_, ty) => record.push(vec![values.read(*ty)?]),
+                Property::List { count, item, .. } => {
+                    let count = values.read(*count)? as usize;
+                    let items = (0..count)
+                        .map(|_| values.read(*item))
+                        .collect::<io::Result<_>>()?;
+                    record.push(items);
+                }
+            }
+        }
+        Ok(Record {
+            element: self,
+            values: record,
+        })
+    }
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+}
+
+impl Header {
+    /// Parses the header, returning it along with the position of the body
+    fn parse(bytes: &[u8]) -> io::Result<(Header, usize)> {
+        const END: &[u8] = b"end_header";
+        let end = bytes
+            .windows(END.len())
+            .position(|window| window == END)
+            .ok_or_else(|| parsers::invalid_data("Missing PLY end_header"))?;
+
+        // the body starts after the line break that ends the header
+        let body_start = bytes[end..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|i| end + i + 1)
+            .unwrap_or(bytes.len());
+
+        let text = String::from_utf8_lossy(&bytes[..end]);
+        let mut lines = text.lines().map(|line| line.trim());
+        if lines.next() != Some("ply") {
+            return Err(parsers::invalid_data("Not a PLY file"));
+        }
+
+        let mut format = None;
+        let mut elements: Vec<Element> = vec![];
+
+        for line in lines {
+            let words: Vec<_> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["format", "ascii", _] => format = Some(Format::Ascii),
+                ["format", "binary_little_endian", _] => format = Some(Format::BinaryLittleEndian),
+                ["format", ..] => {
+                    return Err(parsers::invalid_data("Unsupported PLY format"));
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count
+                        .parse()
+                        .map_err(|_| parsers::invalid_data("Invalid PLY element count"))?,
+                    properties: vec![],
+                }),
+                ["property", "list", count, item, name] => {
+                    let property = Property::List {
+                        name: name.to_string(),
+                        count: Scalar::parse(count)?,
+                        item: Scalar::parse(item)?,
+                    };
+                    Header::last_element(&mut elements)?
+                        .properties
+                        .push(property);
+                }
+                ["property", ty, name] => {
+                    let property = Property::Scalar(name.to_string(), Scalar::parse(ty)?);
+                    Header::last_element(&mut elements)?
+                        .properties
+                        .push(property);
+                }
+                _ => (), // comments and obj_info
+            }
+        }
+
+        let format = format.ok_or_else(|| parsers::invalid_data("Missing PLY format"))?;
+        Ok((Header { format, elements }, body_start))
+    }
+
+    fn last_element(elements: &mut [Element]) -> io::Result<&mut Element> {
+        elements
+            .last_mut()
+            .ok_or_else(|| parsers::invalid_data("PLY property outside of an element"))
+    }
+}
+
+trait ValueReader {
+    fn read(&mut self, ty: Scalar) -> io::Result<Real>;
+}
+
+struct AsciiReader {
+    tokens: std::vec::IntoIter<String>,
+}
+
+impl AsciiReader {
+    fn new(bytes: &[u8]) -> io::Result<AsciiReader> {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| parsers::invalid_data("PLY body is not valid text"))?;
+        let tokens: Vec<String> = text.split_whitespace().map(|s| s.to_string()).collect();
+        Ok(AsciiReader {
+            tokens: tokens.into_iter(),
+        })
+    }
+}
+
+impl ValueReader for AsciiReader {
+    fn read(&mut self, _: Scalar) -> io::Result<Real> {
+        let token = self
+            .tokens
+            .next()
+            .ok_or_else(|| parsers::invalid_data("Unexpected end of PLY data"))?;
+        token
+            .parse::<Real>()
+            .map_err(|_| parsers::invalid_data(&format!("Invalid PLY value: {}", token)))
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ValueReader for BinaryReader<'a> {
+    fn read(&mut self, ty: Scalar) -> io::Result<Real> {
+        let size = ty.size();
+        let data = self
+            .bytes
+            .get(self.pos..self.pos + size)
+            .ok_or_else(|| parsers::invalid_data("Unexpected end of PLY data"))?;
+        self.pos += size;
+
+        let mut buf = [0; 8];
+        buf[..size].copy_from_slice(data);
+        let value = match ty {
+            Scalar::Char => data[0] as i8 as Real,
+            Scalar::UChar => data[0] as Real,
+            Scalar::Short => i16::from_le_bytes([buf[0], buf[1]]) as Real,
+            Scalar::UShort => u16::from_le_bytes([buf[0], buf[1]]) as Real,
+            Scalar::Int => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as Real,
+            Scalar::UInt => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as Real,
+            Scalar::Float => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as Real,
+            Scalar::Double => f64::from_le_bytes(buf),
+        };
+        Ok(value)
+    }
+}
+
+struct Vertex {
+    position: Point,
+    normal: Option<Vector>,
+    color: Option<Color>,
+}
+
+impl Vertex {
+    fn from_record(record: &Record) -> io::Result<Vertex> {
+        let coordinate = |name: &str| {
+            record
+                .scalar(name)
+                .ok_or_else(|| parsers::invalid_data(&format!("PLY vertex without {}", name)))
+        };
+        let position = points::new(coordinate("x")?, coordinate("y")?, coordinate("z")?);
+
+        let normal = match (
+            record.scalar("nx"),
+            record.scalar("ny"),
+            record.scalar("nz"),
+        ) {
+            (Some(x), Some(y), Some(z)) => Some(vectors::new(x, y, z)),
+            _ => None,
+        };
+
+        Ok(Vertex {
+            position,
+            normal,
+            color: record.color(),
+        })
+    }
+}
+
+struct Face {
+    indices: Vec<usize>,
+    color: Option<Color>,
+}
+
+impl Face {
+    fn from_record(record: &Record) -> io::Result<Face> {
+        let indices = record
+            .list(&["vertex_indices", "vertex_index"])
+            .ok_or_else(|| parsers::invalid_data("PLY face without vertex indices"))?
+            .iter()
+            .map(|&i| {
+                if i >= 0.0 && i.fract() == 0.0 {
+                    Ok(i as usize)
+                } else {
+                    Err(parsers::invalid_data("Invalid PLY vertex index"))
+                }
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Face {
+            indices,
+            color: record.color(),
+        })
+    }
+
+    fn to_triangles(&self, vertices: &[Vertex]) -> io::Result<Vec<Shape>> {
+        let corners = self
+            .indices
+            .iter()
+            .map(|&i| {
+                vertices
+                    .get(i)
+                    .ok_or_else(|| parsers::invalid_data("PLY vertex index out of range"))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let positions: Vec<Point> = corners.iter().map(|v| v.position).collect();
+        let mut triangles = vec![];
+        for [a, b, c] in polygons::triangulate(&positions) {
+            let (v1, v2, v3) = (corners[a], corners[b], corners[c]);
+            let triangle = match (v1.normal, v2.normal, v3.normal) {
+                (Some(n1), Some(n2), Some(n3)) => {
+                    Triangle::smooth(v1.position, v2.position, v3.position, n1, n2, n3)
+                }
+                _ => Triangle::regular(v1.position, v2.position, v3.position),
+            };
+
+            let vertex_color = match (v1.color, v2.color, v3.color) {
+                (Some(c1), Some(c2), Some(c3)) => Some((c1 + c2 + c3) / 3.0),
+                _ => None,
+            };
+            let shape = match self.color.or(vertex_color) {
+                None => Shape::new(Geo::Triangle(triangle)),
+                Some(color) => {
+                    Shape::new(Geo::Triangle(triangle)).material(Material::default().color(color))
+                }
+            };
+            triangles.push(shape);
+        }
+        Ok(triangles)
+    }
+}
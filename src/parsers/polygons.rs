@@ -0,0 +1,207 @@
+use crate::math;
+use crate::math::Real;
+use crate::tuples::points::Point;
+use crate::tuples::vectors;
+use crate::tuples::vectors::Vector;
+
+/// Splits a polygon into triangles, returned as indices into `corners`.
+/// Triangles keep the winding of the polygon. Convex polygons are fanned
+/// from their first corner, other polygons are triangulated by ear
+/// clipping, so concave faces don't spill outside their outline.
+pub(crate) fn triangulate(corners: &[Point]) -> Vec<[usize; 3]> {
+    let n = corners.len();
+    if n < 3 {
+        return vec![];
+    }
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let projected = project(corners);
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    let area = signed_area(&projected);
+    if area.abs() < math::EPSILON {
+        // degenerate polygons have no inside to stay within
+        return fan();
+    }
+    let orientation = area.signum();
+
+    let convex = |a: usize, b: usize, c: usize| {
+        cross(projected[a], projected[b], projected[c]) * orientation > 0.0
+    };
+    if n == 4 && (0..4).all(|i| convex(i, (i + 1) % 4, (i + 2) % 4)) {
+        return fan();
+    }
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = vec![];
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corner = |i: usize| {
+            (
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            )
+        };
+        let is_ear = |i: usize| {
+            let (a, b, c) = corner(i);
+            convex(a, b, c)
+                && remaining.iter().all(|&other| {
+                    other == a
+                        || other == b
+                        || other == c
+                        || !inside(projected[other], projected[a], projected[b], projected[c])
+                })
+        };
+
+        // starting after the first corner gives the same triangles as a
+        // fan when the polygon is convex. Polygons that intersect
+        // themselves may have no ear left, so a corner is clipped anyway.
+        let ear = (1..=len).map(|i| i % len).find(|&i| is_ear(i)).unwrap_or(1);
+        let (a, b, c) = corner(ear);
+        triangles.push([a, b, c]);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// Projects the polygon onto the axis-aligned plane it is most
+/// parallel to, dropping the dominant axis of its normal
+fn project(corners: &[Point]) -> Vec<(Real, Real)> {
+    // Newell's method works for concave and slightly bent polygons
+    let normal = (0..corners.len()).fold(Vector::zero(), |normal, i| {
+        let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+        normal
+            + vectors::new(
+                (a.y - b.y) * (a.z + b.z),
+                (a.z - b.z) * (a.x + b.x),
+                (a.x - b.x) * (a.y + b.y),
+            )
+    });
+    let (x, y, z) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+
+    corners
+        .iter()
+        .map(|p| {
+            if x >= y && x >= z {
+                (p.y, p.z)
+            } else if y >= z {
+                (p.z, p.x)
+            } else {
+                (p.x, p.y)
+            }
+        })
+        .collect()
+}
+
+fn signed_area(points: &[(Real, Real)]) -> Real {
+    (0..points.len()).fold(0.0, |area, i| {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        area + a.0 * b.1 - b.0 * a.1
+    }) / 2.0
+}
+
+/// The z component of `(b - a) x (c - b)`, positive for a left turn
+fn cross(a: (Real, Real), b: (Real, Real), c: (Real, Real)) -> Real {
+    (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0)
+}
+
+/// Whether the point is inside the triangle or on its edges
+fn inside(point: (Real, Real), a: (Real, Real), b: (Real, Real), c: (Real, Real)) -> bool {
+    let (d1, d2, d3) = (cross(a, b, point), cross(b, c, point), cross(c, a, point));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Real;
+    use crate::parsers::polygons::{project, signed_area, triangulate};
+    use crate::tuples::points;
+    use crate::tuples::points::Point;
+
+    fn area(corners: &[Point], triangles: &[[usize; 3]]) -> Vec<Real> {
+        let projected = project(corners);
+        triangles
+            .iter()
+            .map(|tri| signed_area(&tri.map(|i| projected[i])))
+            .collect()
+    }
+
+    #[test]
+    fn test_convex_polygons_are_fanned() {
+        let quad = [
+            points::new(0.0, 0.0, 0.0),
+            points::new(1.0, 0.0, 0.0),
+            points::new(1.0, 1.0, 0.0),
+            points::new(0.0, 1.0, 0.0),
+        ];
+        assert_eq!(triangulate(&quad), vec![[0, 1, 2], [0, 2, 3]]);
+
+        let pentagon = [
+            points::new(-1.0, 1.0, 0.0),
+            points::new(-1.0, 0.0, 0.0),
+            points::new(1.0, 0.0, 0.0),
+            points::new(1.0, 1.0, 0.0),
+            points::new(0.0, 2.0, 0.0),
+        ];
+        assert_eq!(
+            triangulate(&pentagon),
+            vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_concave_quad() {
+        // the diagonal from the first corner goes outside the polygon
+        let dart = [
+            points::new(4.0, 0.0, 0.0),
+            points::new(1.0, 1.0, 0.0),
+            points::new(0.0, 4.0, 0.0),
+            points::new(0.0, 0.0, 0.0),
+        ];
+        let triangles = triangulate(&dart);
+        assert_eq!(triangles, vec![[1, 2, 3], [0, 1, 3]]);
+        assert!(area(&dart, &triangles).iter().all(|&a| a > 0.0));
+    }
+
+    #[test]
+    fn test_concave_polygon_on_a_tilted_plane() {
+        // an L shape, rotated out of the axis planes
+        let outline = [
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ];
+        let corners: Vec<Point> = outline
+            .iter()
+            .map(|&(u, v)| points::new(u, v * 0.6, v * 0.8 + u * 0.1))
+            .collect();
+        let triangles = triangulate(&corners);
+        assert_eq!(triangles.len(), 4);
+
+        let areas = area(&corners, &triangles);
+        let total: Real = areas.iter().sum();
+        assert!(areas.iter().all(|&a| a.signum() == total.signum()));
+        assert!((total.abs() - signed_area(&project(&corners)).abs()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_degenerate_polygons() {
+        assert!(triangulate(&[Point::origin(), Point::origin()]).is_empty());
+
+        let line = [
+            points::new(0.0, 0.0, 0.0),
+            points::new(1.0, 0.0, 0.0),
+            points::new(2.0, 0.0, 0.0),
+            points::new(3.0, 0.0, 0.0),
+        ];
+        assert_eq!(triangulate(&line), vec![[0, 1, 2], [0, 2, 3]]);
+    }
+}
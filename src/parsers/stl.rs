@@ -0,0 +1,108 @@
+use std::io;
+use std::io::Read;
+use std::rc::Rc;
+
+use crate::math::Real;
+use crate::parsers;
+use crate::scene::stats;
+use crate::shapes::triangles::Triangle;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::points;
+use crate::tuples::points::Point;
+
+/// Size of the binary header, including the triangle count
+const BINARY_HEADER_SIZE: usize = 84;
+
+/// Normal, three vertices, and the attribute byte count
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+/// Loads an STL file (ASCII or binary) into a group of triangles.
+/// The facet normals stored in the file are ignored; they are
+/// recomputed from the vertices.
+pub fn parse_stl<R: Read>(read: R) -> io::Result<Rc<Shape>> {
+    stats::timed("parse", || read_stl(read))
+}
+
+fn read_stl<R: Read>(mut read: R) -> io::Result<Rc<Shape>> {
+    let mut bytes = vec![];
+    read.read_to_end(&mut bytes)?;
+
+    // Binary files may also start with "solid", so the size is the
+    // more reliable way to tell the two formats apart.
+    let facets = if is_binary(&bytes) {
+        parse_binary(&bytes)
+    } else if bytes.starts_with(b"solid") {
+        parse_ascii(&bytes)?
+    } else {
+        return Err(parsers::invalid_data("Not an STL file"));
+    };
+
+    let triangles = facets
+        .into_iter()
+        .map(|[p1, p2, p3]| Shape::new(Geo::Triangle(Triangle::regular(p1, p2, p3))))
+        .collect();
+    Ok(parsers::to_group(triangles))
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < BINARY_HEADER_SIZE {
+        return false;
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    bytes.len() == BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE
+}
+
+fn parse_binary(bytes: &[u8]) -> Vec<[Point; 3]> {
+    let read_f32 = |at: usize| {
+        f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as Real
+    };
+    let read_point = |at: usize| points::new(read_f32(at), read_f32(at + 4), read_f32(at + 8));
+
+    let count = (bytes.len() - BINARY_HEADER_SIZE) / BINARY_TRIANGLE_SIZE;
+    (0..count)
+        .map(|i| {
+            // skip the facet normal
+            let start = BINARY_HEADER_SIZE + i * BINARY_TRIANGLE_SIZE + 12;
+            [
+                read_point(start),
+                read_point(start + 12),
+                read_point(start + 24),
+            ]
+        })
+        .collect()
+}
+
+fn parse_ascii(bytes: &[u8]) -> io::Result<Vec<[Point; 3]>> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut facets = vec![];
+    let mut corners: Vec<Point> = vec![];
+
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("vertex") => {
+                let coordinates = words
+                    .map(|word| word.parse::<Real>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| parsers::invalid_data(&format!("Invalid STL vertex: {}", line)))?;
+                if coordinates.len() != 3 {
+                    return Err(parsers::invalid_data(&format!(
+                        "Invalid STL vertex: {}",
+                        line
+                    )));
+                }
+                corners.push(points::new(coordinates[0], coordinates[1], coordinates[2]));
+            }
+            Some("endloop") => {
+                if corners.len() != 3 {
+                    return Err(parsers::invalid_data("STL facets need exactly 3 vertices"));
+                }
+                facets.push([corners[0], corners[1], corners[2]]);
+                corners.clear();
+            }
+            _ => (),
+        }
+    }
+
+    Ok(facets)
+}
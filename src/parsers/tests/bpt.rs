@@ -0,0 +1,96 @@
+use crate::parsers::bpt::{parse_bpt, parse_patches};
+use crate::shapes::Geo;
+use crate::tuples::{points, vectors};
+use std::io::ErrorKind;
+
+/// A flat patch over the unit square of the xz plane, followed by a
+/// patch that bulges upwards in the middle
+const PATCHES: &[u8] = b"2
+3 3
+0 0 0
+0 0 0.333
+0 0 0.667
+0 0 1
+0.333 0 0
+0.333 0 0.333
+0.333 0 0.667
+0.333 0 1
+0.667 0 0
+0.667 0 0.333
+0.667 0 0.667
+0.667 0 1
+1 0 0
+1 0 0.333
+1 0 0.667
+1 0 1
+3 3
+0 0 0 0 0 1 0 0 2 0 0 3
+1 0 0 1 2 1 1 2 2 1 0 3
+2 0 0 2 2 1 2 2 2 2 0 3
+3 0 0 3 0 1 3 0 2 3 0 3
+";
+
+#[test]
+fn test_parsing_patches() {
+    let patches = parse_patches(PATCHES).unwrap();
+    assert_eq!(patches.len(), 2);
+    assert_eq!(
+        patches[0].control_points[1][2],
+        points::new(0.333, 0.0, 0.667)
+    );
+    assert_eq!(patches[1].control_points[3][3], points::new(3.0, 0.0, 3.0));
+
+    // the corners of a patch are on the surface
+    assert_eq!(patches[1].point_at(1.0, 0.0), points::new(3.0, 0.0, 0.0));
+    assert_eq!(patches[1].point_at(0.5, 0.5), points::new(1.5, 1.125, 1.5));
+    assert_eq!(patches[0].normal_at(0.5, 0.5), vectors::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_tessellating_patches() {
+    let group = parse_bpt(PATCHES, 4).unwrap();
+    let group = group.as_group().unwrap();
+    assert_eq!(group.children.borrow().len(), 2 * 2 * 4 * 4);
+
+    for child in group.children.borrow().iter() {
+        match &child.geo {
+            Geo::Triangle(triangle) => {
+                // the normals of the triangles and the smooth normals agree
+                let normal = triangle.get_default_normal();
+                assert!(normal.y > 0.0);
+            }
+            _ => panic!("Not a triangle"),
+        }
+    }
+}
+
+#[test]
+fn test_collapsed_patch_rows() {
+    // the first row of control points collapses into a single point,
+    // like the top of the teapot's lid
+    let mut patch = parse_patches(PATCHES).unwrap()[1];
+    patch.control_points[0] = [points::new(0.0, 0.0, 1.5); 4];
+    let normal = patch.normal_at(0.0, 0.5);
+    assert!(normal.y > 0.0);
+    assert_eq!(normal.magnitude(), 1.0);
+
+    // the triangles that collapse into lines are dropped
+    assert_eq!(patch.tessellate(4).len(), 2 * 4 * 4 - 4);
+}
+
+#[test]
+fn test_invalid_patches() {
+    let data: [&[u8]; 6] = [
+        b"1\n2 3\n0 0 0",
+        b"1\n3 3\n0 0 0\n1 1 1",
+        b"1\n3 3\n0 0 x",
+        // the number of patches is checked before anything is allocated
+        b"1e30\n3 3\n0 0 0",
+        b"-1\n3 3\n0 0 0",
+        b"2.5\n3 3\n0 0 0",
+    ];
+    for data in data {
+        let error = parse_patches(data).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,388 @@
+mod bpt;
+mod normals;
+mod ply;
+mod stl;
+
+use crate::parsers;
+use crate::parsers::errors::ParseErrorKind;
+use crate::rays::Ray;
+use crate::shapes::triangles::{Smooth, TriangleKind};
+use crate::shapes::Geo;
+use crate::tuples::{colors, points, vectors};
+use std::fs::File;
+use std::io;
+
+#[test]
+fn test_ignoring_unrecognized_files() {
+    let gibberish = b"There was a young lady named Bright
+        who traveled much faster than light.
+        She set out one day.
+        in a relative way,
+        and came back the previous night." as &[u8];
+    let result = parsers::parse_obj(gibberish);
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_parsing_vertex_records() {
+    let file = b"
+        v -1 1 0
+        v -1.0000 0.5000 0.0000
+        v 1 0 0
+        v 1 1 0" as &[u8];
+    let parser = parsers::parse_obj(file);
+    let vertices = parser.get_vertices();
+
+    assert_eq!(vertices[1], points::new(-1.0, 1.0, 0.0));
+    assert_eq!(vertices[2], points::new(-1.0, 0.5, 0.0));
+    assert_eq!(vertices[3], points::new(1.0, 0.0, 0.0));
+    assert_eq!(vertices[4], points::new(1.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_parsing_faces() {
+    let file = b"
+        v -1 1 0
+        v -1 0 0
+        v 1 0 0
+        v 1 1 0
+
+        f 1 2 3
+        f 1 3 4" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+    let group = parser.get_default_group();
+    if let Geo::Group(g) = &group.geo {
+        let t1 = g.get_child(0);
+        let t2 = g.get_child(1);
+
+        if let (Geo::Triangle(t1), Geo::Triangle(t2)) = (t1.geo, t2.geo) {
+            let vertices = parser.get_vertices();
+
+            assert_eq!(t1.get_p1(), vertices[1]);
+            assert_eq!(t1.get_p2(), vertices[2]);
+            assert_eq!(t1.get_p3(), vertices[3]);
+
+            assert_eq!(t2.get_p1(), vertices[1]);
+            assert_eq!(t2.get_p2(), vertices[3]);
+            assert_eq!(t2.get_p3(), vertices[4]);
+        }
+    }
+}
+
+#[test]
+fn test_triangulating_polygons() {
+    let file = b"
+        v -1 1 0
+        v -1 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 2 0
+        f 1 2 3 4 5" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+    let group = parser.get_default_group();
+    if let Geo::Group(g) = &group.geo {
+        let t1 = g.get_child(0);
+        let t2 = g.get_child(1);
+        let t3 = g.get_child(2);
+
+        if let (Geo::Triangle(t1), Geo::Triangle(t2), Geo::Triangle(t3)) = (t1.geo, t2.geo, t3.geo)
+        {
+            let vertices = parser.get_vertices();
+
+            assert_eq!(t1.get_p1(), vertices[1]);
+            assert_eq!(t1.get_p2(), vertices[2]);
+            assert_eq!(t1.get_p3(), vertices[3]);
+
+            assert_eq!(t2.get_p1(), vertices[1]);
+            assert_eq!(t2.get_p2(), vertices[3]);
+            assert_eq!(t2.get_p3(), vertices[4]);
+
+            assert_eq!(t3.get_p1(), vertices[1]);
+            assert_eq!(t3.get_p2(), vertices[4]);
+            assert_eq!(t3.get_p3(), vertices[5]);
+        }
+    }
+}
+
+#[test]
+fn test_triangulating_concave_polygons() {
+    let file = b"
+        v 4 0 0
+        v 1 0 1
+        v 0 0 4
+        v 0 0 0
+        f 1 2 3 4" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+    let group = parser.get_default_group();
+
+    // the notch between the first and third corners stays empty
+    let down = |x, z| Ray::new(points::new(x, 1.0, z), vectors::new(0.0, -1.0, 0.0));
+    assert!(group.intersect(&down(1.5, 1.5)).is_empty());
+    assert_eq!(group.intersect(&down(0.5, 0.3)).len(), 1);
+    assert_eq!(group.intersect(&down(2.5, 0.2)).len(), 1);
+
+    let mesh = parser.to_indexed_mesh().to_shape();
+    assert!(mesh.intersect(&down(1.5, 1.5)).is_empty());
+    assert_eq!(mesh.intersect(&down(0.2, 2.5)).len(), 1);
+}
+
+#[test]
+fn test_triangles_in_groups() -> io::Result<()> {
+    let file = File::open("resources/triangles.obj")?;
+    let parser = parsers::parse_obj(file);
+
+    let t1 = parser.get_triangle_unsafe("FirstGroup", 0);
+    let t2 = parser.get_triangle_unsafe("SecondGroup", 0);
+
+    assert_eq!(t1.get_p1(), parser.vertices[1]);
+    assert_eq!(t1.get_p2(), parser.vertices[2]);
+    assert_eq!(t1.get_p3(), parser.vertices[3]);
+
+    assert_eq!(t2.get_p1(), parser.vertices[1]);
+    assert_eq!(t2.get_p2(), parser.vertices[3]);
+    assert_eq!(t2.get_p3(), parser.vertices[4]);
+    Ok(())
+}
+
+#[test]
+fn test_vertex_normal_records() {
+    let file = b"
+        vn 0 0 1
+        vn 0.707 0 -0.707
+        vn 1 2 3" as &[u8];
+    let parser = parsers::parse_obj(file);
+    assert_eq!(parser.get_normals()[1], vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(parser.get_normals()[2], vectors::new(0.707, 0.0, -0.707));
+    assert_eq!(parser.get_normals()[3], vectors::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+fn test_faces_with_normals() {
+    let file = b"
+        v 0 1 0
+        v -1 0 0
+        v 1 0 0
+
+        vn -1 0 0
+        vn 1 0 0
+        vn 0 1 0
+
+        f 1//3 2//1 3//2
+        f 1/0/3 2/102/1 3/14/2" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+
+    parser.group_default();
+
+    let t1 = parser.get_triangle_unsafe(parsers::DEFAULT_GROUP, 0);
+    let t2 = parser.get_triangle_unsafe(parsers::DEFAULT_GROUP, 1);
+
+    assert_eq!(t1.get_p1(), parser.vertices[1]);
+    assert_eq!(t1.get_p2(), parser.vertices[2]);
+    assert_eq!(t1.get_p3(), parser.vertices[3]);
+
+    if let TriangleKind::Smooth(Smooth { n1, n2, n3 }) = t1.kind {
+        assert_eq!(n1, parser.get_normals()[3]);
+        assert_eq!(n2, parser.get_normals()[1]);
+        assert_eq!(n3, parser.get_normals()[2]);
+    } else {
+        panic!("Not a smooth triangle");
+    }
+    assert_eq!(t2, t1);
+}
+
+#[test]
+fn test_texture_vertex_records() {
+    let file = b"
+        vt 0.5 0.25
+        vt 1 0 0
+        vt 0.75" as &[u8];
+    let parser = parsers::parse_obj(file);
+    let uvs = parser.get_uvs();
+
+    assert_eq!(uvs[1], (0.5, 0.25));
+    assert_eq!(uvs[2], (1.0, 0.0));
+    assert_eq!(uvs[3], (0.75, 0.0));
+}
+
+#[test]
+fn test_faces_with_texture_vertices() {
+    let file = b"
+        v 0 1 0
+        v -1 0 0
+        v 1 0 0
+
+        vt 0.5 1
+        vt 0 0
+        vt 1 0
+
+        f 1/1 2/2 3/3" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+    parser.group_default();
+
+    let t1 = parser.get_triangle_unsafe(parsers::DEFAULT_GROUP, 0);
+    assert_eq!(t1.get_uvs(), Some([(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]));
+}
+
+#[test]
+fn test_negative_indices() {
+    let file = b"
+        v 0 1 0
+        v -1 0 0
+        v 1 0 0
+        vn 0 0 1
+
+        f -3//-1 -2//-1 -1//-1
+        f 1 2 -4" as &[u8];
+    let mut parser = parsers::parse_obj(file);
+    parser.group_default();
+
+    let t1 = parser.get_triangle_unsafe(parsers::DEFAULT_GROUP, 0);
+    assert_eq!(t1.get_p1(), parser.vertices[1]);
+    assert_eq!(t1.get_p2(), parser.vertices[2]);
+    assert_eq!(t1.get_p3(), parser.vertices[3]);
+    assert!(matches!(t1.kind, TriangleKind::Smooth(_)));
+
+    // the second face refers to a vertex that doesn't exist
+    assert_eq!(parser.faces.len(), 1);
+}
+
+#[test]
+fn test_objects_and_materials() -> io::Result<()> {
+    let parser = parsers::parse_obj_file("resources/textured.obj")?;
+
+    let quad = parser.get_group("quad").unwrap();
+    if let Geo::Group(g) = &quad.geo {
+        assert_eq!(g.children.borrow().len(), 2);
+        let child = g.get_child(0);
+        assert!(child.material.pattern.is_some());
+        if let Geo::Triangle(t) = &child.geo {
+            assert_eq!(t.get_uvs(), Some([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]));
+        }
+    } else {
+        panic!("Not a group");
+    }
+
+    let red = parser.get_triangle_unsafe("triangle", 0);
+    assert_eq!(red.get_uvs(), None);
+    let triangle = parser.get_group_geo("triangle").unwrap().get_child(0);
+    assert_eq!(triangle.material.color, colors::new(1.0, 0.0, 0.0));
+    assert_eq!(triangle.material.shininess, 100.0);
+    Ok(())
+}
+
+#[test]
+fn test_lenient_parsing_collects_warnings() {
+    let file = b"
+        v 0 1 0
+        v -1 x 0 0
+        v 1 0 0
+        v 2
+
+        f 1 2 3
+        f 1 2 7
+        f 1 2" as &[u8];
+    let parser = parsers::parse_obj(file);
+
+    // the malformed number is skipped, like before
+    assert_eq!(parser.get_vertices()[2], points::new(-1.0, 0.0, 0.0));
+    assert_eq!(parser.len(), 3);
+    assert_eq!(parser.faces.len(), 1);
+
+    let warnings: Vec<(usize, ParseErrorKind)> = parser
+        .get_warnings()
+        .iter()
+        .map(|warning| (warning.line, warning.kind.clone()))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            (3, ParseErrorKind::BadNumber("x".to_string())),
+            (
+                5,
+                ParseErrorKind::MissingComponents {
+                    expected: 3,
+                    found: 1
+                }
+            ),
+            (8, ParseErrorKind::IndexOutOfRange(7)),
+            (9, ParseErrorKind::TooFewVertices(2)),
+        ]
+    );
+}
+
+#[test]
+fn test_strict_parsing_reports_the_first_error() {
+    let file = b"v 0 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 3\nf 1 -5 3" as &[u8];
+    let error = parsers::try_parse_obj(file).err().unwrap();
+
+    assert_eq!(error.line, 5);
+    assert_eq!(error.text, "f 1 -5 3");
+    assert_eq!(error.kind, ParseErrorKind::IndexOutOfRange(-5));
+    assert_eq!(
+        error.to_string(),
+        "line 5: index -5 is out of range: f 1 -5 3"
+    );
+}
+
+#[test]
+fn test_missing_materials() {
+    let file = b"mtllib missing.mtl
+v 0 1 0
+v -1 0 0
+v 1 0 0
+usemtl red
+f 1 2 3" as &[u8];
+    let parser = parsers::parse_obj(file);
+    assert_eq!(parser.faces.len(), 1);
+    let warnings: Vec<(usize, &ParseErrorKind)> = parser
+        .get_warnings()
+        .iter()
+        .map(|warning| (warning.line, &warning.kind))
+        .collect();
+    assert_eq!(warnings.len(), 2);
+    assert!(matches!(
+        warnings[0],
+        (1, ParseErrorKind::MaterialLibrary { name, .. }) if name == "missing.mtl"
+    ));
+    assert_eq!(
+        warnings[1],
+        (5, &ParseErrorKind::UnknownMaterial("red".to_string()))
+    );
+
+    let error = parsers::try_parse_obj(file).err().unwrap();
+    assert_eq!(error.line, 1);
+    assert!(error
+        .to_string()
+        .starts_with("line 1: can't load material library \"missing.mtl\""));
+}
+
+#[test]
+fn test_missing_textures() {
+    let file = b"mtllib resources/missing_texture.mtl
+v 0 1 0
+v -1 0 0
+v 1 0 0
+usemtl wood
+f 1 2 3" as &[u8];
+    let parser = parsers::parse_obj(file);
+    let warnings = parser.get_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].line, 1);
+    assert!(matches!(
+        &warnings[0].kind,
+        ParseErrorKind::Texture { name, .. } if name == "missing.ppm"
+    ));
+
+    let error = parsers::try_parse_obj(file).err().unwrap();
+    assert!(error
+        .to_string()
+        .starts_with("line 1: can't load texture \"missing.ppm\""));
+}
+
+#[test]
+fn test_strict_parsing_of_valid_files() -> io::Result<()> {
+    let parser = parsers::try_parse_obj_file("resources/triangles.obj")?;
+    assert!(parser.get_warnings().is_empty());
+    assert!(parser.get_group("FirstGroup").is_some());
+    Ok(())
+}
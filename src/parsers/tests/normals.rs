@@ -0,0 +1,129 @@
+use crate::math;
+use crate::parsers::normals::{NormalWeighting, SmoothNormals};
+use crate::parsers::ObjOptions;
+use crate::shapes::triangles::{Smooth, Triangle, TriangleKind};
+use crate::shapes::Geo;
+use crate::tuples::vectors;
+use crate::tuples::vectors::Vector;
+
+/// A cube, wound so that its normals point outwards
+const CUBE: &[u8] = b"
+    v -1 -1 -1
+    v 1 -1 -1
+    v -1 1 -1
+    v 1 1 -1
+    v -1 -1 1
+    v 1 -1 1
+    v -1 1 1
+    v 1 1 1
+    g cube
+    f 1 2 4 3
+    f 5 7 8 6
+    f 1 5 6 2
+    f 3 4 8 7
+    f 1 3 7 5
+    f 2 6 8 4";
+
+fn triangles(file: &[u8], options: ObjOptions) -> Vec<Triangle> {
+    let parser = options.parse(file).unwrap();
+    let group = parser.get_group("cube").unwrap();
+    let children = group.as_group().unwrap().children.borrow();
+    children
+        .iter()
+        .map(|child| match &child.geo {
+            Geo::Triangle(triangle) => triangle.clone(),
+            _ => panic!("Not a triangle"),
+        })
+        .collect()
+}
+
+fn smooth_normals(triangle: &Triangle) -> [Vector; 3] {
+    match triangle.kind {
+        TriangleKind::Smooth(Smooth { n1, n2, n3 }) => [n1, n2, n3],
+        _ => panic!("Not a smooth triangle"),
+    }
+}
+
+#[test]
+fn test_faces_stay_flat_without_smoothing() {
+    let triangles = triangles(CUBE, ObjOptions::new());
+    assert_eq!(triangles.len(), 12);
+    assert!(triangles
+        .iter()
+        .all(|t| matches!(t.kind, TriangleKind::Regular { .. })));
+}
+
+#[test]
+fn test_smoothing_across_all_edges() {
+    let options =
+        ObjOptions::new().smooth_normals(SmoothNormals::new(NormalWeighting::Angle, math::PI));
+    for triangle in triangles(CUBE, options) {
+        let corners = [triangle.get_p1(), triangle.get_p2(), triangle.get_p3()];
+        for (corner, normal) in corners.iter().zip(smooth_normals(&triangle)) {
+            // the corners of a cube are shared by three faces at right angles
+            assert_eq!(normal, corner.to_vector().normalize());
+        }
+    }
+}
+
+#[test]
+fn test_crease_angle_keeps_edges_sharp() {
+    let options = ObjOptions::new()
+        .smooth_normals(SmoothNormals::new(NormalWeighting::Angle, math::PI / 4.0));
+    for triangle in triangles(CUBE, options) {
+        let flat = triangle.get_edge2().cross(triangle.get_edge1()).normalize();
+        assert_eq!(smooth_normals(&triangle), [flat, flat, flat]);
+    }
+}
+
+/// A fan of a large and a small face meeting at a ridge
+#[test]
+fn test_normal_weightings() {
+    let file = b"
+        v 0 0 0
+        v 0 0 1
+        v 4 -1 0
+        v 4 -1 1
+        v -1 -1 0
+        v -1 -1 1
+        g cube
+        f 1 3 4 2
+        f 1 2 6 5" as &[u8];
+    let normal_at_origin = |weighting| {
+        let options =
+            ObjOptions::new().smooth_normals(SmoothNormals::new(weighting, math::PI / 2.0));
+        smooth_normals(&triangles(file, options)[0])[0]
+    };
+
+    // by angle, both faces have right angles at the shared vertex, so the
+    // normal is halfway between theirs
+    let by_angle = normal_at_origin(NormalWeighting::Angle);
+    let big = vectors::new(1.0, 4.0, 0.0).normalize();
+    let small = vectors::new(-1.0, 1.0, 0.0).normalize();
+    assert_eq!(by_angle, (big + small).normalize());
+
+    // by area, the larger face pulls the normal towards its own
+    let by_area = normal_at_origin(NormalWeighting::Area);
+    assert!(by_area.dot(big) > by_angle.dot(big));
+}
+
+#[test]
+fn test_existing_normals_are_kept() {
+    let file = b"
+        v 0 0 0
+        v 1 0 0
+        v 0 0 1
+        v 1 0 1
+        vn 1 0 0
+        g cube
+        f 1//1 3//1 2//1
+        f 2 3 4" as &[u8];
+    let options =
+        ObjOptions::new().smooth_normals(SmoothNormals::new(NormalWeighting::Area, math::PI));
+    let triangles = triangles(file, options);
+    assert_eq!(
+        smooth_normals(&triangles[0])[0],
+        vectors::new(1.0, 0.0, 0.0)
+    );
+    assert_eq!(smooth_normals(&triangles[1])[0].magnitude(), 1.0);
+}
@@ -0,0 +1,163 @@
+use crate::parsers::ply::parse_ply;
+use crate::shapes::triangles::{Smooth, TriangleKind};
+use crate::shapes::{Geo, Shape};
+use crate::tuples::{colors, points, vectors};
+use std::rc::Rc;
+
+fn children(group: &Rc<Shape>) -> Vec<Rc<Shape>> {
+    if let Geo::Group(g) = &group.geo {
+        g.children.borrow().clone()
+    } else {
+        panic!("Not a group")
+    }
+}
+
+#[test]
+fn test_ascii_ply() {
+    let file = b"ply
+format ascii 1.0
+comment a unit square
+element vertex 4
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+4 0 1 2 3
+" as &[u8];
+    let group = parse_ply(file).unwrap();
+    let triangles = children(&group);
+    assert_eq!(triangles.len(), 2);
+
+    if let Geo::Triangle(t) = &triangles[1].geo {
+        assert_eq!(t.get_p1(), points::new(0.0, 0.0, 0.0));
+        assert_eq!(t.get_p2(), points::new(1.0, 1.0, 0.0));
+        assert_eq!(t.get_p3(), points::new(0.0, 1.0, 0.0));
+    } else {
+        panic!("Not a triangle");
+    }
+
+    // children are linked to the group
+    assert_eq!(triangles[0].get_parent(), Some(Rc::clone(&group)));
+}
+
+#[test]
+fn test_ply_normals_and_colors() {
+    let file = b"ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property uchar red
+property uchar green
+property uchar blue
+element edge 1
+property int vertex1
+property int vertex2
+element face 1
+property list uchar uint vertex_index
+end_header
+0 1 0 0 0 1 255 0 0
+-1 0 0 0 0 1 0 255 0
+1 0 0 1 0 0 0 0 255
+0 1
+3 0 1 2
+" as &[u8];
+    let group = parse_ply(file).unwrap();
+    let triangle = &children(&group)[0];
+    assert_eq!(
+        triangle.material.color,
+        colors::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0)
+    );
+
+    if let Geo::Triangle(t) = &triangle.geo {
+        if let TriangleKind::Smooth(Smooth { n1, n3, .. }) = t.kind {
+            assert_eq!(n1, vectors::new(0.0, 0.0, 1.0));
+            assert_eq!(n3, vectors::new(1.0, 0.0, 0.0));
+        } else {
+            panic!("Not a smooth triangle");
+        }
+    }
+}
+
+#[test]
+fn test_binary_ply() {
+    let mut file = b"ply
+format binary_little_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+"
+    .to_vec();
+    for value in [0.0_f32, 1.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0].iter() {
+        file.extend(&value.to_le_bytes());
+    }
+    file.push(3);
+    for index in [0_i32, 1, 2].iter() {
+        file.extend(&index.to_le_bytes());
+    }
+
+    let group = parse_ply(file.as_slice()).unwrap();
+    let triangles = children(&group);
+    assert_eq!(triangles.len(), 1);
+    if let Geo::Triangle(t) = &triangles[0].geo {
+        assert_eq!(t.get_p1(), points::new(0.0, 1.0, 0.0));
+        assert_eq!(t.get_p3(), points::new(1.0, 0.0, 0.0));
+        assert_eq!(t.get_default_normal(), vectors::new(0.0, 0.0, -1.0));
+    }
+}
+
+#[test]
+fn test_invalid_ply() {
+    let out_of_range = b"ply
+format ascii 1.0
+element vertex 1
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+3 0 1 2
+" as &[u8];
+    assert!(parse_ply(out_of_range).is_err());
+
+    // negative or fractional indices don't fall back to the first vertex
+    for face in ["3 0 1 -1", "3 0 1 2.7"] {
+        let file = format!(
+            "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar float vertex_indices
+end_header
+0 0 0
+1 0 0
+1 1 0
+{}
+",
+            face
+        );
+        assert!(parse_ply(file.as_bytes()).is_err());
+    }
+
+    let big_endian = b"ply\nformat binary_big_endian 1.0\nend_header\n" as &[u8];
+    assert!(parse_ply(big_endian).is_err());
+}
@@ -0,0 +1,71 @@
+use crate::parsers::stl::parse_stl;
+use crate::shapes::Geo;
+use crate::tuples::points;
+use crate::tuples::points::Point;
+
+fn first_triangle_points(group: &crate::shapes::Shape) -> (usize, [Point; 3]) {
+    if let Geo::Group(g) = &group.geo {
+        let count = g.children.borrow().len();
+        if let Geo::Triangle(t) = g.get_child(0).geo {
+            return (count, [t.get_p1(), t.get_p2(), t.get_p3()]);
+        }
+    }
+    panic!("Not a group of triangles")
+}
+
+#[test]
+fn test_ascii_stl() {
+    let file = b"solid test
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 1 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid test" as &[u8];
+    let group = parse_stl(file).unwrap();
+    let (count, [p1, p2, p3]) = first_triangle_points(&group);
+    assert_eq!(count, 2);
+    assert_eq!(p1, points::new(0.0, 0.0, 0.0));
+    assert_eq!(p2, points::new(1.0, 0.0, 0.0));
+    assert_eq!(p3, points::new(0.0, 1.0, 0.0));
+}
+
+/// The header of a binary STL can start with "solid" as well
+#[test]
+fn test_binary_stl() {
+    let mut file = vec![0; 80];
+    file[..5].copy_from_slice(b"solid");
+    file.extend(&1_u32.to_le_bytes());
+    let values = [
+        0.0_f32, 0.0, 1.0, // normal
+        0.0, 0.0, 0.0, // vertex 1
+        2.0, 0.0, 0.0, // vertex 2
+        0.0, 2.0, 0.0, // vertex 3
+    ];
+    for value in values.iter() {
+        file.extend(&value.to_le_bytes());
+    }
+    file.extend(&[0, 0]);
+
+    let group = parse_stl(file.as_slice()).unwrap();
+    let (count, [p1, p2, p3]) = first_triangle_points(&group);
+    assert_eq!(count, 1);
+    assert_eq!(p1, points::new(0.0, 0.0, 0.0));
+    assert_eq!(p2, points::new(2.0, 0.0, 0.0));
+    assert_eq!(p3, points::new(0.0, 2.0, 0.0));
+}
+
+#[test]
+fn test_invalid_stl() {
+    assert!(parse_stl(b"solid x\nouter loop\nvertex 0 0\nendloop" as &[u8]).is_err());
+    assert!(parse_stl(b"not an stl file" as &[u8]).is_err());
+}
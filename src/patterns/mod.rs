@@ -1,14 +1,21 @@
+use std::rc::Rc;
+
+use crate::canvas::Canvas;
 use crate::math::Real;
-use crate::matrix::{CanTransform, Matrix};
+use crate::matrix::{CanTransform, Matrix44, SingularMatrix};
+use crate::shapes::uvs::UvMapping;
 use crate::shapes::Object;
-use crate::tuples::colors;
 use crate::tuples::colors::Color;
 use crate::tuples::points::Point;
+use crate::tuples::{colors, points};
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Pattern {
     pub pattern_type: PatternType,
-    pub transformation: Matrix,
+
+    /// Only changed through `CanTransform`, which keeps the inverse in sync
+    transformation: Matrix44,
+    inverse: Matrix44,
     first: Color,
     second: Color,
 }
@@ -19,6 +26,10 @@ pub enum PatternType {
     Gradient,
     Ring,
     Checkers,
+
+    /// An image looked up with the texture coordinates of the surface.
+    /// The pattern's transformation applies to the texture coordinates.
+    Texture(Rc<Canvas>),
     Test,
 }
 
@@ -28,7 +39,8 @@ impl Pattern {
             pattern_type,
             first,
             second,
-            transformation: Matrix::id44(),
+            transformation: Matrix44::id(),
+            inverse: Matrix44::id(),
         }
     }
 
@@ -48,6 +60,14 @@ impl Pattern {
         Pattern::new(PatternType::Checkers, first, second)
     }
 
+    pub fn texture(image: Canvas) -> Pattern {
+        Pattern::texture_ref(Rc::new(image))
+    }
+
+    pub fn texture_ref(image: Rc<Canvas>) -> Pattern {
+        Pattern::new(PatternType::Texture(image), Color::white(), Color::black())
+    }
+
     pub fn test() -> Pattern {
         Pattern::new(PatternType::Test, Color::white(), Color::black())
     }
@@ -61,7 +81,7 @@ impl Pattern {
             }
         };
 
-        match self.pattern_type {
+        match &self.pattern_type {
             PatternType::Stripe => choose(point.x.floor()),
             PatternType::Gradient => {
                 let distance = self.second - self.first;
@@ -70,25 +90,34 @@ impl Pattern {
             }
             PatternType::Ring => choose((point.x.powi(2) + point.z.powi(2)).sqrt().floor()),
             PatternType::Checkers => choose(point.x.floor() + point.y.floor() + point.z.floor()),
+            // texture coordinates start at the bottom of the image
+            PatternType::Texture(image) => image.sample(point.x, 1.0 - point.y),
             PatternType::Test => colors::new(point.x, point.y, point.z),
         }
     }
 
-    pub fn at_object<S>(&self, object: &Object<S>, world_point: Point) -> Color {
-        let object_point = object.world_to_object(world_point);
-        let pattern_point = self.transformation.inverse_or_id44() * object_point;
+    pub fn at_object<S: UvMapping>(&self, object: &Object<S>, world_point: Point) -> Color {
+        let object_point = match self.pattern_type {
+            PatternType::Texture(_) => {
+                let (u, v) = object.uv_at(world_point);
+                points::new(u, v, 0.0)
+            }
+            _ => object.world_to_object(world_point),
+        };
+        let pattern_point = self.inverse * object_point;
         self.at(pattern_point)
     }
 }
 
 impl CanTransform for Pattern {
-    fn get_transformation(&self) -> &Matrix {
+    fn get_transformation(&self) -> &Matrix44 {
         &self.transformation
     }
 
-    fn set_transformation(mut self, transformation: Matrix) -> Self {
+    fn try_set_transformation(mut self, transformation: Matrix44) -> Result<Self, SingularMatrix> {
+        self.inverse = transformation.try_inverse()?;
         self.transformation = transformation;
-        self
+        Ok(self)
     }
 }
 
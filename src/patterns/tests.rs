@@ -1,4 +1,4 @@
-use crate::matrix::CanTransform;
+use crate::matrix::{scaling, CanTransform};
 use crate::patterns::{Pattern, PatternType};
 use crate::shapes::Shape;
 use crate::tuples::colors::Color;
@@ -69,12 +69,18 @@ fn test_stripes_with_pattern_transformation() {
 /// Stripes with both an object and pattern transformation
 #[test]
 fn test_stripes_with_both_transformation() {
-    let object = Shape::sphere().scale(2.0, 0.0, 0.0);
+    let object = Shape::sphere().scale(2.0, 2.0, 2.0);
     let pattern = default_stripe().translate(0.5, 0.0, 0.0);
     let c = pattern.at_object(&object, points::new(2.5, 0.0, 0.0));
     assert_eq!(c, Color::white());
 }
 
+#[test]
+fn test_singular_pattern_transformation() {
+    let result = default_stripe().try_set_transformation(scaling(0.0, 1.0, 1.0));
+    assert!(result.is_err());
+}
+
 /// A gradient linearly interpolates between colors
 #[test]
 fn test_gradient_linear_interpolation() {
@@ -94,6 +94,10 @@ impl<'a> Comps3D<'a> {
 }
 
 impl<'a, S> Comps<'a, S> {
+    pub fn get_t(&self) -> Real {
+        self.t
+    }
+
     pub fn get_over_point(&self) -> Point {
         Comps::<'a, S>::expect_or_invalid(self.over_point)
     }
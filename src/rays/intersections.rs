@@ -18,7 +18,14 @@ pub type Intersection3D = Intersection<Geo>;
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum IntersectionKind {
     Regular,
-    Triangle { u: Real, v: Real },
+
+    /// A hit on a triangle, with the barycentric coordinates of the hit.
+    /// For meshes, `index` is the triangle that was hit within the mesh.
+    Triangle {
+        u: Real,
+        v: Real,
+        index: usize,
+    },
 }
 
 impl<S: Clone + PartialEq> Intersection<S> {
@@ -79,7 +86,7 @@ impl Intersection3D {
             t,
             Rc::clone(&shape),
             if let Geo::Triangle(_) = shape.geo {
-                IntersectionKind::Triangle { u, v }
+                IntersectionKind::Triangle { u, v, index: 0 }
             } else {
                 IntersectionKind::Regular
             },
@@ -238,7 +245,7 @@ mod tests {
         );
         let intersection = Intersection::new_with_uv(3.5, Rc::new(shape), 0.2, 0.4);
 
-        if let IntersectionKind::Triangle { u, v } = intersection.get_kind() {
+        if let IntersectionKind::Triangle { u, v, .. } = intersection.get_kind() {
             assert_eq!(u, 0.2);
             assert_eq!(v, 0.4);
         } else {
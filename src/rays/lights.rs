@@ -1,4 +1,5 @@
 use crate::math::random::{RandGen, SeqRand};
+use crate::math::sampling::Sampler;
 use crate::math::Real;
 use crate::scene::World3D;
 use crate::tuples::colors::Color;
@@ -51,6 +52,7 @@ pub struct AreaLight {
     samples: Step,
     position: Point,
     jitter_by: RandGen,
+    sampler: Sampler,
 }
 
 type Step = usize;
@@ -90,6 +92,7 @@ impl AreaLight {
             intensity,
             position: mid_point,
             jitter_by,
+            sampler: Sampler::default(),
         }
     }
 
@@ -112,23 +115,56 @@ impl AreaLight {
         )
     }
 
+    /// Changes how the samples are spread over the light. By default each
+    /// cell of the light is sampled once, at a jittered position.
+    pub fn sampler(mut self, sampler: Sampler) -> AreaLight {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Starts the jittering over from the given stream, so that a seeded
+    /// light samples the same positions for the same pixel
+    pub fn restart(&self, stream: u64) {
+        self.jitter_by.restart(stream)
+    }
+
     pub fn point_on_light(&self, u: Step, v: Step) -> Point {
         self.corner
             + self.u_vec * (u as Real + self.jitter_by.next())
             + self.v_vec * (v as Real + self.jitter_by.next())
     }
 
-    pub fn intensity_at(&self, point: Point, world: &World3D) -> Real {
-        let mut total = 0.0;
-        for v in 0..self.v_steps {
-            for u in 0..self.u_steps {
-                let light_position = self.point_on_light(u, v);
-                if !world.is_shadowed(light_position, point) {
-                    total += 1.0;
-                }
-            }
+    /// The positions sampled on the light, one for each cell
+    pub fn sample_points(&self) -> Vec<Point> {
+        if self.sampler == Sampler::Stratified {
+            let cells = (0..self.v_steps).flat_map(|v| (0..self.u_steps).map(move |u| (u, v)));
+            return cells.map(|(u, v)| self.point_on_light(u, v)).collect();
         }
-        total / self.samples as Real
+        let (full_u_vec, full_v_vec) = (self.get_full_u_vec(), self.get_full_v_vec());
+        self.sampler
+            .pattern((self.u_steps, self.v_steps), &self.jitter_by)
+            .into_iter()
+            .map(|(u, v)| self.corner + full_u_vec * u + full_v_vec * v)
+            .collect()
+    }
+
+    pub fn intensity_at(&self, point: Point, world: &World3D) -> Real {
+        let lit = self
+            .sample_points()
+            .into_iter()
+            .filter(|&light_position| !world.is_shadowed(light_position, point))
+            .count();
+        lit as Real / self.samples as Real
+    }
+
+    /// The edge of the light along u, spanning all the steps
+    pub fn get_full_u_vec(&self) -> Vector {
+        self.u_vec * self.u_steps as Real
+    }
+
+    /// The edge of the light along v, spanning all the steps
+    pub fn get_full_v_vec(&self) -> Vector {
+        self.v_vec * self.v_steps as Real
     }
 
     pub fn get_samples(&self) -> Step {
@@ -143,6 +179,7 @@ impl AreaLight {
 #[cfg(test)]
 mod tests {
     use crate::math::random::{RandGen, SeqRand};
+    use crate::math::sampling::Sampler;
     use crate::rays::lights::{AreaLight, PointLight};
     use crate::scene::World;
     use crate::tuples::colors::Color;
@@ -242,4 +279,32 @@ mod tests {
             assert_eq!(point, result);
         }
     }
+
+    #[test]
+    fn test_seeded_lights_sample_the_same_points_again() {
+        let corner = Point::origin();
+        let v1 = vectors::new(2.0, 0.0, 0.0);
+        let v2 = vectors::new(0.0, 0.0, 1.0);
+        for sampler in [
+            Sampler::Random,
+            Sampler::Stratified,
+            Sampler::Halton,
+            Sampler::Sobol,
+        ] {
+            let light = AreaLight::new(corner, v1, 4, v2, 2, Color::white(), RandGen::seeded(3))
+                .sampler(sampler);
+            light.restart(10);
+            let first = light.sample_points();
+            light.restart(11);
+            let other = light.sample_points();
+            light.restart(10);
+
+            assert_eq!(first.len(), 8);
+            assert_eq!(light.sample_points(), first);
+            assert_ne!(other, first);
+            assert!(first
+                .iter()
+                .all(|p| (0.0..2.0).contains(&p.x) && p.y == 0.0 && (0.0..1.0).contains(&p.z)));
+        }
+    }
 }
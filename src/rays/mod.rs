@@ -2,7 +2,7 @@ pub use comps::*;
 pub use intersections::{Intersection, Intersection3D, IntersectionKind};
 
 use crate::math::Real;
-use crate::matrix::Matrix;
+use crate::matrix::Matrix44;
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
 
@@ -25,9 +25,9 @@ impl Ray {
         self.origin + self.direction * t
     }
 
-    pub(crate) fn transform(&self, transformation: Matrix) -> Ray {
+    pub(crate) fn transform(&self, transformation: Matrix44) -> Ray {
         Ray::new(
-            transformation.clone() * self.origin,
+            transformation * self.origin,
             transformation * self.direction,
         )
     }
@@ -1,5 +1,6 @@
 use std::fs;
 
+use crate::canvas::tone::ToneMap;
 use crate::materials::Material;
 use crate::math;
 use crate::math::random::RandGen;
@@ -7,16 +8,40 @@ use crate::math::Real;
 use crate::matrix::{scaling, translation, view_transformation, CanTransform};
 use crate::patterns::Pattern;
 use crate::rays::lights::{AreaLight, PointLight};
+use crate::scene::aovs::Aov;
 use crate::scene::camera::Camera;
-use crate::scene::World3D;
+use crate::scene::{stats, World3D};
 use crate::shapes::cylinders::CylLike;
-use crate::shapes::{Geo, Shape};
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::Shape;
 use crate::tuples::colors::Color;
 use crate::tuples::points::Point;
 use crate::tuples::{colors, points, vectors};
 use std::rc::Rc;
 
 pub(crate) fn render_scene() {
+    let ((), stats) = stats::collect(|| {
+        let (world, mut camera) = stats::timed("build", || showcase(1000, 600));
+        camera.aovs = Aov::ALL.to_vec();
+        let layers = camera.render_layers(world, true);
+        let canvas = &layers.beauty;
+
+        stats::timed("encode", || {
+            fs::write("erena.hdr", canvas.to_hdr().to_bytes()).expect("Can not save HDR image");
+            fs::write("erena-layers.exr", layers.to_exr().to_bytes())
+                .expect("Can not save the layers");
+
+            let canvas = canvas.tone_map(ToneMap::Clamp, 0.0);
+            fs::write("erena.ppm", canvas.to_ppm().to_string()).expect("Can not render scene");
+        })
+    });
+
+    print!("{}", stats.summary());
+    fs::write("erena-stats.json", stats.to_json()).expect("Can not save the statistics");
+}
+
+/// The scene shown in the README, viewed with a camera of the given size
+pub(crate) fn showcase(width: usize, height: usize) -> (World3D, Camera) {
     let floor = Shape::plane().material(
         Material::default()
             .pattern(Pattern::checkers(
@@ -39,27 +64,25 @@ pub(crate) fn render_scene() {
             full_vec,
             4,
             Color::white(),
-            RandGen::Live,
+            RandGen::seeded(0),
         )),
     );
-    world.add_objects_refs(vec![
-        &right(),
-        &bottom(),
-        &cylinders(),
-        &glasses(),
-        &triangles(),
+    world.add_objects(vec![
+        &*right(),
+        &*bottom(),
+        &*cylinders(),
+        &*glasses(),
+        &*triangles(),
     ]);
 
-    let mut camera = Camera::new(1000, 600, math::PI / 3.0);
+    let mut camera = Camera::new(width, height, math::PI / 3.0);
     camera.transformation = view_transformation(
         points::new(0.0, 1.5, -5.0),
         points::new(0.0, 1.0, 0.0),
         vectors::new(0.0, 1.0, 0.0),
     );
 
-    let canvas = camera.render(world, true);
-
-    fs::write("erena.ppm", canvas.to_ppm().to_string()).expect("Can not render scene");
+    (world, camera)
 }
 
 fn middle() -> Shape {
@@ -103,14 +126,7 @@ fn right() -> Rc<Shape> {
             ),
         );
 
-    let group = Rc::new(Shape::empty_group());
-    if let Geo::Group(g) = &group.geo {
-        g.add_children(
-            Rc::downgrade(&group),
-            vec![Rc::new(right_sphere), Rc::new(cube)],
-        );
-    }
-    group
+    Shape::group(vec![right_sphere, cube])
 }
 
 fn bottom() -> Rc<Shape> {
@@ -128,6 +144,8 @@ fn bottom() -> Rc<Shape> {
                 .reflective(0.5),
         );
 
+    // the small spheres share the geometry of the left sphere
+    let prototype = Rc::new(left.clone());
     let mut small_spheres: Vec<Shape> = vec![];
     for i in 0..5 {
         let component_scale = 0.5 + 0.1 * (i as Real);
@@ -135,33 +153,22 @@ fn bottom() -> Rc<Shape> {
             colors::new(1.0, 0.8, 0.1),
             colors::new(220.0 / 255.0, 20.0 / 255.0, 60.0 / 255.0),
         );
+        let material = Material::default()
+            .color(colors::new(0.5, 0.6, 1.0))
+            .diffuse(0.7)
+            .specular(0.3)
+            .pattern_opt(if i % 2 == 0 { Some(pattern) } else { None })
+            .reflective(0.5);
         small_spheres.push(
-            left.clone()
-                .transform(
-                    translation(i as Real, 0.0, 0.0)
-                        * scaling(component_scale, component_scale, component_scale),
-                )
-                .material(
-                    Material::default()
-                        .color(colors::new(0.5, 0.6, 1.0))
-                        .diffuse(0.7)
-                        .specular(0.3)
-                        .pattern_opt(if i % 2 == 0 { Some(pattern) } else { None })
-                        .reflective(0.5),
-                ),
+            Shape::instance_with_material(Rc::clone(&prototype), material).transform(
+                translation(i as Real, 0.0, 0.0)
+                    * scaling(component_scale, component_scale, component_scale),
+            ),
         )
     }
 
-    let group = Rc::new(Shape::empty_group());
-
-    if let Geo::Group(g) = &group.geo {
-        g.add_child(Rc::downgrade(&group), Rc::new(left));
-        small_spheres
-            .into_iter()
-            .for_each(|sphere| g.add_child(Rc::downgrade(&group), Rc::new(sphere)));
-    }
-
-    group
+    small_spheres.insert(0, left);
+    Shape::group(small_spheres)
 }
 
 fn cylinders() -> Rc<Shape> {
@@ -208,14 +215,7 @@ fn cylinders() -> Rc<Shape> {
         cyls.push(new_cyl);
     }
 
-    let group = Rc::new(Shape::empty_group());
-    if let Geo::Group(g) = &group.geo {
-        g.add_children(
-            Rc::downgrade(&group),
-            cyls.into_iter().map(|cyl| Rc::new(cyl)).collect(),
-        );
-    }
-    group
+    Shape::group(cyls)
 }
 
 fn glasses() -> Rc<Shape> {
@@ -240,14 +240,7 @@ fn glasses() -> Rc<Shape> {
         .transform(translation(0.7, 1.25, -1.5) * scaling(0.15, 0.15, 0.15))
         .material(Material::glass());
 
-    let group = Rc::new(Shape::empty_group());
-    if let Geo::Group(g) = &group.geo {
-        g.add_child(Rc::downgrade(&group), Rc::new(upper_base));
-        g.add_child(Rc::downgrade(&group), Rc::new(body));
-        g.add_child(Rc::downgrade(&group), Rc::new(sphere));
-        g.add_child(Rc::downgrade(&group), Rc::new(small_sphere));
-    }
-    group
+    Shape::group(vec![upper_base, body, sphere, small_sphere])
 }
 
 fn cones() -> Vec<Shape> {
@@ -332,20 +325,8 @@ fn triangles() -> Rc<Shape> {
         .scale_all(0.17)
         .translate(0.3, 0.17, 0.5);
 
-    let groups = Rc::new(Shape::empty_group().translate(4.5, 0.0, 4.0));
-    if let Geo::Group(g) = &groups.geo {
-        g.add_children(
-            Rc::downgrade(&groups),
-            vec![
-                Rc::new(side1),
-                Rc::new(side2),
-                Rc::new(side3),
-                Rc::new(side4),
-                Rc::new(sphere),
-                Rc::new(cylinder),
-                Rc::new(cube),
-            ],
-        );
-    }
-    groups
+    GroupBuilder::new()
+        .children(vec![side1, side2, side3, side4, sphere, cylinder, cube])
+        .translate(4.5, 0.0, 4.0)
+        .build()
 }
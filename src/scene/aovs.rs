@@ -0,0 +1,167 @@
+//! Arbitrary output variables: the parts of the shaded image, rendered
+//! next to it for compositing.
+
+use std::fs;
+use std::io;
+
+use crate::canvas::exr::{Channel, Exr};
+use crate::canvas::Canvas;
+use crate::rays::{Comps3D, Intersection, Ray};
+use crate::scene::debug::object_color;
+use crate::scene::{stats, Shading, World3D, DEFAULT_DEPTH};
+use crate::tuples::colors;
+use crate::tuples::colors::Color;
+
+/// A buffer the camera can render along with the shaded image. The
+/// lighting buffers add up to it: diffuse + specular + reflection +
+/// refraction. Where the camera rays miss, all the buffers are black.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Aov {
+    /// The ambient and diffuse light, including the image-based lighting
+    Diffuse,
+
+    /// The specular highlights of the light
+    Specular,
+
+    /// The reflected color, weighted by the Fresnel effect on glass
+    Reflection,
+
+    /// The refracted color, weighted by the Fresnel effect on glass
+    Refraction,
+
+    /// The share of the light that is blocked, from 0 for lit points
+    /// to 1 for points in full shadow
+    Shadow,
+
+    /// The surface color, patterns included, before any lighting
+    Albedo,
+
+    /// The shading normal in world space, with components in [-1, 1]
+    Normal,
+
+    /// The distance `t` along the camera ray
+    Depth,
+
+    /// A flat color for each object, as in the object IDs debug mode
+    ObjectId,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 9] = [
+        Aov::Diffuse,
+        Aov::Specular,
+        Aov::Reflection,
+        Aov::Refraction,
+        Aov::Shadow,
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::ObjectId,
+    ];
+
+    /// The name of the layer in multi-layer files, and the suffix of the
+    /// files the layer is saved to on its own
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Diffuse => "diffuse",
+            Aov::Specular => "specular",
+            Aov::Reflection => "reflection",
+            Aov::Refraction => "refraction",
+            Aov::Shadow => "shadow",
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::ObjectId => "object_id",
+        }
+    }
+}
+
+impl World3D {
+    /// The color seen by the ray, the same as `default_color_at`, along
+    /// with the value of each of the given AOVs
+    pub fn aovs_at(&self, ray: &Ray, aovs: &[Aov]) -> (Color, Vec<Color>) {
+        stats::record(|stats| stats.traced_at(0));
+        let xs = self.intersect(ray);
+        let hit = match Intersection::hit(xs.clone()) {
+            None => {
+                let background = self.background.at(ray.direction);
+                return (background, vec![Color::black(); aovs.len()]);
+            }
+            Some(hit) => hit,
+        };
+        let comps = Comps3D::prepare(&hit, ray, &xs);
+        let object = comps.get_object();
+        let albedo = object.material.color_at(object, comps.get_over_point());
+        let normal = comps.get_normal_vec();
+        let (t, id) = (comps.get_t(), object_color(object));
+
+        let shading = self.shade_terms(comps, DEFAULT_DEPTH);
+        let beauty = shading.map_or_else(Color::black, |shading| shading.total());
+        let lighting = |f: fn(Shading) -> Color| shading.map_or_else(Color::black, f);
+        let values = aovs
+            .iter()
+            .map(|aov| match aov {
+                Aov::Diffuse => lighting(|shading| shading.diffuse),
+                Aov::Specular => lighting(|shading| shading.specular),
+                Aov::Reflection => lighting(|shading| shading.reflection),
+                Aov::Refraction => lighting(|shading| shading.refraction),
+                Aov::Shadow => {
+                    let blocked = shading.map_or(1.0, |shading| 1.0 - shading.visibility);
+                    colors::new(blocked, blocked, blocked)
+                }
+                Aov::Albedo => albedo,
+                Aov::Normal => colors::new(normal.x, normal.y, normal.z),
+                Aov::Depth => colors::new(t, t, t),
+                Aov::ObjectId => id,
+            })
+            .collect();
+        (beauty, values)
+    }
+}
+
+/// The shaded image, with the AOVs the camera rendered next to it
+pub struct Layers {
+    pub beauty: Canvas,
+    pub aovs: Vec<(Aov, Canvas)>,
+}
+
+impl Layers {
+    pub fn get(&self, aov: Aov) -> Option<&Canvas> {
+        self.aovs
+            .iter()
+            .find(|(other, _)| *other == aov)
+            .map(|(_, canvas)| canvas)
+    }
+
+    /// All the layers in a single EXR image. The shaded image is in the
+    /// R, G and B channels, and each AOV in channels named after it, such
+    /// as `diffuse.R`. The depth only has the one channel, `depth.Z`.
+    pub fn to_exr(&self) -> Exr {
+        let mut channels = self.beauty.to_exr_channels("");
+        for (aov, canvas) in &self.aovs {
+            channels.extend(aov_channels(*aov, canvas, &format!("{}.", aov.name())));
+        }
+        Exr::new(self.beauty.width, self.beauty.height, channels)
+    }
+
+    /// Saves each layer to its own EXR image: the shaded image to
+    /// `<stem>.exr`, and the AOVs to files such as `<stem>.diffuse.exr`
+    pub fn save_separately(&self, stem: &str) -> io::Result<()> {
+        fs::write(format!("{}.exr", stem), self.beauty.to_exr().to_bytes())?;
+        for (aov, canvas) in &self.aovs {
+            let exr = Exr::new(canvas.width, canvas.height, aov_channels(*aov, canvas, ""));
+            fs::write(format!("{}.{}.exr", stem, aov.name()), exr.to_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn aov_channels(aov: Aov, canvas: &Canvas, prefix: &str) -> Vec<Channel> {
+    if aov != Aov::Depth {
+        return canvas.to_exr_channels(prefix);
+    }
+    let values = (0..canvas.height)
+        .flat_map(|y| (0..canvas.width).map(move |x| canvas[(x, y)].x as f32))
+        .collect();
+    vec![Channel::new(&format!("{}Z", prefix), values)]
+}
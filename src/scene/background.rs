@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use crate::canvas::Canvas;
+use crate::math;
+use crate::tuples::colors::Color;
+use crate::tuples::vectors::Vector;
+
+/// What a ray sees when it doesn't hit anything
+#[derive(Debug, Clone)]
+pub enum Background {
+    Solid(Color),
+
+    /// A vertical gradient that blends from `bottom` (looking straight down)
+    /// to `top` (looking straight up)
+    Gradient {
+        top: Color,
+        bottom: Color,
+    },
+
+    /// An equirectangular (latitude-longitude) environment image.
+    /// The center of the image is along the +z axis.
+    Environment(Rc<Canvas>),
+}
+
+impl Background {
+    pub fn black() -> Background {
+        Background::Solid(Color::black())
+    }
+
+    pub fn gradient(top: Color, bottom: Color) -> Background {
+        Background::Gradient { top, bottom }
+    }
+
+    pub fn environment(image: Canvas) -> Background {
+        Background::Environment(Rc::new(image))
+    }
+
+    pub fn at(&self, direction: Vector) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { top, bottom } => {
+                let t = 0.5 * (direction.normalize().y + 1.0);
+                *bottom * (1.0 - t) + *top * t
+            }
+            Background::Environment(image) => {
+                let direction = direction.normalize();
+                let u = 0.5 + direction.x.atan2(direction.z) / (2.0 * math::PI);
+                let v = direction.y.clamp(-1.0, 1.0).acos() / math::PI;
+                image.sample(u, v)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::scene::background::Background;
+    use crate::tuples::colors::Color;
+    use crate::tuples::{colors, vectors};
+
+    #[test]
+    fn test_solid_background() {
+        let background = Background::Solid(Color::red());
+        assert_eq!(background.at(vectors::new(0.3, -1.0, 2.0)), Color::red());
+    }
+
+    #[test]
+    fn test_gradient_background() {
+        let background = Background::gradient(Color::white(), Color::black());
+        assert_eq!(background.at(vectors::new(0.0, 1.0, 0.0)), Color::white());
+        assert_eq!(background.at(vectors::new(0.0, -2.0, 0.0)), Color::black());
+        assert_eq!(
+            background.at(vectors::new(0.0, 0.0, 1.0)),
+            colors::new(0.5, 0.5, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_environment_background() {
+        // top half is white, bottom half is red. The middle column is green.
+        let mut image = Canvas::new(5, 2);
+        for x in 0..5 {
+            image[(x, 0)] = Color::white();
+            image[(x, 1)] = Color::red();
+        }
+        image[(2, 0)] = colors::new(0.0, 1.0, 0.0);
+
+        let background = Background::environment(image);
+        assert_eq!(
+            background.at(vectors::new(0.0, 1.0, 0.01)),
+            colors::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(background.at(vectors::new(0.0, -1.0, -0.01)), Color::red());
+        assert_eq!(background.at(vectors::new(0.0, 1.0, -0.01)), Color::white());
+    }
+}
@@ -1,7 +1,15 @@
 use crate::canvas::Canvas;
-use crate::matrix::Matrix;
+use crate::math;
+use crate::math::random::{shuffle, Pcg, RandGen};
+use crate::math::sampling::Sampler;
+use crate::math::Real;
+use crate::matrix::Matrix44;
 use crate::rays::Ray;
-use crate::scene::World3D;
+use crate::scene::aovs::{Aov, Layers};
+use crate::scene::debug::{heatmap, RenderMode};
+use crate::scene::stats::RenderStats;
+use crate::scene::{stats, World3D};
+use crate::tuples::colors::Color;
 use crate::tuples::points;
 use crate::tuples::points::Point;
 
@@ -9,7 +17,32 @@ pub struct Camera {
     pub width: usize,
     pub height: usize,
     pub field_of_view: f64,
-    pub transformation: Matrix,
+    pub transformation: Matrix44,
+
+    /// Rays per pixel along each axis, when antialiasing or with depth of field
+    pub samples: usize,
+
+    /// How the rays are spread over each pixel and over the lens
+    pub sampler: Sampler,
+
+    /// Seeds the samples over the pixels and the lens, so renders with the
+    /// same seed are identical. The seed doesn't reach the light: a jittered
+    /// area light also needs its own seeded generator (`RandGen::seeded`),
+    /// otherwise its samples change from one render to the next.
+    pub seed: u64,
+
+    /// The radius of the lens. Points away from the focal distance are
+    /// blurred when it is larger than 0.
+    pub aperture: Real,
+
+    /// The distance from the camera to the plane in focus
+    pub focal_distance: Real,
+
+    /// Whether to shade the scene, or to show one of the debug views
+    pub mode: RenderMode,
+
+    /// The buffers `render_layers` renders along with the shaded image
+    pub aovs: Vec<Aov>,
     pixel_size: f64,
     half_width: f64,
     half_height: f64,
@@ -32,7 +65,14 @@ impl Camera {
             width,
             height,
             field_of_view,
-            transformation: Matrix::id44(),
+            transformation: Matrix44::id(),
+            samples: 2,
+            sampler: Sampler::default(),
+            seed: 0,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            mode: RenderMode::default(),
+            aovs: vec![],
             pixel_size,
             half_width,
             half_height,
@@ -40,60 +80,173 @@ impl Camera {
     }
 
     fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        self.ray_for_sample(x, y, (0.5, 0.5), (0.0, 0.0))
+    }
+
+    /// The ray through the given position on the pixel, both in the unit
+    /// square, starting from the given position on the lens
+    fn ray_for_sample(&self, x: usize, y: usize, offset: (Real, Real), lens: (Real, Real)) -> Ray {
+        stats::record(|stats| stats.primary_rays += 1);
         // compute the offsets from
-        let x_offset = (x as f64 + 0.5) * self.pixel_size;
-        let y_offset = (y as f64 + 0.5) * self.pixel_size;
+        let x_offset = (x as f64 + offset.0) * self.pixel_size;
+        let y_offset = (y as f64 + offset.1) * self.pixel_size;
 
         // compute the untransformed coordinates of the pixel in the world space
         // note: the scene.camera looks toward -z, so +x is to the left
         let world_x = self.half_width - x_offset;
         let world_y = self.half_height - y_offset;
 
-        let inverse = self.transformation.inverse_or_id44();
-        let pixel = &inverse * points::new(world_x, world_y, -1.0);
-        let origin = inverse * Point::origin();
-        let direction = (pixel - origin).normalize();
+        let inverse = self.transformation.inverse_or_id();
+        if self.aperture <= 0.0 {
+            let pixel = inverse * points::new(world_x, world_y, -1.0);
+            let origin = inverse * Point::origin();
+            let direction = (pixel - origin).normalize();
+            return Ray::new(origin, direction);
+        }
 
+        // all the rays through the pixel meet again at the focal distance
+        let focus = points::new(world_x, world_y, -1.0).to_vector() * self.focal_distance;
+        let (radius, theta) = (self.aperture * lens.0.sqrt(), lens.1 * 2.0 * math::PI);
+        let on_lens = points::new(radius * theta.cos(), radius * theta.sin(), 0.0);
+        let origin = inverse * on_lens;
+        let direction = (inverse * focus.to_point() - origin).normalize();
         Ray::new(origin, direction)
     }
 
+    /// Renders the world, one pixel after the other. With antialiasing
+    /// (or a lens with an aperture), each pixel averages `samples` x `samples`
+    /// rays. The numbers used for the pixel only depend on the seed and on
+    /// the pixel, and so do the ones of a light with a seeded generator, so
+    /// renders can be repeated exactly.
     pub(crate) fn render(&self, world: World3D, antialias: bool) -> Canvas {
-        if antialias {
-            // set to higher resolution first if antialiasing is considered
-            let mut high_res_cam = Camera::new(self.width * 2, self.height * 2, self.field_of_view);
-            high_res_cam.transformation = self.transformation.clone();
-
-            let high_res_canvas = high_res_cam.render(world, false);
-            let mut canvas = Canvas::new(self.width, self.height);
-
-            // apply down-sampling to remove jagged edges
-            for y in 0..canvas.height {
-                let source_y = y * 2;
-                for x in 0..canvas.width {
-                    let source_x = x * 2;
-
-                    // computes the average...
-                    canvas[(x, y)] = (high_res_canvas[(source_x, source_y)]
-                        + high_res_canvas[(source_x, source_y + 1)]
-                        + high_res_canvas[(source_x + 1, source_y)]
-                        + high_res_canvas[(source_x + 1, source_y + 1)])
-                        * 0.25;
+        self.render_layers(world, antialias).beauty
+    }
+
+    /// Renders the world, like `render`, along with the AOVs of the camera.
+    /// The AOVs average the same rays as the shaded image. In the debug
+    /// modes, they come from the ray through the center of each pixel, like
+    /// the debug view.
+    pub(crate) fn render_layers(&self, world: World3D, antialias: bool) -> Layers {
+        stats::timed("render", || self.render_pixels(world, antialias))
+    }
+
+    /// Renders the world while counting the rays and intersection tests
+    pub(crate) fn render_with_stats(
+        &self,
+        world: World3D,
+        antialias: bool,
+    ) -> (Canvas, RenderStats) {
+        stats::collect(|| self.render(world, antialias))
+    }
+
+    fn render_pixels(&self, world: World3D, antialias: bool) -> Layers {
+        world.finalize();
+        if self.mode != RenderMode::Shaded {
+            return self.render_debug(&world);
+        }
+        let canvas = || Canvas::new(self.width, self.height);
+        let mut image = canvas();
+        let mut aovs: Vec<Canvas> = self.aovs.iter().map(|_| canvas()).collect();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = math::index_of(x, y, self.width) as u64;
+                world.restart_sampling(pixel);
+                let rays = self.pixel_rays(x, y, pixel, antialias);
+                let count = rays.len() as Real;
+                if self.aovs.is_empty() {
+                    let total = rays.iter().fold(Color::black(), |total, ray| {
+                        total + world.default_color_at(ray)
+                    });
+                    image[(x, y)] = total / count;
+                    continue;
+                }
+
+                let mut totals = vec![Color::black(); aovs.len()];
+                let mut total = Color::black();
+                for ray in &rays {
+                    let (color, values) = world.aovs_at(ray, &self.aovs);
+                    total = total + color;
+                    for (total, value) in totals.iter_mut().zip(values) {
+                        *total = *total + value;
+                    }
+                }
+                image[(x, y)] = total / count;
+                for (canvas, total) in aovs.iter_mut().zip(totals) {
+                    canvas[(x, y)] = total / count;
                 }
             }
+        }
 
-            canvas
-        } else {
-            let mut image = Canvas::new(self.width, self.height);
+        Layers {
+            beauty: image,
+            aovs: self.aovs.iter().copied().zip(aovs).collect(),
+        }
+    }
 
-            for y in 0..self.height {
-                for x in 0..self.width {
-                    let ray = self.ray_for_pixel(x, y);
-                    image[(x, y)] = world.default_color_at(&ray);
+    /// Renders one of the debug views, with a single ray through the
+    /// center of each pixel, so the values aren't blended. The AOVs use
+    /// the same ray.
+    fn render_debug(&self, world: &World3D) -> Layers {
+        let canvas = || Canvas::new(self.width, self.height);
+        let mut image = canvas();
+        let mut aovs: Vec<Canvas> = self.aovs.iter().map(|_| canvas()).collect();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ray = self.ray_for_pixel(x, y);
+                image[(x, y)] = world.debug_color_at(&ray, self.mode);
+                if self.aovs.is_empty() {
+                    continue;
+                }
+
+                world.restart_sampling(math::index_of(x, y, self.width) as u64);
+                let (_, values) = world.aovs_at(&ray, &self.aovs);
+                for (canvas, value) in aovs.iter_mut().zip(values) {
+                    canvas[(x, y)] = value;
                 }
             }
+        }
+        if self.mode == RenderMode::Heatmap {
+            image = heatmap(&image);
+        }
+        Layers {
+            beauty: image,
+            aovs: self.aovs.iter().copied().zip(aovs).collect(),
+        }
+    }
 
-            image
+    /// The rays averaged for a pixel: the one through its center, or
+    /// `samples` x `samples` of them with antialiasing or an aperture
+    fn pixel_rays(&self, x: usize, y: usize, pixel: u64, antialias: bool) -> Vec<Ray> {
+        let samples = if antialias || self.aperture > 0.0 {
+            self.samples.max(1)
+        } else {
+            1
+        };
+        if samples == 1 {
+            return vec![self.ray_for_pixel(x, y)];
         }
+
+        let gen = RandGen::Pcg(Pcg::new(self.seed, pixel));
+        let grid = (samples, samples);
+        let offsets = if antialias {
+            self.sampler.pattern(grid, &gen)
+        } else {
+            vec![(0.5, 0.5); samples * samples]
+        };
+        let mut lens = if self.aperture > 0.0 {
+            self.sampler.pattern(grid, &gen)
+        } else {
+            vec![(0.0, 0.0); samples * samples]
+        };
+        // the lens samples are shuffled, or they would follow the pixel samples
+        shuffle(&mut lens, &gen);
+
+        offsets
+            .into_iter()
+            .zip(lens)
+            .map(|(offset, lens)| self.ray_for_sample(x, y, offset, lens))
+            .collect()
     }
 }
 
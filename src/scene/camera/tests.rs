@@ -1,7 +1,11 @@
 use crate::math;
-use crate::matrix::{rotation_y, translation, view_transformation, Matrix};
+use crate::math::random::RandGen;
+use crate::math::sampling::Sampler;
+use crate::matrix::{rotation_y, translation, view_transformation, Matrix44};
+use crate::rays::lights::AreaLight;
 use crate::scene::camera::Camera;
 use crate::scene::World;
+use crate::tuples::colors::Color;
 use crate::tuples::points::Point;
 use crate::tuples::{colors, points, vectors};
 
@@ -15,7 +19,7 @@ fn test_constructing_camera() {
     assert_eq!(camera.width, width);
     assert_eq!(camera.height, height);
     assert_eq!(camera.field_of_view, field_of_view);
-    assert_eq!(camera.transformation, Matrix::id44());
+    assert_eq!(camera.transformation, Matrix44::id());
 }
 
 /// Tests the pixel size for a horizontal canvas (i.e. a canvas
@@ -83,3 +87,63 @@ fn test_render_camera() {
         colors::new(0.38066, 0.47583, 0.28550)
     );
 }
+
+/// Tests that two renders with the same seed are identical, even with
+/// a jittered area light, and that the seed changes the noise
+#[test]
+fn test_seeded_renders_are_reproducible() {
+    let render = |sampler: Sampler, seed: u64| {
+        let mut world = World::default();
+        world.light = Some(
+            AreaLight::new(
+                points::new(-10.0, 10.0, -10.0),
+                vectors::new(2.0, 0.0, 0.0),
+                2,
+                vectors::new(0.0, 2.0, 0.0),
+                2,
+                Color::white(),
+                RandGen::seeded(seed),
+            )
+            .sampler(sampler),
+        );
+        let mut camera = Camera::new(11, 11, math::PI / 2.0);
+        camera.transformation = view_transformation(
+            points::new(0.0, 0.0, -5.0),
+            Point::origin(),
+            vectors::new(0.0, 1.0, 0.0),
+        );
+        camera.sampler = sampler;
+        camera.seed = seed;
+        camera.render(world, true)
+    };
+
+    for sampler in [
+        Sampler::Random,
+        Sampler::Stratified,
+        Sampler::Halton,
+        Sampler::Sobol,
+    ] {
+        let first = render(sampler, 1);
+        assert!(first == render(sampler, 1));
+        assert!(first != render(sampler, 2));
+    }
+}
+
+/// Tests that the rays of a lens with an aperture all meet at the focal distance
+#[test]
+fn test_rays_through_a_lens() {
+    let mut camera = Camera::new(201, 101, math::PI / 2.0);
+    camera.transformation = translation(0.0, -2.0, 0.0);
+    camera.aperture = 0.5;
+    camera.focal_distance = 4.0;
+
+    let focus = points::new(0.0, 2.0, -4.0);
+    for lens in [(0.0, 0.0), (1.0, 0.0), (0.5, 0.25), (0.3, 0.8)] {
+        let ray = camera.ray_for_sample(100, 50, (0.5, 0.5), lens);
+        let to_focus = (focus - ray.origin).to_vector();
+        assert!((ray.origin.y - 2.0).abs() <= 0.5);
+        assert!((ray.direction.dot(to_focus.normalize()) - 1.0).abs() < math::EPSILON);
+    }
+    let edge = camera.ray_for_sample(100, 50, (0.5, 0.5), (1.0, 0.0));
+    assert_eq!(edge.origin.round_items(), points::new(0.5, 2.0, 0.0));
+}
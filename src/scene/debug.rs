@@ -0,0 +1,145 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::canvas::Canvas;
+use crate::math::Real;
+use crate::rays::{Comps3D, Intersection, IntersectionKind, Ray};
+use crate::scene::{stats, World3D};
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::points::Point;
+use crate::tuples::{colors, points};
+
+/// What the camera shows. All the modes but `Shaded` skip the lighting
+/// and show one property of the surface seen through each pixel, with
+/// black where nothing is hit.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum RenderMode {
+    /// The lit and shaded scene
+    #[default]
+    Shaded,
+
+    /// The shading normal, facing the camera, with each component
+    /// mapped from [-1, 1] to [0, 1]
+    Normals,
+
+    /// The distance `t` along the camera ray, in all the channels. The
+    /// values aren't scaled, so they are best saved as HDR or EXR.
+    Depth,
+
+    /// The barycentric coordinates of triangle hits, as (1 - u - v, u, v)
+    Barycentric,
+
+    /// The texture coordinates of the surface, as (u, v, 0)
+    Uvs,
+
+    /// A flat color for each object
+    ObjectIds,
+
+    /// The number of intersection tests for the camera ray, from blue for
+    /// the fewest to red for the most in the image
+    Heatmap,
+}
+
+impl World3D {
+    /// The color seen by the ray in the given mode. In the heatmap mode,
+    /// this is the number of intersection tests, before the colors are
+    /// spread over the image with `heatmap`.
+    pub fn debug_color_at(&self, ray: &Ray, mode: RenderMode) -> Color {
+        match mode {
+            RenderMode::Shaded => return self.default_color_at(ray),
+            RenderMode::Heatmap => {
+                let (_, tests) = stats::collect(|| self.intersect(ray));
+                // the tests still count for the statistics of the whole render
+                stats::record(|stats| stats.merge(&tests));
+                let count = tests.total_tests() as Real;
+                return colors::new(count, count, count);
+            }
+            _ => (),
+        }
+
+        let xs = self.intersect(ray);
+        let hit = match Intersection::hit(xs.clone()) {
+            None => return Color::black(),
+            Some(hit) => hit,
+        };
+        let comps = Comps3D::prepare(&hit, ray, &xs);
+        match mode {
+            RenderMode::Normals => {
+                let normal = comps.get_normal_vec();
+                colors::new(
+                    (normal.x + 1.0) / 2.0,
+                    (normal.y + 1.0) / 2.0,
+                    (normal.z + 1.0) / 2.0,
+                )
+            }
+            RenderMode::Depth => {
+                let t = comps.get_t();
+                colors::new(t, t, t)
+            }
+            RenderMode::Barycentric => match hit.get_kind() {
+                IntersectionKind::Triangle { u, v, .. } => colors::new(1.0 - u - v, u, v),
+                IntersectionKind::Regular => Color::black(),
+            },
+            RenderMode::Uvs => {
+                let (u, v) = comps.get_object().uv_at(comps.get_point());
+                colors::new(u, v, 0.0)
+            }
+            RenderMode::ObjectIds => object_color(comps.get_object()),
+            RenderMode::Shaded | RenderMode::Heatmap => unreachable!(),
+        }
+    }
+}
+
+/// Hits carry copies of the objects, so objects are told apart by their
+/// kind, their color and where they are in world space
+pub(crate) fn object_color(object: &Shape) -> Color {
+    let mut hasher = DefaultHasher::new();
+    object.geo.kind().hash(&mut hasher);
+    let color = object.material.color;
+    let corners = [
+        Point::origin(),
+        points::new(1.0, 0.0, 0.0),
+        points::new(0.0, 1.0, 0.0),
+        points::new(0.0, 0.0, 1.0),
+    ];
+    let placement = corners.iter().map(|&p| object.world_to_object(p));
+    for value in placement
+        .flat_map(|p| [p.x, p.y, p.z])
+        .chain([color.x, color.y, color.z])
+    {
+        value.to_bits().hash(&mut hasher);
+    }
+
+    // a bright, saturated color from the hue
+    let hue = (hasher.finish() % 360) as Real / 60.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    colors::new(0.2 + 0.8 * r, 0.2 + 0.8 * g, 0.2 + 0.8 * b)
+}
+
+/// Turns the counts of the heatmap mode into colors, from blue through
+/// green to red, relative to the largest count in the image
+pub fn heatmap(counts: &Canvas) -> Canvas {
+    let (width, height) = (counts.width, counts.height);
+    let pixels = || (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+    let max = pixels().map(|idx| counts[idx].x).fold(0.0, Real::max);
+
+    let mut image = Canvas::new(width, height);
+    for idx in pixels() {
+        let heat = if max > 0.0 { counts[idx].x / max } else { 0.0 };
+        image[idx] = if heat < 0.5 {
+            colors::new(0.0, heat * 2.0, 1.0 - heat * 2.0)
+        } else {
+            colors::new(heat * 2.0 - 1.0, 2.0 - heat * 2.0, 0.0)
+        };
+    }
+    image
+}
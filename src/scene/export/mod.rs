@@ -0,0 +1,6 @@
+//! Writes scenes back out, as OBJ files (with their material libraries)
+//! and as YAML scene descriptions
+
+pub mod obj;
+pub mod tessellate;
+pub mod yaml;
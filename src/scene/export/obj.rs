@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::materials::Material;
+use crate::matrix::{CanTransform, Matrix44};
+use crate::scene::export::tessellate::{tessellate, Tessellation};
+use crate::scene::World3D;
+use crate::shapes::triangles::{Triangle, TriangleKind};
+use crate::shapes::uvs::Uv;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+
+/// The 1-based indices of a face corner: vertex, texture and normal
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// The triangles of one shape, in world space
+struct Part {
+    material: usize,
+    faces: Vec<[Corner; 3]>,
+}
+
+/// Collects shapes as triangles in world space, to write them out as an
+/// OBJ file and an MTL material library. Curved and infinite shapes are
+/// tessellated. Identical vertices, normals and texture coordinates are
+/// written once.
+pub struct ObjExport {
+    tessellation: Tessellation,
+    vertices: Vec<Point>,
+    normals: Vec<Vector>,
+    uvs: Vec<Uv>,
+    indices: HashMap<Vec<u64>, usize>,
+    parts: Vec<Part>,
+    materials: Vec<Material>,
+    skipped: usize,
+}
+
+impl ObjExport {
+    pub fn new() -> ObjExport {
+        ObjExport {
+            tessellation: Tessellation::new(),
+            vertices: vec![],
+            normals: vec![],
+            uvs: vec![],
+            indices: HashMap::new(),
+            parts: vec![],
+            materials: vec![],
+            skipped: 0,
+        }
+    }
+
+    pub fn tessellation(mut self, tessellation: Tessellation) -> Self {
+        self.tessellation = tessellation;
+        self
+    }
+
+    /// Adds all the objects of the world
+    pub fn world(mut self, world: &World3D) -> Self {
+        for object in &world.objects {
+            self.add(object, Matrix44::id(), None);
+        }
+        self
+    }
+
+    /// Adds a shape and all its descendants, as placed by its transformation
+    pub fn shape(mut self, shape: &Shape) -> Self {
+        self.add(shape, Matrix44::id(), None);
+        self
+    }
+
+    /// The number of shapes that couldn't be turned into triangles, such
+    /// as signed distance fields and blobs
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// `material` replaces the materials of the shapes, as instances do
+    fn add(&mut self, shape: &Shape, parent: Matrix44, material: Option<&Material>) {
+        let transformation = parent * *shape.get_transformation();
+        match &shape.geo {
+            Geo::Group(group) => {
+                for child in group.children.borrow().iter() {
+                    self.add(child, transformation, material);
+                }
+            }
+            Geo::Instance(instance) => {
+                // the outermost instance decides the material
+                let material = material.or(instance.material.as_ref());
+                self.add(&instance.shape, transformation, material);
+            }
+            geo => match tessellate(geo, self.tessellation) {
+                None => self.skipped += 1,
+                Some(triangles) => {
+                    let material = material.unwrap_or(&shape.material);
+                    self.add_triangles(&triangles, transformation, material)
+                }
+            },
+        }
+    }
+
+    fn add_triangles(
+        &mut self,
+        triangles: &[Triangle],
+        transformation: Matrix44,
+        material: &Material,
+    ) {
+        let normal_transformation = transformation.inverse_or_id().transpose();
+        let material = match self.materials.iter().position(|m| m == material) {
+            Some(index) => index,
+            None => {
+                self.materials.push(material.clone());
+                self.materials.len() - 1
+            }
+        };
+
+        let mut faces = vec![];
+        for triangle in triangles {
+            let points = [triangle.get_p1(), triangle.get_p2(), triangle.get_p3()];
+            let normals = match &triangle.kind {
+                TriangleKind::Regular { .. } => None,
+                TriangleKind::Smooth(smooth) => Some([smooth.n1, smooth.n2, smooth.n3]),
+            };
+            let uvs = triangle.get_uvs();
+
+            let mut face = [(0, None, None); 3];
+            for (i, corner) in face.iter_mut().enumerate() {
+                let point = transformation * points[i];
+                let v = self.index(&[point.x, point.y, point.z], |export| {
+                    export.vertices.push(point);
+                    export.vertices.len()
+                });
+                let vt = uvs.map(|uvs| {
+                    let uv = uvs[i];
+                    self.index(&[uv.0, uv.1, 0.0, 1.0], |export| {
+                        export.uvs.push(uv);
+                        export.uvs.len()
+                    })
+                });
+                let vn = normals.map(|normals| {
+                    let normal = (normal_transformation * normals[i]).normalize();
+                    self.index(&[normal.x, normal.y, normal.z, 2.0], |export| {
+                        export.normals.push(normal);
+                        export.normals.len()
+                    })
+                });
+                *corner = (v, vt, vn);
+            }
+            faces.push(face);
+        }
+        self.parts.push(Part { material, faces });
+    }
+
+    /// Finds the index of the values written before, or adds them. The
+    /// last component tells the kind of the values apart.
+    fn index<F>(&mut self, values: &[f64], add: F) -> usize
+    where
+        F: FnOnce(&mut ObjExport) -> usize,
+    {
+        let key: Vec<u64> = values.iter().map(|value| value.to_bits()).collect();
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+        let index = add(self);
+        self.indices.insert(key, index);
+        index
+    }
+
+    /// Writes the OBJ data. The faces refer to the materials of
+    /// `material_library`, if given.
+    pub fn write_obj<W: Write>(
+        &self,
+        mut out: W,
+        material_library: Option<&str>,
+    ) -> io::Result<()> {
+        if let Some(library) = material_library {
+            writeln!(out, "mtllib {}", library)?;
+        }
+        for point in &self.vertices {
+            writeln!(out, "v {} {} {}", point.x, point.y, point.z)?;
+        }
+        for uv in &self.uvs {
+            writeln!(out, "vt {} {}", uv.0, uv.1)?;
+        }
+        for normal in &self.normals {
+            writeln!(out, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+        }
+
+        for (i, part) in self.parts.iter().enumerate() {
+            writeln!(out, "g shape{}", i + 1)?;
+            if material_library.is_some() {
+                writeln!(out, "usemtl {}", material_name(part.material))?;
+            }
+            for face in &part.faces {
+                write!(out, "f")?;
+                for corner in face {
+                    match corner {
+                        (v, None, None) => write!(out, " {}", v)?,
+                        (v, Some(vt), None) => write!(out, " {}/{}", v, vt)?,
+                        (v, None, Some(vn)) => write!(out, " {}//{}", v, vn)?,
+                        (v, Some(vt), Some(vn)) => write!(out, " {}/{}/{}", v, vt, vn)?,
+                    }
+                }
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the materials the way `parse_mtl` reads them. Patterns and
+    /// textures are left out.
+    pub fn write_mtl<W: Write>(&self, mut out: W) -> io::Result<()> {
+        for (i, material) in self.materials.iter().enumerate() {
+            let Material { color, .. } = material;
+            writeln!(out, "newmtl {}", material_name(i))?;
+            writeln!(
+                out,
+                "Kd {} {} {}",
+                color.red_value(),
+                color.green_value(),
+                color.blue_value()
+            )?;
+            let specular = material.specular;
+            writeln!(out, "Ks {} {} {}", specular, specular, specular)?;
+            writeln!(out, "Ns {}", material.shininess)?;
+            writeln!(out, "d {}", 1.0 - material.transparency)?;
+            writeln!(out, "Ni {}", material.refractive_index)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the OBJ file, and the material library next to it with
+    /// the `.mtl` extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let library = path.with_extension("mtl");
+        let library_name = library
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("materials.mtl");
+
+        let mut obj = BufWriter::new(File::create(path)?);
+        self.write_obj(&mut obj, Some(library_name))?;
+        obj.flush()?;
+        let mut mtl = BufWriter::new(File::create(&library)?);
+        self.write_mtl(&mut mtl)?;
+        mtl.flush()
+    }
+}
+
+fn material_name(index: usize) -> String {
+    format!("material{}", index + 1)
+}
@@ -0,0 +1,186 @@
+use crate::math;
+use crate::math::Real;
+use crate::shapes::cylinders::CylLike;
+use crate::shapes::triangles::Triangle;
+use crate::shapes::Geo;
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use crate::tuples::{points, vectors};
+
+/// How finely curved and infinite shapes are turned into triangles
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tessellation {
+    /// The number of segments around a full circle
+    pub segments: usize,
+
+    /// Planes, and cylinders without limits, are cut off at this distance
+    /// from the origin
+    pub extent: Real,
+}
+
+impl Tessellation {
+    pub fn new() -> Tessellation {
+        Tessellation {
+            segments: 32,
+            extent: 100.0,
+        }
+    }
+}
+
+/// Turns a primitive into triangles, in object space. Groups and instances
+/// are left to the caller, and shapes without a surface that can be
+/// evaluated directly (signed distance fields, blobs) give `None`.
+pub(crate) fn tessellate(geo: &Geo, options: Tessellation) -> Option<Vec<Triangle>> {
+    let segments = options.segments.max(3);
+    let rings = (segments / 2).max(2);
+    let up = vectors::new(0.0, 1.0, 0.0);
+
+    let triangles = match geo {
+        Geo::Sphere => grid(segments, rings, |u, v| {
+            let (theta, phi) = (u * 2.0 * math::PI, v * math::PI);
+            let normal = vectors::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+            (normal.to_point(), normal)
+        }),
+        Geo::Plane => {
+            let extent = options.extent;
+            flat_quad((-extent, -extent), (extent, extent))
+        }
+        Geo::Cube => {
+            let axes = [
+                (vectors::new(1.0, 0.0, 0.0), vectors::new(0.0, 1.0, 0.0)),
+                (vectors::new(0.0, 1.0, 0.0), vectors::new(0.0, 0.0, 1.0)),
+                (vectors::new(0.0, 0.0, 1.0), vectors::new(1.0, 0.0, 0.0)),
+            ];
+            axes.iter()
+                .flat_map(|&(a, b)| {
+                    let normal = a.cross(b);
+                    [normal, -normal].map(|normal| {
+                        grid(1, 1, move |u, v| {
+                            let point = normal + a * (u * 2.0 - 1.0) + b * (v * 2.0 - 1.0);
+                            (point.to_point(), normal)
+                        })
+                    })
+                })
+                .flatten()
+                .collect()
+        }
+        Geo::Cylinder(cyl) => cylinder(cyl, segments, options.extent),
+        Geo::Triangle(tri) => vec![tri.clone()],
+        Geo::Torus(torus) => grid(segments, segments, |u, v| {
+            let (theta, phi) = (u * 2.0 * math::PI, v * 2.0 * math::PI);
+            let normal = vectors::new(phi.cos() * theta.cos(), phi.sin(), phi.cos() * theta.sin());
+            let center = vectors::new(theta.cos(), 0.0, theta.sin()) * torus.major_radius;
+            ((center + normal * torus.minor_radius).to_point(), normal)
+        }),
+        Geo::Disk(disk) => grid(segments, 1, |u, v| {
+            let theta = u * 2.0 * math::PI;
+            let radius = disk.inner_radius + (disk.radius - disk.inner_radius) * v;
+            (
+                points::new(theta.cos() * radius, 0.0, theta.sin() * radius),
+                up,
+            )
+        }),
+        Geo::Rect(rect) => flat_quad(rect.min, rect.max),
+        Geo::HeightField(field) => field.to_triangles(),
+        Geo::Mesh(mesh) => (0..mesh.len()).map(|i| mesh.triangle(i)).collect(),
+        Geo::Group(_)
+        | Geo::Instance(_)
+        | Geo::DistanceField(_)
+        | Geo::Blob(_)
+        | Geo::TestShape => return None,
+    };
+    Some(triangles)
+}
+
+fn flat_quad(min: (Real, Real), max: (Real, Real)) -> Vec<Triangle> {
+    grid(1, 1, |u, v| {
+        let point = points::new(
+            min.0 + (max.0 - min.0) * u,
+            0.0,
+            min.1 + (max.1 - min.1) * v,
+        );
+        (point, vectors::new(0.0, 1.0, 0.0))
+    })
+}
+
+fn cylinder(cyl: &CylLike, segments: usize, extent: Real) -> Vec<Triangle> {
+    let min = cyl.min.max(-extent);
+    let max = cyl.max.min(extent);
+
+    let radius = |y: Real| if cyl.cone { y.abs() } else { 1.0 };
+    let side = |from: Real, to: Real| {
+        // the normals of a cone point away from its apex
+        let slope = if cyl.cone { -(from + to).signum() } else { 0.0 };
+        grid(segments, 1, move |u, v| {
+            let theta = u * 2.0 * math::PI;
+            let y = from + (to - from) * v;
+            let normal = vectors::new(theta.cos(), slope, theta.sin()).normalize();
+            let point = points::new(theta.cos() * radius(y), y, theta.sin() * radius(y));
+            (point, normal)
+        })
+    };
+
+    // cones are split at their apex, where the normal flips
+    let mut triangles = if cyl.cone && min < 0.0 && max > 0.0 {
+        let mut triangles = side(min, 0.0);
+        triangles.extend(side(0.0, max));
+        triangles
+    } else {
+        side(min, max)
+    };
+
+    if cyl.closed {
+        for (y, direction) in [(min, -1.0), (max, 1.0)] {
+            let radius = radius(y);
+            triangles.extend(grid(segments, 1, |u, v| {
+                let theta = u * 2.0 * math::PI;
+                let point = points::new(theta.cos() * radius * v, y, theta.sin() * radius * v);
+                (point, vectors::new(0.0, direction, 0.0))
+            }));
+        }
+    }
+    triangles
+}
+
+/// Samples a surface over the unit square, with `columns` x `rows` quads.
+/// The function gives the point and the normal at (u, v). Triangles are
+/// wound so that their own normals agree with the sampled ones, and
+/// degenerate triangles (at the poles of a sphere, for example) are left out.
+fn grid<F>(columns: usize, rows: usize, f: F) -> Vec<Triangle>
+where
+    F: Fn(Real, Real) -> (Point, Vector),
+{
+    let sample =
+        |col: usize, row: usize| f(col as Real / columns as Real, row as Real / rows as Real);
+
+    let mut triangles = vec![];
+    for row in 0..rows {
+        for col in 0..columns {
+            let corners = [
+                sample(col, row),
+                sample(col + 1, row),
+                sample(col + 1, row + 1),
+                sample(col, row + 1),
+            ];
+            for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+                if let Some(triangle) = oriented(corners[a], corners[b], corners[c]) {
+                    triangles.push(triangle);
+                }
+            }
+        }
+    }
+    triangles
+}
+
+fn oriented(a: (Point, Vector), b: (Point, Vector), c: (Point, Vector)) -> Option<Triangle> {
+    let normal = (c.0 - a.0).to_vector().cross((b.0 - a.0).to_vector());
+    if normal.magnitude() < math::EPSILON * math::EPSILON {
+        return None;
+    }
+    let (b, c) = if normal.dot(a.1 + b.1 + c.1) < 0.0 {
+        (c, b)
+    } else {
+        (b, c)
+    };
+    Some(Triangle::smooth(a.0, b.0, c.0, a.1, b.1, c.1))
+}
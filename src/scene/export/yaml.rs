@@ -0,0 +1,388 @@
+//! The YAML scene description format, the one with `- add: sphere` entries.
+//!
+//! Transformations are written as the standard operations, in the order
+//! they apply: `scale`, `shear`, `rotate-x`, `rotate-y`, `rotate-z` and
+//! `translate`. Meshes become groups of triangles, and instances become
+//! groups around the shared shape. Patterns, the background and signed
+//! distance fields are left out, with a comment in their place.
+//!
+//! The shapes the format doesn't know about are written with these
+//! extensions, which other tools will skip or reject:
+//! - `torus` (`major-radius`, `minor-radius`), `disk` (`radius`,
+//!   `inner-radius`), `rect` (`min`, `max`), `blob` (`threshold` and
+//!   `metaballs` with a `center`, `radius` and `strength` each) and
+//!   `heightfield` (`columns`, `rows` and the `heights`, row by row)
+//! - `instance`, only for instances that replace the materials of the
+//!   shared shape, with an `override-material` and the shared `shape`
+//! - the `n1`, `n2` and `n3` normals of smooth triangles, and the `uvs`
+//!   of textured ones, as fields of a `triangle`
+
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::materials::Material;
+use crate::math::Real;
+use crate::matrix::{CanTransform, Matrix44};
+use crate::rays::lights::AreaLight;
+use crate::scene::background::Background;
+use crate::scene::camera::Camera;
+use crate::scene::World3D;
+use crate::shapes::triangles::TriangleKind;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::colors::Color;
+use crate::tuples::points::Point;
+use crate::tuples::vectors;
+use crate::tuples::vectors::Vector;
+
+/// Writes the scene, so it can be rendered or inspected by other tools
+pub fn write_scene<W: Write>(out: W, world: &World3D, camera: Option<&Camera>) -> io::Result<()> {
+    let mut writer = SceneWriter { out };
+    if let Some(camera) = camera {
+        writer.camera(camera)?;
+    }
+    if let Some(light) = world.get_light() {
+        writer.light(light)?;
+    }
+    writer.background(&world.background)?;
+    for object in &world.objects {
+        writer.shape(object, 0)?;
+    }
+    Ok(())
+}
+
+/// Writes the scene to a file, see `write_scene`
+pub fn save_scene<P: AsRef<Path>>(
+    path: P,
+    world: &World3D,
+    camera: Option<&Camera>,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_scene(&mut out, world, camera)?;
+    out.flush()
+}
+
+struct SceneWriter<W: Write> {
+    out: W,
+}
+
+fn point(p: Point) -> String {
+    format!("[{}, {}, {}]", p.x, p.y, p.z)
+}
+
+fn vector(v: Vector) -> String {
+    format!("[{}, {}, {}]", v.x, v.y, v.z)
+}
+
+fn color(c: Color) -> String {
+    format!(
+        "[{}, {}, {}]",
+        c.red_value(),
+        c.green_value(),
+        c.blue_value()
+    )
+}
+
+fn list(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<_>>().join(", "))
+}
+
+/// Rounds away the noise left by the decomposition of transformations
+fn number(value: Real) -> String {
+    // adding 0 turns -0 into 0
+    ((value * 1e10).round() / 1e10 + 0.0).to_string()
+}
+
+/// Splits an affine transformation into the operations of the format, in
+/// the order they apply. The linear part is factored with Gram-Schmidt
+/// as `rotation * shear * scale`, and a mirroring goes into the scale.
+/// The operations that do nothing are left out.
+pub(crate) fn operations(matrix: &Matrix44) -> Vec<(&'static str, Vec<Real>)> {
+    let dot = |a: [Real; 3], b: [Real; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let columns: Vec<[Real; 3]> = (0..3)
+        .map(|col| [matrix[(0, col)], matrix[(1, col)], matrix[(2, col)]])
+        .collect();
+
+    // the columns of the rotation, and the upper triangular rest
+    let mut q = [[0.0; 3]; 3];
+    let mut u = [[0.0; 3]; 3];
+    for j in 0..3 {
+        let mut v = columns[j];
+        for i in 0..j {
+            u[i][j] = dot(q[i], columns[j]);
+            for k in 0..3 {
+                v[k] -= u[i][j] * q[i][k];
+            }
+        }
+        u[j][j] = dot(v, v).sqrt();
+        for k in 0..3 {
+            q[j][k] = v[k] / u[j][j];
+        }
+    }
+    let cross =
+        vectors::new(q[0][0], q[0][1], q[0][2]).cross(vectors::new(q[1][0], q[1][1], q[1][2]));
+    if dot([cross.x, cross.y, cross.z], q[2]) < 0.0 {
+        q[2] = [-q[2][0], -q[2][1], -q[2][2]];
+        u[2][2] = -u[2][2];
+    }
+
+    // rotation = rotation_z(z) * rotation_y(y) * rotation_x(x)
+    let r = |row: usize, col: usize| q[col][row];
+    let y = (-r(2, 0)).clamp(-1.0, 1.0).asin();
+    let (x, z) = if r(2, 0).abs() < 1.0 - 1e-12 {
+        (r(2, 1).atan2(r(2, 2)), r(1, 0).atan2(r(0, 0)))
+    } else {
+        // gimbal lock: the x and z rotations turn around the same axis
+        ((-r(1, 2)).atan2(r(1, 1)), 0.0)
+    };
+
+    let scale = vec![u[0][0], u[1][1], u[2][2]];
+    let shear = vec![
+        u[0][1] / u[1][1],
+        u[0][2] / u[2][2],
+        0.0,
+        u[1][2] / u[2][2],
+        0.0,
+        0.0,
+    ];
+    let translate = vec![matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]];
+    let candidates = vec![
+        ("scale", scale, 1.0),
+        ("shear", shear, 0.0),
+        ("rotate-x", vec![x], 0.0),
+        ("rotate-y", vec![y], 0.0),
+        ("rotate-z", vec![z], 0.0),
+        ("translate", translate, 0.0),
+    ];
+    candidates
+        .into_iter()
+        .filter(|(_, values, identity)| values.iter().any(|&v| number(v) != number(*identity)))
+        .map(|(name, values, _)| (name, values))
+        .collect()
+}
+
+impl<W: Write> SceneWriter<W> {
+    /// Starts an entry of a list, indented by `indent` levels
+    fn entry(&mut self, indent: usize, key: &str, value: &str) -> io::Result<()> {
+        writeln!(self.out, "{}- {}: {}", "  ".repeat(indent), key, value)
+    }
+
+    /// Writes a field of the entry started at the same indentation. Fields
+    /// without a value start a nested list or mapping.
+    fn field(&mut self, indent: usize, key: &str, value: &str) -> io::Result<()> {
+        if value.is_empty() {
+            writeln!(self.out, "{}  {}:", "  ".repeat(indent), key)
+        } else {
+            writeln!(self.out, "{}  {}: {}", "  ".repeat(indent), key, value)
+        }
+    }
+
+    fn comment(&mut self, indent: usize, text: &str) -> io::Result<()> {
+        writeln!(self.out, "{}# {}", "  ".repeat(indent), text)
+    }
+
+    /// The camera's view transformation is turned back into the
+    /// position it looks from, the point it looks at and its up vector
+    fn camera(&mut self, camera: &Camera) -> io::Result<()> {
+        let inverse = camera.transformation.inverse_or_id();
+        let from = inverse * Point::origin();
+        let forward = inverse * vectors::new(0.0, 0.0, -1.0);
+        let up = inverse * vectors::new(0.0, 1.0, 0.0);
+
+        self.entry(0, "add", "camera")?;
+        self.field(0, "width", &camera.width.to_string())?;
+        self.field(0, "height", &camera.height.to_string())?;
+        self.field(0, "field-of-view", &camera.field_of_view.to_string())?;
+        self.field(0, "from", &point(from))?;
+        self.field(0, "to", &point(from + forward))?;
+        self.field(0, "up", &vector(up))?;
+        writeln!(self.out)
+    }
+
+    fn light(&mut self, light: &AreaLight) -> io::Result<()> {
+        let (u_vec, v_vec) = (light.get_full_u_vec(), light.get_full_v_vec());
+        self.entry(0, "add", "light")?;
+
+        // point lights are area lights with a single cell, see `PointLight::to_area_light`
+        if light.u_steps == 1
+            && light.v_steps == 1
+            && light.corner == Point::origin()
+            && u_vec == v_vec
+        {
+            self.field(0, "at", &point(u_vec.to_point()))?;
+        } else {
+            self.field(0, "corner", &point(light.corner))?;
+            self.field(0, "uvec", &vector(u_vec))?;
+            self.field(0, "usteps", &light.u_steps.to_string())?;
+            self.field(0, "vvec", &vector(v_vec))?;
+            self.field(0, "vsteps", &light.v_steps.to_string())?;
+        }
+        self.field(0, "intensity", &color(light.intensity))?;
+        writeln!(self.out)
+    }
+
+    /// The format has no background, so anything but the default black one
+    /// is only noted
+    fn background(&mut self, background: &Background) -> io::Result<()> {
+        match background {
+            Background::Solid(color) if *color == Color::black() => Ok(()),
+            _ => {
+                self.comment(0, "the background was left out")?;
+                writeln!(self.out)
+            }
+        }
+    }
+
+    fn shape(&mut self, shape: &Shape, indent: usize) -> io::Result<()> {
+        let kind = match &shape.geo {
+            Geo::DistanceField(_) | Geo::TestShape => {
+                return self.comment(indent, "a shape that can't be exported was left out");
+            }
+            Geo::Mesh(_) => "group",
+            Geo::Instance(instance) if instance.material.is_none() => "group",
+            geo => geo.kind(),
+        };
+
+        self.entry(indent, "add", kind)?;
+        self.geometry(&shape.geo, indent)?;
+        if *shape.get_transformation() != Matrix44::id() {
+            self.transform(shape.get_transformation(), indent)?;
+        }
+        if shape.material != Material::default() {
+            self.material("material", &shape.material, indent)?;
+        }
+
+        match &shape.geo {
+            Geo::Group(group) => {
+                self.field(indent, "children", "")?;
+                for child in group.children.borrow().iter() {
+                    self.shape(child, indent + 2)?;
+                }
+            }
+            Geo::Mesh(mesh) => {
+                // meshes are written as groups of triangles
+                self.field(indent, "children", "")?;
+                for i in 0..mesh.len() {
+                    self.shape(&Shape::new(Geo::Triangle(mesh.triangle(i))), indent + 2)?;
+                }
+            }
+            Geo::Instance(instance) => match &instance.material {
+                // the shared shape is copied in place
+                None => {
+                    self.field(indent, "children", "")?;
+                    self.shape(&instance.shape, indent + 2)?;
+                }
+                Some(material) => {
+                    self.material("override-material", material, indent)?;
+                    self.field(indent, "shape", "")?;
+                    self.shape(&instance.shape, indent + 2)?;
+                }
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// The parameters of the primitives
+    fn geometry(&mut self, geo: &Geo, indent: usize) -> io::Result<()> {
+        match geo {
+            Geo::Cylinder(cyl) => {
+                if cyl.min.is_finite() {
+                    self.field(indent, "min", &cyl.min.to_string())?;
+                }
+                if cyl.max.is_finite() {
+                    self.field(indent, "max", &cyl.max.to_string())?;
+                }
+                self.field(indent, "closed", &cyl.closed.to_string())?;
+            }
+            Geo::Triangle(tri) => {
+                self.field(indent, "p1", &point(tri.get_p1()))?;
+                self.field(indent, "p2", &point(tri.get_p2()))?;
+                self.field(indent, "p3", &point(tri.get_p3()))?;
+                if let TriangleKind::Smooth(smooth) = &tri.kind {
+                    self.field(indent, "n1", &vector(smooth.n1))?;
+                    self.field(indent, "n2", &vector(smooth.n2))?;
+                    self.field(indent, "n3", &vector(smooth.n3))?;
+                }
+                if let Some(uvs) = tri.get_uvs() {
+                    let uvs = uvs.iter().map(|(u, v)| format!("[{}, {}]", u, v));
+                    self.field(indent, "uvs", &list(uvs))?;
+                }
+            }
+            Geo::Torus(torus) => {
+                self.field(indent, "major-radius", &torus.major_radius.to_string())?;
+                self.field(indent, "minor-radius", &torus.minor_radius.to_string())?;
+            }
+            Geo::Disk(disk) => {
+                self.field(indent, "radius", &disk.radius.to_string())?;
+                if disk.inner_radius > 0.0 {
+                    self.field(indent, "inner-radius", &disk.inner_radius.to_string())?;
+                }
+            }
+            Geo::Rect(rect) => {
+                self.field(indent, "min", &format!("[{}, {}]", rect.min.0, rect.min.1))?;
+                self.field(indent, "max", &format!("[{}, {}]", rect.max.0, rect.max.1))?;
+            }
+            Geo::Blob(blob) => {
+                self.field(indent, "threshold", &blob.threshold.to_string())?;
+                self.field(indent, "metaballs", "")?;
+                for ball in blob.metaballs.iter() {
+                    self.entry(indent + 2, "center", &point(ball.center))?;
+                    self.field(indent + 2, "radius", &ball.radius.to_string())?;
+                    self.field(indent + 2, "strength", &ball.strength.to_string())?;
+                }
+            }
+            Geo::HeightField(field) => {
+                self.field(indent, "columns", &field.columns().to_string())?;
+                self.field(indent, "rows", &field.rows().to_string())?;
+                let heights = field.heights().iter().map(Real::to_string);
+                self.field(indent, "heights", &list(heights))?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn transform(&mut self, matrix: &Matrix44, indent: usize) -> io::Result<()> {
+        self.field(indent, "transform", "")?;
+        for (name, values) in operations(matrix) {
+            let values = values.into_iter().map(number);
+            let operation = list(std::iter::once(name.to_string()).chain(values));
+            writeln!(self.out, "{}    - {}", "  ".repeat(indent), operation)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the fields of the material that differ from the default
+    fn material(&mut self, key: &str, material: &Material, indent: usize) -> io::Result<()> {
+        let default = Material::default();
+        self.field(indent, key, "")?;
+        let inner = indent + 1;
+        if material.color != default.color {
+            self.field(inner, "color", &color(material.color))?;
+        }
+        let values = [
+            ("ambient", material.ambient, default.ambient),
+            ("diffuse", material.diffuse, default.diffuse),
+            ("specular", material.specular, default.specular),
+            ("shininess", material.shininess, default.shininess),
+            ("reflective", material.reflective, default.reflective),
+            ("transparency", material.transparency, default.transparency),
+            (
+                "refractive-index",
+                material.refractive_index,
+                default.refractive_index,
+            ),
+        ];
+        for (name, value, default) in values {
+            if value != default {
+                self.field(inner, name, &value.to_string())?;
+            }
+        }
+        if material.pattern.is_some() {
+            self.comment(inner, "the pattern was left out")?;
+        }
+        Ok(())
+    }
+}
@@ -1,29 +1,67 @@
 use crate::materials::dielectrics;
+use crate::math::Real;
 use crate::matrix::{scaling, CanTransform};
 use crate::rays::lights::{AreaLight, PointLight};
 use crate::rays::{Comps3D, Intersection, Intersection3D, Ray};
+use crate::scene::background::Background;
 use crate::shapes::{Geo, Object, Shape};
 use crate::tuples::colors::Color;
 use crate::tuples::points::Point;
 use crate::tuples::{colors, points};
-use std::borrow::Borrow;
-use std::rc::Rc;
 
+pub mod aovs;
+pub mod background;
 pub mod camera;
+pub mod debug;
+pub mod export;
+pub mod stats;
 
 #[derive(Clone)]
 pub struct World<S> {
     pub objects: Vec<Object<S>>,
     pub light: Option<AreaLight>,
+    pub background: Background,
+
+    /// When enabled, the background also lights the surfaces, as if
+    /// it were a light source surrounding the whole scene.
+    pub image_based_lighting: bool,
 }
 
 pub type World3D = World<Geo>;
 
 const DEFAULT_DEPTH: u8 = 5;
 
+/// The contributions to the color of a hit. They add up to the color
+/// `shade_hit` gives.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Shading {
+    /// The ambient and diffuse light, with the image-based lighting
+    pub diffuse: Color,
+    pub specular: Color,
+
+    /// The reflected and refracted colors, already weighted by the
+    /// material and the Fresnel effect
+    pub reflection: Color,
+    pub refraction: Color,
+
+    /// How much of the light reaches the point, from 0 in full shadow to 1
+    pub visibility: Real,
+}
+
+impl Shading {
+    pub fn total(&self) -> Color {
+        self.diffuse + self.specular + self.reflection + self.refraction
+    }
+}
+
 impl<S> World<S> {
     pub fn new(objects: Vec<Object<S>>, light: Option<AreaLight>) -> World<S> {
-        World { objects, light }
+        World {
+            objects,
+            light,
+            background: Background::black(),
+            image_based_lighting: false,
+        }
     }
 
     pub fn empty() -> World<S> {
@@ -50,13 +88,6 @@ impl<S> World<S> {
         }
     }
 
-    pub fn add_objects_refs(&mut self, rcs: Vec<&Rc<Object<S>>>)
-    where
-        S: Clone,
-    {
-        self.add_objects(rcs.into_iter().map(|rc| rc.borrow()).collect());
-    }
-
     fn contains(&self, shape: &Object<S>) -> bool
     where
         S: PartialEq,
@@ -87,6 +118,19 @@ impl<S> World<S> {
     pub fn get_light(&self) -> Option<&AreaLight> {
         self.light.as_ref()
     }
+
+    /// Restarts the random sampling of the light for the given stream,
+    /// usually the index of the pixel being rendered
+    pub fn restart_sampling(&self, stream: u64) {
+        if let Some(light) = &self.light {
+            light.restart(stream)
+        }
+    }
+
+    pub fn set_background(&mut self, background: Background, image_based_lighting: bool) {
+        self.background = background;
+        self.image_based_lighting = image_based_lighting;
+    }
 }
 
 impl World3D {
@@ -109,6 +153,11 @@ impl World3D {
         world
     }
 
+    /// Bakes the world transformations of all the objects in the scene
+    pub fn finalize(&self) {
+        self.objects.iter().for_each(Shape::finalize);
+    }
+
     fn intersect(&self, ray: &Ray) -> Vec<Intersection<Geo>> {
         let mut intersections: Vec<Intersection3D> = self
             .objects
@@ -130,6 +179,7 @@ impl World3D {
         let direction = v.normalize();
 
         let ray = Ray::new(point, direction);
+        stats::record(|stats| stats.shadow_rays += 1);
         let intersections = self.intersect(&ray);
 
         match Intersection::hit(intersections) {
@@ -143,12 +193,13 @@ impl World3D {
     }
 
     pub fn color_at(&self, ray: &Ray, depth: u8) -> Color {
+        stats::record(|stats| stats.traced_at(DEFAULT_DEPTH.saturating_sub(depth) as u64));
         let xs = self.intersect(ray);
         if let Some(hit) = Intersection::hit(xs.clone()) {
             let comps = Comps3D::prepare(&hit, ray, &xs);
             self.shade_hit(comps, depth)
         } else {
-            Color::black()
+            self.background.at(ray.direction)
         }
     }
 
@@ -157,28 +208,54 @@ impl World3D {
     }
 
     fn shade_hit(&self, comps: Comps3D, depth: u8) -> Color {
-        if let Some(light) = self.get_light() {
-            let surface = comps.get_object().material.lighting(
-                comps.get_object(),
-                light.clone(),
-                comps.get_over_point(),
-                comps.get_eye_vec(),
-                comps.get_normal_vec(),
-                light.intensity_at(comps.get_over_point(), self),
-            );
-            let reflected = self.reflected_color(comps.clone(), depth);
-            let refracted = self.refracted_color(comps.clone(), depth);
-
-            let material = &comps.get_object().material;
-            if material.reflective > 0.0 && material.transparency > 0.0 {
-                let reflectance = dielectrics::schlick(comps);
-                surface + reflected * reflectance + refracted * (1.0 - reflectance)
-            } else {
-                surface + reflected + refracted
-            }
+        self.shade_terms(comps, depth)
+            .map_or_else(Color::black, |shading| shading.total())
+    }
+
+    /// The parts `shade_hit` adds up, or `None` without a light
+    fn shade_terms(&self, comps: Comps3D, depth: u8) -> Option<Shading> {
+        let light = self.get_light()?;
+        // the copy replays the light samples the shadow tests draw
+        let samples = light.clone();
+        let visibility = light.intensity_at(comps.get_over_point(), self);
+        let lighting = comps.get_object().material.lighting_terms(
+            comps.get_object(),
+            samples,
+            comps.get_over_point(),
+            comps.get_eye_vec(),
+            comps.get_normal_vec(),
+            visibility,
+        );
+        let reflected = self.reflected_color(comps.clone(), depth);
+        let refracted = self.refracted_color(comps.clone(), depth);
+
+        let material = &comps.get_object().material;
+        let (reflection, refraction) = if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = dielectrics::schlick(comps.clone());
+            (reflected * reflectance, refracted * (1.0 - reflectance))
         } else {
-            Color::black()
+            (reflected, refracted)
+        };
+
+        Some(Shading {
+            diffuse: lighting.ambient + lighting.diffuse + self.background_lighting(&comps),
+            specular: lighting.specular,
+            reflection,
+            refraction,
+            visibility,
+        })
+    }
+
+    /// A cheap image-based lighting term: the background is looked up in the
+    /// direction of the normal and treated as the irradiance at the point.
+    /// Occlusion is ignored.
+    fn background_lighting(&self, comps: &Comps3D) -> Color {
+        if !self.image_based_lighting {
+            return Color::black();
         }
+        let object = comps.get_object();
+        let color = object.material.color_at(object, comps.get_over_point());
+        color * self.background.at(comps.get_normal_vec()) * object.material.diffuse
     }
 
     pub fn reflected_color_default(&self, comps: Comps3D) -> Color {
@@ -190,6 +267,7 @@ impl World3D {
             Color::black()
         } else {
             let reflect_ray = Ray::new(comps.get_over_point(), comps.get_reflect_vec());
+            stats::record(|stats| stats.reflection_rays += 1);
             let color = self.color_at(&reflect_ray, depth - 1);
             color * comps.get_object().material.reflective
         }
@@ -223,6 +301,7 @@ impl World3D {
                     - comps.get_eye_vec() * n_ratio;
 
                 let refracted_ray = Ray::new(comps.get_under_point(), direction);
+                stats::record(|stats| stats.refraction_rays += 1);
                 self.color_at(&refracted_ray, depth - 1) * comps.get_object().material.transparency
             }
         }
@@ -0,0 +1,195 @@
+//! Counters collected while rendering, to see where the time goes. They
+//! are only kept inside `collect`, so rendering without it costs a check
+//! per counter and nothing else.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::math::Real;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    pub primary_rays: u64,
+    pub shadow_rays: u64,
+    pub reflection_rays: u64,
+    pub refraction_rays: u64,
+
+    /// The number of rays tested against each kind of shape. Meshes and
+    /// heightfields also count the tests against their parts: "bvh node"
+    /// for the boxes of a mesh and "triangle" for the triangles of both.
+    pub intersection_tests: BTreeMap<&'static str, u64>,
+
+    /// Rays that missed the bounding box of a group, so its children
+    /// weren't tested
+    pub bounding_box_rejections: u64,
+
+    /// The sum of the recursion depths of the traced rays, camera rays
+    /// being at depth 0
+    pub total_depth: u64,
+    pub traced_rays: u64,
+    pub max_depth: u64,
+
+    /// The time spent in each phase, in the order they first ran
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+thread_local! {
+    static STATS: RefCell<Option<RenderStats>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` while collecting the statistics of the renders in it, on this thread
+pub fn collect<T, F: FnOnce() -> T>(f: F) -> (T, RenderStats) {
+    let previous = STATS.with(|stats| stats.replace(Some(RenderStats::default())));
+    let result = f();
+    let stats = STATS.with(|stats| stats.replace(previous));
+    (result, stats.unwrap_or_default())
+}
+
+/// Updates the statistics, if they are being collected
+pub fn record<F: FnOnce(&mut RenderStats)>(f: F) {
+    STATS.with(|stats| {
+        if let Some(stats) = stats.borrow_mut().as_mut() {
+            f(stats)
+        }
+    })
+}
+
+/// Runs `f` and adds the time it took to the given phase
+pub fn timed<T, F: FnOnce() -> T>(phase: &'static str, f: F) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    record(
+        |stats| match stats.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => stats.phases.push((phase, elapsed)),
+        },
+    );
+    result
+}
+
+impl RenderStats {
+    pub fn rays(&self) -> u64 {
+        self.primary_rays + self.shadow_rays + self.reflection_rays + self.refraction_rays
+    }
+
+    pub fn average_depth(&self) -> Real {
+        if self.traced_rays == 0 {
+            0.0
+        } else {
+            self.total_depth as Real / self.traced_rays as Real
+        }
+    }
+
+    pub(crate) fn traced_at(&mut self, depth: u64) {
+        self.total_depth += depth;
+        self.traced_rays += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    pub(crate) fn tested(&mut self, kind: &'static str) {
+        self.add_tests(kind, 1);
+    }
+
+    pub(crate) fn add_tests(&mut self, kind: &'static str, count: u64) {
+        *self.intersection_tests.entry(kind).or_insert(0) += count;
+    }
+
+    /// The number of intersection tests with all kinds of shapes
+    pub fn total_tests(&self) -> u64 {
+        self.intersection_tests.values().sum()
+    }
+
+    /// Adds the counters of the other statistics to these
+    pub(crate) fn merge(&mut self, other: &RenderStats) {
+        self.primary_rays += other.primary_rays;
+        self.shadow_rays += other.shadow_rays;
+        self.reflection_rays += other.reflection_rays;
+        self.refraction_rays += other.refraction_rays;
+        for (kind, count) in &other.intersection_tests {
+            *self.intersection_tests.entry(kind).or_insert(0) += count;
+        }
+        self.bounding_box_rejections += other.bounding_box_rejections;
+        self.total_depth += other.total_depth;
+        self.traced_rays += other.traced_rays;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+
+    /// A readable report, one counter per line
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "rays: {}", self.rays());
+        for (name, count) in [
+            ("primary", self.primary_rays),
+            ("shadow", self.shadow_rays),
+            ("reflection", self.reflection_rays),
+            ("refraction", self.refraction_rays),
+        ] {
+            let _ = writeln!(out, "  {}: {}", name, count);
+        }
+        let _ = writeln!(out, "intersection tests: {}", self.total_tests());
+        for (kind, count) in &self.intersection_tests {
+            let _ = writeln!(out, "  {}: {}", kind, count);
+        }
+        let _ = writeln!(
+            out,
+            "bounding box rejections: {}",
+            self.bounding_box_rejections
+        );
+        let _ = writeln!(
+            out,
+            "recursion depth: {:.3} average, {} max",
+            self.average_depth(),
+            self.max_depth
+        );
+        if !self.phases.is_empty() {
+            let _ = writeln!(out, "time:");
+            for (phase, duration) in &self.phases {
+                let _ = writeln!(out, "  {}: {:?}", phase, duration);
+            }
+        }
+        out
+    }
+
+    /// The statistics as a JSON object. Durations are in seconds.
+    pub fn to_json(&self) -> String {
+        let object = |fields: Vec<(&str, String)>| {
+            let fields: Vec<_> = fields
+                .into_iter()
+                .map(|(key, value)| format!("\"{}\": {}", key, value))
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        };
+        let rays = object(vec![
+            ("primary", self.primary_rays.to_string()),
+            ("shadow", self.shadow_rays.to_string()),
+            ("reflection", self.reflection_rays.to_string()),
+            ("refraction", self.refraction_rays.to_string()),
+        ]);
+        let tests = object(
+            self.intersection_tests
+                .iter()
+                .map(|(&kind, count)| (kind, count.to_string()))
+                .collect(),
+        );
+        let phases = object(
+            self.phases
+                .iter()
+                .map(|(phase, duration)| (*phase, duration.as_secs_f64().to_string()))
+                .collect(),
+        );
+        object(vec![
+            ("rays", rays),
+            ("intersection_tests", tests),
+            (
+                "bounding_box_rejections",
+                self.bounding_box_rejections.to_string(),
+            ),
+            ("average_depth", self.average_depth().to_string()),
+            ("max_depth", self.max_depth.to_string()),
+            ("phases", phases),
+        ])
+    }
+}
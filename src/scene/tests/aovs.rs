@@ -0,0 +1,145 @@
+use super::camera;
+use crate::materials::Material;
+use crate::math;
+use crate::matrix::CanTransform;
+use crate::rays::lights::PointLight;
+use crate::rays::Ray;
+use crate::scene::aovs::Aov;
+use crate::scene::camera::Camera;
+use crate::scene::debug::RenderMode;
+use crate::scene::{World, World3D};
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::{colors, points, vectors};
+
+fn aov_camera(aovs: &[Aov]) -> Camera {
+    let mut camera = camera();
+    camera.aovs = aovs.to_vec();
+    camera
+}
+
+#[test]
+fn test_aovs_add_up_to_the_shaded_image() {
+    let world = || {
+        let mut world = World::default();
+        world.add_object(
+            &Shape::plane()
+                .translate(0.0, -1.0, 0.0)
+                .material(Material::glass()),
+        );
+        world.add_object(&Shape::plane().translate(0.0, -2.0, 0.0));
+        world.add_object(
+            &Shape::sphere()
+                .scale_all(0.5)
+                .translate(1.5, 0.0, -1.0)
+                .material(Material::default().reflective(0.5)),
+        );
+        world
+    };
+    let layers = aov_camera(&Aov::ALL).render_layers(world(), true);
+    assert_eq!(layers.beauty, aov_camera(&[]).render(world(), true));
+
+    let parts = [
+        Aov::Diffuse,
+        Aov::Specular,
+        Aov::Reflection,
+        Aov::Refraction,
+    ];
+    for y in 0..11 {
+        for x in 0..11 {
+            let sum = parts.iter().fold(Color::black(), |sum, &aov| {
+                sum + layers.get(aov).unwrap()[(x, y)]
+            });
+            let beauty = layers.beauty[(x, y)];
+            assert!((sum.x - beauty.x).abs() < math::EPSILON);
+            assert!((sum.y - beauty.y).abs() < math::EPSILON);
+            assert!((sum.z - beauty.z).abs() < math::EPSILON);
+        }
+    }
+    // the reflective sphere and the floor under the glass are in the picture
+    let seen = |aov| {
+        let canvas = layers.get(aov).unwrap();
+        (0..11).any(|y| (0..11).any(|x| canvas[(x, y)] != Color::black()))
+    };
+    assert!(seen(Aov::Reflection));
+    assert!(seen(Aov::Refraction));
+}
+
+#[test]
+fn test_surface_aovs() {
+    let layers = aov_camera(&Aov::ALL).render_layers(World::default(), false);
+    let at = |aov| layers.get(aov).unwrap()[(5, 5)];
+    assert_eq!(at(Aov::Depth), colors::new(4.0, 4.0, 4.0));
+    assert_eq!(at(Aov::Normal), colors::new(0.0, 0.0, -1.0));
+    assert_eq!(at(Aov::Albedo), colors::new(0.8, 1.0, 0.6));
+    assert_eq!(at(Aov::Shadow), Color::black());
+    assert_ne!(at(Aov::ObjectId), Color::black());
+
+    // where nothing is hit, all the buffers are black
+    for aov in Aov::ALL {
+        assert_eq!(layers.get(aov).unwrap()[(0, 0)], Color::black());
+    }
+}
+
+/// The debug views render the AOVs from their single ray per pixel
+#[test]
+fn test_aovs_with_a_debug_view() {
+    let mut camera = aov_camera(&[Aov::Depth, Aov::Normal]);
+    camera.mode = RenderMode::Normals;
+    let layers = camera.render_layers(World::default(), true);
+    assert_eq!(layers.beauty[(5, 5)], colors::new(0.5, 0.5, 0.0));
+    assert_eq!(
+        layers.get(Aov::Depth).unwrap()[(5, 5)],
+        colors::new(4.0, 4.0, 4.0)
+    );
+    assert_eq!(
+        layers.get(Aov::Normal).unwrap()[(5, 5)],
+        colors::new(0.0, 0.0, -1.0)
+    );
+}
+
+#[test]
+fn test_shadow_mask() {
+    let mut world = World3D::empty();
+    world.add_object(&Shape::sphere());
+    world.add_object(&Shape::plane().translate(0.0, -2.0, 0.0));
+    world.add_point_light(PointLight::new(points::new(0.0, 10.0, 0.0), Color::white()));
+
+    let direction = vectors::new(0.0, -0.3, 1.0).normalize();
+    let shadow = |origin| {
+        let (_, values) = world.aovs_at(&Ray::new(origin, direction), &[Aov::Shadow]);
+        values[0]
+    };
+    assert_eq!(
+        shadow(points::new(0.0, -0.5, -5.0)),
+        colors::new(1.0, 1.0, 1.0)
+    );
+    assert_eq!(shadow(points::new(3.0, -0.5, -5.0)), Color::black());
+}
+
+#[test]
+fn test_writing_layers() {
+    let layers = aov_camera(&[Aov::Diffuse, Aov::Depth]).render_layers(World::default(), false);
+    assert!(layers.get(Aov::Specular).is_none());
+    assert_eq!(
+        layers.to_exr().channel_names(),
+        vec![
+            "B",
+            "G",
+            "R",
+            "depth.Z",
+            "diffuse.B",
+            "diffuse.G",
+            "diffuse.R"
+        ]
+    );
+
+    let stem = std::env::temp_dir().join("erena-aovs-test");
+    let stem = stem.to_str().unwrap();
+    layers.save_separately(stem).unwrap();
+    for file in ["", ".diffuse", ".depth"] {
+        let path = format!("{}{}.exr", stem, file);
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
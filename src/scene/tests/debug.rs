@@ -0,0 +1,105 @@
+use super::camera;
+use crate::math;
+use crate::matrix::CanTransform;
+use crate::rays::Ray;
+use crate::scene::camera::Camera;
+use crate::scene::debug::RenderMode;
+use crate::scene::{World, World3D};
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::{colors, points, vectors};
+
+fn debug_camera(mode: RenderMode) -> Camera {
+    let mut camera = camera();
+    camera.mode = mode;
+    camera
+}
+
+#[test]
+fn test_rendering_normals_and_depth() {
+    let normals = debug_camera(RenderMode::Normals).render(World::default(), false);
+    assert_eq!(normals[(5, 5)], colors::new(0.5, 0.5, 0.0));
+    assert_eq!(normals[(0, 0)], Color::black());
+
+    let depth = debug_camera(RenderMode::Depth).render(World::default(), false);
+    assert_eq!(depth[(5, 5)], colors::new(4.0, 4.0, 4.0));
+    assert!(depth[(4, 5)].x > 4.0);
+    assert_eq!(depth[(0, 0)], Color::black());
+
+    // with antialiasing, debug views still use a single ray per pixel
+    assert_eq!(
+        debug_camera(RenderMode::Depth).render(World::default(), true),
+        depth
+    );
+}
+
+#[test]
+fn test_rendering_barycentric_coordinates_and_uvs() {
+    let mut world = World3D::empty();
+    world.add_object(&Shape::triangle(
+        points::new(0.0, 3.0, 0.0),
+        points::new(-3.0, -2.0, 0.0),
+        points::new(3.0, -2.0, 0.0),
+    ));
+    let image = debug_camera(RenderMode::Barycentric).render(world, false);
+    let center = image[(5, 5)];
+    assert!(center.x > 0.0 && center.y > 0.0 && center.z > 0.0);
+    assert!((center.x + center.y + center.z - 1.0).abs() < math::EPSILON);
+    assert_ne!(image[(5, 3)], center);
+
+    // shapes other than triangles have no barycentric coordinates
+    let spheres = debug_camera(RenderMode::Barycentric).render(World::default(), false);
+    assert_eq!(spheres[(5, 5)], Color::black());
+
+    let world = World::default();
+    let uvs = debug_camera(RenderMode::Uvs).render(World::default(), false);
+    let sphere = &world.objects[0];
+    let (u, v) = sphere.uv_at(points::new(0.0, 0.0, -1.0));
+    assert_eq!(uvs[(5, 5)], colors::new(u, v, 0.0));
+}
+
+#[test]
+fn test_rendering_object_ids() {
+    let mut world = World3D::empty();
+    world.add_object(&Shape::sphere().translate(-1.5, 0.0, 0.0));
+    world.add_object(&Shape::sphere().translate(1.5, 0.0, 0.0));
+    let image = debug_camera(RenderMode::ObjectIds).render(world, false);
+
+    let (left, right) = (image[(3, 5)], image[(7, 5)]);
+    assert_ne!(left, Color::black());
+    assert_ne!(left, right);
+    assert_eq!(image[(4, 5)], left);
+    assert_eq!(image[(6, 5)], right);
+    assert_eq!(image[(5, 5)], Color::black());
+}
+
+#[test]
+fn test_rendering_a_heatmap() {
+    let mut world = World3D::default();
+    world.add_object(
+        &GroupBuilder::new()
+            .children(vec![Shape::sphere(), Shape::cube()])
+            .translate(-2.0, 0.0, 0.0)
+            .build(),
+    );
+
+    let (image, stats) = debug_camera(RenderMode::Heatmap).render_with_stats(world, false);
+    // the rays through the group's bounding box test its children as well
+    // 5 tests through the box, against 3 elsewhere
+    assert_eq!(image[(2, 5)], colors::new(1.0, 0.0, 0.0));
+    assert_eq!(image[(8, 5)].round_items(), colors::new(0.2, 0.8, 0.0));
+    assert_eq!(stats.primary_rays, 121);
+    assert_eq!(stats.intersection_tests["group"], 121);
+    assert!(stats.intersection_tests.contains_key("cube"));
+}
+
+#[test]
+fn test_the_shaded_mode_is_the_regular_rendering() {
+    let world = World::default();
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(
+        world.debug_color_at(&ray, RenderMode::Shaded),
+        world.default_color_at(&ray)
+    );
+}
@@ -0,0 +1,334 @@
+use crate::materials::Material;
+use crate::math;
+use crate::math::Real;
+use crate::matrix::{
+    rotation_x, rotation_y, rotation_z, scaling, shearing, translation, CanTransform, Matrix44,
+};
+use crate::parsers;
+use crate::parsers::mtl::parse_mtl;
+use crate::rays::lights::PointLight;
+use crate::rays::{Intersection, Ray};
+use crate::scene::background::Background;
+use crate::scene::camera::Camera;
+use crate::scene::export::obj::ObjExport;
+use crate::scene::export::tessellate::Tessellation;
+use crate::scene::export::yaml::{operations, save_scene, write_scene};
+use crate::scene::World3D;
+use crate::shapes::cylinders::CylLike;
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::sdfs::Sdf;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::{colors, points, vectors};
+use std::io;
+use std::rc::Rc;
+
+fn to_obj(export: &ObjExport) -> parsers::Parser {
+    let mut obj = vec![];
+    export.write_obj(&mut obj, None).unwrap();
+    parsers::parse_obj(obj.as_slice())
+}
+
+#[test]
+fn test_exporting_triangles() {
+    let group = GroupBuilder::new()
+        .children(vec![
+            Shape::triangle(
+                points::new(0.0, 1.0, 0.0),
+                points::new(-1.0, 0.0, 0.0),
+                points::new(1.0, 0.0, 0.0),
+            ),
+            Shape::triangle(
+                points::new(0.0, 1.0, 0.0),
+                points::new(1.0, 0.0, 0.0),
+                points::new(0.0, 0.0, 1.0),
+            ),
+        ])
+        .translate(0.0, 2.0, 0.0)
+        .build();
+    let mut parser = to_obj(&ObjExport::new().shape(&group));
+
+    // shared corners are written once
+    assert_eq!(parser.get_vertices().len(), 1 + 4);
+    let triangles = parser.get_default_group();
+    let first = parser.get_triangle_unsafe(parsers::DEFAULT_GROUP, 0);
+    assert_eq!(first.get_p1(), points::new(0.0, 3.0, 0.0));
+    assert_eq!(first.get_p3(), points::new(1.0, 2.0, 0.0));
+
+    let ray = Ray::new(points::new(0.0, 2.5, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(triangles.intersect(&ray)).unwrap();
+    assert_eq!(hit.t, 5.0);
+    assert_eq!(
+        hit.object.normal_at(ray.position(hit.t), &hit),
+        vectors::new(0.0, 0.0, -1.0)
+    );
+}
+
+#[test]
+fn test_exporting_materials() {
+    let mut world = World3D::empty();
+    let red = Material::default()
+        .color(colors::new(1.0, 0.0, 0.0))
+        .specular(0.5)
+        .shininess(50.0);
+    let glass = Material::default().transparency(0.75).refractive_index(1.5);
+    world.add_object(&Shape::cube().material(red.clone()));
+    world.add_object(
+        &Shape::cube()
+            .translate(3.0, 0.0, 0.0)
+            .material(glass.clone()),
+    );
+    world.add_object(&Shape::sphere().material(red.clone()));
+
+    let export = ObjExport::new().world(&world);
+    let mut mtl = vec![];
+    export.write_mtl(&mut mtl).unwrap();
+    let (materials, _) = parse_mtl(mtl.as_slice(), None);
+
+    assert_eq!(materials.len(), 2);
+    assert_eq!(materials["material1"], red);
+    assert_eq!(materials["material2"], glass);
+}
+
+#[test]
+fn test_tessellated_shapes_stay_on_their_surfaces() {
+    let export = ObjExport::new()
+        .tessellation(Tessellation {
+            segments: 16,
+            extent: 10.0,
+        })
+        .shape(&Shape::sphere().transform(scaling(2.0, 2.0, 2.0)));
+    let parser = to_obj(&export);
+
+    let vertices = &parser.get_vertices()[1..];
+    assert!(vertices
+        .iter()
+        .all(|p| math::round_to_5(p.to_vector().magnitude()) == 2.0));
+
+    let mut parser = parser;
+    let sphere = parser.get_default_group();
+    let ray = Ray::new(points::new(0.3, 0.2, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(sphere.intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    let normal = hit.object.normal_at(point, &hit);
+    let exact = 5.0 - (4.0 - 0.3 * 0.3 - 0.2 * 0.2 as Real).sqrt();
+    // the flat facets sink up to 2 * (1 - cos(PI / 16)^2) below the sphere
+    assert!(hit.t >= exact && hit.t - exact < 0.08);
+    assert!(normal.dot(point.to_vector().normalize()) > 0.99);
+}
+
+#[test]
+fn test_exporting_primitives() {
+    let cube = Shape::cube().translate(0.0, 0.0, 2.0);
+    let mut parser = to_obj(&ObjExport::new().shape(&cube));
+    let exported = parser.get_default_group();
+    let ray = Ray::new(points::new(0.3, 0.6, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let ts = |shape: &Shape| {
+        shape
+            .intersect(&ray)
+            .iter()
+            .map(|x| math::round_to_5(x.t))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ts(&exported), ts(&cube));
+
+    // the other primitives make closed or bounded surfaces
+    for shape in [
+        Shape::torus(1.0, 0.25),
+        Shape::cylinder(),
+        Shape::cone(),
+        Shape::annulus(1.0, 0.5),
+        Shape::rect(1.0, 2.0),
+        Shape::plane(),
+    ] {
+        let parser = to_obj(&ObjExport::new().shape(&shape));
+        assert!(!parser.is_empty());
+    }
+}
+
+#[test]
+fn test_instances_and_skipped_shapes() {
+    let shared = Rc::new(Shape::triangle(
+        points::new(0.0, 1.0, 0.0),
+        points::new(-1.0, 0.0, 0.0),
+        points::new(1.0, 0.0, 0.0),
+    ));
+    let override_material = Material::default().color(colors::new(0.0, 0.0, 1.0));
+    let group = Shape::group(vec![
+        Shape::instance(Rc::clone(&shared)).translate(5.0, 0.0, 0.0),
+        Shape::instance_with_material(shared, override_material.clone()),
+        Shape::sdf(Sdf::sphere(1.0)),
+    ]);
+
+    let export = ObjExport::new().shape(&group);
+    assert_eq!(export.skipped(), 1);
+    let parser = to_obj(&export);
+    assert!(parser.get_vertices().contains(&points::new(6.0, 0.0, 0.0)));
+
+    let mut mtl = vec![];
+    export.write_mtl(&mut mtl).unwrap();
+    let (materials, _) = parse_mtl(mtl.as_slice(), None);
+    assert_eq!(materials["material2"], override_material);
+}
+
+#[test]
+fn test_saving_obj_and_mtl() -> io::Result<()> {
+    let path = std::env::temp_dir().join("erena_export_test.obj");
+    let material = Material::default().color(colors::new(0.2, 0.4, 0.6));
+    ObjExport::new()
+        .shape(&Shape::cube().material(material.clone()))
+        .save(&path)?;
+
+    let mut parser = parsers::parse_obj_file(&path)?;
+    assert_eq!(parser.get_material("material1"), Some(&material));
+    let group = parser.get_default_group();
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(group.intersect(&ray)).unwrap();
+    assert_eq!(hit.object.material, material);
+    Ok(())
+}
+
+#[test]
+fn test_writing_the_scene_description() {
+    let mut world = World3D::empty();
+    world.add_point_light(PointLight::new(
+        points::new(-10.0, 10.0, -10.0),
+        Color::white(),
+    ));
+    world.add_object(
+        &Shape::sphere().translate(1.0, 2.0, 3.0).material(
+            Material::default()
+                .color(colors::new(1.0, 0.5, 0.0))
+                .reflective(0.5),
+        ),
+    );
+    world.add_object(&Shape::group(vec![
+        CylLike::cylinder()
+            .min(-1.0)
+            .max(1.0)
+            .closed(true)
+            .to_shape(),
+        Shape::sdf(Sdf::sphere(1.0)),
+    ]));
+    let camera = Camera::new(100, 50, 1.5);
+
+    let mut out = vec![];
+    write_scene(&mut out, &world, Some(&camera)).unwrap();
+    let expected = "\
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 1.5
+  from: [0, 0, 0]
+  to: [0, 0, -1]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- add: sphere
+  transform:
+    - [translate, 1, 2, 3]
+  material:
+    color: [1, 0.5, 0]
+    reflective: 0.5
+- add: group
+  children:
+    - add: cylinder
+      min: -1
+      max: 1
+      closed: true
+    # a shape that can't be exported was left out
+";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn test_saving_the_scene_description() -> io::Result<()> {
+    let mut world = World3D::empty();
+    world.set_background(Background::gradient(Color::white(), Color::black()), false);
+    world.add_object(&Shape::sphere());
+
+    let path = std::env::temp_dir().join("erena_export_test.yaml");
+    save_scene(&path, &world, None)?;
+    let saved = std::fs::read_to_string(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(saved, "# the background was left out\n\n- add: sphere\n");
+    Ok(())
+}
+
+#[test]
+fn test_transformations_as_operations() {
+    let transform = translation(1.0, 2.0, 3.0) * rotation_y(0.5) * scaling(2.0, 1.0, 0.5);
+    let names: Vec<_> = operations(&transform)
+        .into_iter()
+        .map(|(name, values)| (name, values.iter().map(|v| math::round_to_5(*v)).collect()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("scale", vec![2.0, 1.0, 0.5]),
+            ("rotate-y", vec![0.5]),
+            ("translate", vec![1.0, 2.0, 3.0]),
+        ]
+    );
+    assert!(operations(&Matrix44::id()).is_empty());
+
+    // any transformation, sheared or mirrored, is rebuilt from the operations
+    let transforms = [
+        transform,
+        rotation_z(2.0) * rotation_x(-1.2) * shearing(0.5, 0.0, 1.0, 0.0, 0.2, 0.3),
+        scaling(-1.0, 2.0, 1.0) * rotation_y(math::PI / 2.0) * rotation_x(0.3),
+        translation(-4.0, 0.0, 1.0) * rotation_y(math::PI / 2.0),
+    ];
+    for transform in transforms {
+        let rebuilt =
+            operations(&transform)
+                .into_iter()
+                .fold(Matrix44::id(), |matrix, (name, v)| {
+                    let operation = match name {
+                        "scale" => scaling(v[0], v[1], v[2]),
+                        "shear" => shearing(v[0], v[1], v[2], v[3], v[4], v[5]),
+                        "rotate-x" => rotation_x(v[0]),
+                        "rotate-y" => rotation_y(v[0]),
+                        "rotate-z" => rotation_z(v[0]),
+                        "translate" => translation(v[0], v[1], v[2]),
+                        _ => panic!("Unknown operation {}", name),
+                    };
+                    operation * matrix
+                });
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((rebuilt[(row, col)] - transform[(row, col)]).abs() < math::EPSILON);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_writing_instances() {
+    let shared = Rc::new(Shape::sphere());
+    let mut world = World3D::empty();
+    world.add_object(&Shape::instance(Rc::clone(&shared)).translate(0.0, 1.0, 0.0));
+    world.add_object(&Shape::instance_with_material(
+        shared,
+        Material::default().reflective(0.5),
+    ));
+
+    let mut out = vec![];
+    write_scene(&mut out, &world, None).unwrap();
+    let expected = "\
+- add: group
+  transform:
+    - [translate, 0, 1, 0]
+  children:
+    - add: sphere
+- add: instance
+  override-material:
+    reflective: 0.5
+  shape:
+    - add: sphere
+";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
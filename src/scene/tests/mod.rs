@@ -1,11 +1,17 @@
+mod aovs;
+mod debug;
+mod export;
 mod shadows;
+mod stats;
 
 use crate::materials::Material;
 use crate::math;
-use crate::matrix::{scaling, CanTransform};
+use crate::matrix::{scaling, view_transformation, CanTransform};
 use crate::patterns::Pattern;
 use crate::rays::lights::PointLight;
 use crate::rays::{Comps, Comps3D, Intersection, Ray};
+use crate::scene::background::Background;
+use crate::scene::camera::Camera;
 use crate::scene::{World, World3D};
 use crate::shapes::Shape;
 use crate::tuples::colors::Color;
@@ -13,6 +19,18 @@ use crate::tuples::points::Point;
 use crate::tuples::{colors, points, vectors};
 use std::rc::Rc;
 
+/// An 11 x 11 camera with a wide view, looking at the origin from z = -5,
+/// for the tests of whole renders
+fn camera() -> Camera {
+    let mut camera = Camera::new(11, 11, math::PI / 2.0);
+    camera.transformation = view_transformation(
+        points::new(0.0, 0.0, -5.0),
+        Point::origin(),
+        vectors::new(0.0, 1.0, 0.0),
+    );
+    camera
+}
+
 #[test]
 fn test_creating_world() {
     let world = World3D::empty();
@@ -81,6 +99,47 @@ fn test_missed_ray_color() {
     assert_eq!(color, Color::black());
 }
 
+#[test]
+fn test_missed_ray_sees_background() {
+    let mut world = World::default();
+    world.set_background(Background::gradient(Color::white(), Color::black()), false);
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 1.0, 0.0));
+    let color = world.default_color_at(&ray);
+    assert_eq!(color, Color::white());
+}
+
+/// Reflected rays that escape the scene pick up the background
+#[test]
+fn test_reflection_of_background() {
+    let mut world = World3D::empty();
+    world.set_background(Background::Solid(colors::new(0.2, 0.4, 0.6)), false);
+    let shape = Rc::new(Shape::plane().material(Material::default().reflective(0.5)));
+    world.add_object(&shape);
+    let ray = Ray::new(
+        points::new(0.0, 1.0, -1.0),
+        vectors::new(0.0, -math::two_sqrt_div_2(), math::two_sqrt_div_2()),
+    );
+    let i = Intersection::from_ref(2_f64.sqrt(), &shape);
+    let comps = Comps::prepare_default(&i, &ray);
+    let color = world.reflected_color_default(comps);
+    assert_eq!(color, colors::new(0.1, 0.2, 0.3));
+}
+
+#[test]
+fn test_image_based_lighting() {
+    let mut world = World::default();
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let without_ibl = world.default_color_at(&ray);
+
+    world.set_background(Background::Solid(colors::new(0.1, 0.1, 0.1)), true);
+    let with_ibl = world.default_color_at(&ray);
+
+    // the outer sphere has a diffuse value of 0.7
+    let material = &world.objects[0].material;
+    let expected = without_ibl + material.color * 0.1 * material.diffuse;
+    assert_eq!(with_ibl, expected);
+}
+
 /// Tests the color with an intersection behind the ray
 #[test]
 fn test_behind_ray_color() {
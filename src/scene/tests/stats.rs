@@ -0,0 +1,123 @@
+use super::camera;
+use crate::materials::Material;
+use crate::matrix::CanTransform;
+use crate::parsers;
+use crate::rays::Ray;
+use crate::scene::stats;
+use crate::scene::{World, World3D};
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::heightfields::HeightField;
+use crate::shapes::Shape;
+use crate::tuples::{points, vectors};
+
+#[test]
+fn test_counting_rays_and_intersection_tests() {
+    let (image, stats) = camera().render_with_stats(World::default(), false);
+    assert_eq!(image, camera().render(World::default(), false));
+
+    assert_eq!(stats.primary_rays, 121);
+    assert!(stats.shadow_rays > 0 && stats.shadow_rays < 121);
+    assert_eq!(stats.reflection_rays, 0);
+    assert_eq!(stats.refraction_rays, 0);
+
+    // each ray is tested against both spheres
+    assert_eq!(stats.intersection_tests.len(), 1);
+    assert_eq!(
+        stats.intersection_tests["sphere"],
+        2 * (stats.primary_rays + stats.shadow_rays)
+    );
+    assert_eq!(stats.traced_rays, 121);
+    assert_eq!(stats.average_depth(), 0.0);
+    assert!(stats.phases.iter().any(|(phase, _)| *phase == "render"));
+}
+
+#[test]
+fn test_counting_secondary_rays_and_rejections() {
+    let mut world = World3D::default();
+    world.add_object(
+        &Shape::plane()
+            .translate(0.0, -1.0, 0.0)
+            .material(Material::default().reflective(0.5)),
+    );
+    world.add_object(
+        &Shape::sphere()
+            .translate(0.0, 0.0, -2.0)
+            .material(Material::glass()),
+    );
+    world.add_object(
+        &GroupBuilder::new()
+            .child(Shape::cube())
+            .translate(0.0, 50.0, 0.0)
+            .build(),
+    );
+
+    let (_, stats) = camera().render_with_stats(world, false);
+    assert!(stats.reflection_rays > 0);
+    assert!(stats.refraction_rays > 0);
+    assert!(stats.max_depth >= 2);
+    assert!(stats.average_depth() > 0.0);
+
+    // the group is out of sight, so its cube is never tested
+    assert_eq!(
+        stats.bounding_box_rejections,
+        stats.intersection_tests["group"]
+    );
+    assert!(!stats.intersection_tests.contains_key("cube"));
+}
+
+#[test]
+fn test_counting_the_parts_of_meshes_and_heightfields() {
+    let mesh = parsers::parse_obj_file("resources/triangles.obj")
+        .unwrap()
+        .to_indexed_mesh()
+        .to_shape();
+    let ray = Ray::new(points::new(0.5, 0.2, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let (xs, stats) = stats::collect(|| mesh.intersect(&ray));
+    assert_eq!(xs.len(), 1);
+    assert_eq!(stats.intersection_tests["mesh"], 1);
+    assert!(stats.intersection_tests["bvh node"] >= 1);
+    assert_eq!(stats.intersection_tests["triangle"], 2);
+
+    // each cell the ray crosses has two triangles
+    let field = HeightField::from_fn(5, 5, |x, _| x).to_shape();
+    let ray = Ray::new(points::new(-1.0, 1.0, 0.5), vectors::new(1.0, -0.1, 0.0));
+    let (xs, stats) = stats::collect(|| field.intersect(&ray));
+    assert_eq!(xs.len(), 1);
+    assert_eq!(stats.intersection_tests["heightfield"], 1);
+    let triangles = stats.intersection_tests["triangle"];
+    assert!(triangles > 2 && triangles % 2 == 0);
+}
+
+#[test]
+fn test_statistics_are_only_collected_when_asked() {
+    camera().render(World::default(), false);
+    let (_, stats) = stats::collect(|| ());
+    assert_eq!(stats, Default::default());
+
+    let (parser, stats) = stats::collect(|| {
+        stats::timed("build", || ());
+        parsers::parse_obj_file("resources/triangles.obj").unwrap()
+    });
+    assert!(!parser.is_empty());
+    let phases: Vec<_> = stats.phases.iter().map(|(phase, _)| *phase).collect();
+    assert_eq!(phases, vec!["build", "parse"]);
+}
+
+#[test]
+fn test_statistics_as_json() {
+    let (_, mut stats) = camera().render_with_stats(World::default(), false);
+    stats.phases.clear();
+    let json = stats.to_json();
+    assert_eq!(
+        json,
+        format!(
+            "{{\"rays\": {{\"primary\": 121, \"shadow\": {}, \"reflection\": 0, \"refraction\": 0}}, \
+             \"intersection_tests\": {{\"sphere\": {}}}, \"bounding_box_rejections\": 0, \
+             \"average_depth\": 0, \"max_depth\": 0, \"phases\": {{}}}}",
+            stats.shadow_rays, stats.intersection_tests["sphere"]
+        )
+    );
+    assert!(stats
+        .summary()
+        .starts_with(&format!("rays: {}\n", stats.rays())));
+}
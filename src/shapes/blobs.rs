@@ -0,0 +1,179 @@
+use crate::math;
+use crate::math::polynomials;
+use crate::math::Real;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::shapes::bounds::Bounds;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors;
+use crate::tuples::vectors::Vector;
+use std::rc::Rc;
+
+/// A sphere of influence. Its contribution to the field is `strength` at
+/// the center, and falls off smoothly to zero at `radius`. A negative
+/// strength carves the other metaballs instead.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Metaball {
+    pub center: Point,
+    pub radius: Real,
+    pub strength: Real,
+}
+
+/// An iso-surface made of metaballs. The surface is where the sum of
+/// their fields equals the threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blob {
+    pub metaballs: Rc<Vec<Metaball>>,
+    pub threshold: Real,
+}
+
+impl Metaball {
+    pub fn new(center: Point, radius: Real, strength: Real) -> Metaball {
+        Metaball {
+            center,
+            radius,
+            strength,
+        }
+    }
+
+    /// The field of the metaball is `strength * (1 - d^2/r^2)^2` within the
+    /// radius. Along a ray, `d^2/r^2 = at^2 + bt + c`, so the field is a quartic.
+    fn field_along(&self, ray: &Ray) -> [Real; 5] {
+        let sq_radius = self.radius * self.radius;
+        let to_ray = ray.origin - self.center;
+        let a = ray.direction.dot(ray.direction) / sq_radius;
+        let b = 2.0 * ray.direction.dot(to_ray) / sq_radius;
+        let c = to_ray.dot(to_ray) / sq_radius;
+
+        [
+            1.0 - 2.0 * c + c * c,
+            2.0 * b * c - 2.0 * b,
+            b * b + 2.0 * a * c - 2.0 * a,
+            2.0 * a * b,
+            a * a,
+        ]
+        .map(|coefficient| coefficient * self.strength)
+    }
+
+    /// Where the ray enters and leaves the sphere of influence
+    fn span(&self, ray: &Ray) -> Option<(Real, Real)> {
+        let to_ray = ray.origin - self.center;
+        let a = ray.direction.dot(ray.direction);
+        let b = 2.0 * ray.direction.dot(to_ray);
+        let c = to_ray.dot(to_ray) - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+
+        if discriminant <= 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            Some(((-b - root) / (2.0 * a), (-b + root) / (2.0 * a)))
+        }
+    }
+
+    fn gradient(&self, point: Point) -> Vector {
+        let sq_radius = self.radius * self.radius;
+        let to_point = (point - self.center).to_vector();
+        let falloff = 1.0 - to_point.dot(to_point) / sq_radius;
+        if falloff <= 0.0 {
+            vectors::new(0.0, 0.0, 0.0)
+        } else {
+            to_point * (-4.0 * self.strength * falloff / sq_radius)
+        }
+    }
+}
+
+impl Blob {
+    pub fn new(threshold: Real) -> Blob {
+        Blob {
+            metaballs: Rc::new(vec![]),
+            threshold,
+        }
+    }
+
+    pub fn metaball(mut self, center: Point, radius: Real, strength: Real) -> Blob {
+        Rc::make_mut(&mut self.metaballs).push(Metaball::new(center, radius, strength));
+        self
+    }
+
+    pub fn to_geo(&self) -> Geo {
+        Geo::Blob(self.clone())
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    /// The surface can only be within the metaballs that add to the field
+    pub fn bounds(&self) -> Bounds {
+        self.metaballs
+            .iter()
+            .filter(|ball| ball.strength > 0.0)
+            .fold(Bounds::empty(), |bounds, ball| {
+                let offset = vectors::new(ball.radius, ball.radius, ball.radius);
+                bounds + (ball.center - offset) + (ball.center + offset)
+            })
+    }
+
+    /// Splits the ray at the points where it enters or leaves a sphere of
+    /// influence. Within each segment the same metaballs are active, so the
+    /// field is a single quartic whose roots are the intersections.
+    pub fn intersect(&self, blob: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        let spans: Vec<_> = self
+            .metaballs
+            .iter()
+            .filter_map(|ball| ball.span(ray).map(|span| (ball, span)))
+            .collect();
+
+        let mut boundaries: Vec<Real> = spans
+            .iter()
+            .flat_map(|(_, (t_min, t_max))| [*t_min, *t_max])
+            .collect();
+        boundaries.sort_by(|a, b| math::order_reals(*a, *b));
+
+        let blob = Rc::new(blob.clone());
+        let mut xs = vec![];
+        for segment in boundaries.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let middle = (start + end) / 2.0;
+
+            let mut field = [-self.threshold, 0.0, 0.0, 0.0, 0.0];
+            for (ball, _) in spans
+                .iter()
+                .filter(|(_, (t_min, t_max))| *t_min <= middle && middle <= *t_max)
+            {
+                for (total, term) in field.iter_mut().zip(ball.field_along(ray)) {
+                    *total += term;
+                }
+            }
+
+            // the leading coefficient shrinks with the scale of the blob, so
+            // it can't be compared to a fixed epsilon. It is only zero when
+            // opposite metaballs cancel out exactly.
+            let ts = if field[4] == 0.0 {
+                polynomials::solve(&field)
+            } else {
+                polynomials::solve_quartic(field)
+            };
+            xs.extend(
+                ts.into_iter()
+                    .filter(|t| start <= *t && *t < end)
+                    .map(|t| Intersection::new(t, Rc::clone(&blob))),
+            );
+        }
+
+        xs.sort_by(|a, b| math::order_reals(a.t, b.t));
+        xs
+    }
+
+    /// The field grows towards the centers of the metaballs, so the normal
+    /// points against its gradient
+    pub fn normal_at(&self, point: Point) -> Vector {
+        -self
+            .metaballs
+            .iter()
+            .fold(vectors::new(0.0, 0.0, 0.0), |gradient, ball| {
+                gradient + ball.gradient(point)
+            })
+    }
+}
@@ -1,9 +1,11 @@
+use crate::math;
 use crate::math::Real;
+use crate::rays::Ray;
 use crate::shapes::{Geo, Shape};
 use crate::tuples::points;
 use crate::tuples::points::Point;
 
-use crate::matrix::Matrix;
+use crate::matrix::Matrix44;
 use std::ops::Add;
 
 /// Represents a bounding box
@@ -37,10 +39,36 @@ impl Bounds {
             Geo::Cylinder(cyl) => cyl.bounds(),
             Geo::Group(group) => group.bounds(),
             Geo::Triangle(tri) => tri.bounds(),
+            Geo::Instance(instance) => instance.bounds(),
+            Geo::Torus(torus) => torus.bounds(),
+            Geo::Disk(disk) => disk.bounds(),
+            Geo::Rect(rect) => rect.bounds(),
+            Geo::DistanceField(field) => field.bounds(),
+            Geo::Blob(blob) => blob.bounds(),
+            Geo::HeightField(field) => field.bounds(),
+            Geo::Mesh(mesh) => mesh.bounds(),
             Geo::TestShape => Bounds::from_min(points::new(-1.0, -1.0, -1.0)),
         }
     }
 
+    /// Where the ray enters and leaves the box, if it crosses it
+    pub fn intersect(&self, ray: &Ray) -> Option<(Real, Real)> {
+        let Ray { origin, direction } = ray;
+        let Bounds { min, max } = self;
+        let (x_t_min, x_t_max) = check_axis(origin.x, direction.x, min.x, max.x);
+        let (y_t_min, y_t_max) = check_axis(origin.y, direction.y, min.y, max.y);
+        let (z_t_min, z_t_max) = check_axis(origin.z, direction.z, min.z, max.z);
+
+        let t_min = Real::max(x_t_min, Real::max(y_t_min, z_t_min));
+        let t_max = Real::min(x_t_max, Real::min(y_t_max, z_t_max));
+
+        if t_min > t_max {
+            None
+        } else {
+            Some((t_min, t_max))
+        }
+    }
+
     pub fn contains_point(&self, point: Point) -> bool {
         let between = |p, min, max| p >= min && p <= max;
         let Bounds { min, max } = self;
@@ -53,7 +81,7 @@ impl Bounds {
         self.contains_point(other.min) && self.contains_point(other.max)
     }
 
-    pub fn transform(&self, matrix: &Matrix) -> Bounds {
+    pub fn transform(&self, matrix: &Matrix44) -> Bounds {
         let Bounds { min, max } = self;
 
         // transform corners. Note that we are using the left-hand rule
@@ -70,11 +98,28 @@ impl Bounds {
         ];
 
         corners.iter().fold(Bounds::empty(), |bounds, (x, y, z)| {
-            bounds + (matrix.clone() * points::new(*x, *y, *z))
+            bounds + (matrix * points::new(*x, *y, *z))
         })
     }
 }
 
+fn check_axis(origin: Real, direction: Real, min: Real, max: Real) -> (Real, Real) {
+    let t_min_num = min - origin;
+    let t_max_num = max - origin;
+
+    let (t_min, t_max) = if direction.abs() >= math::EPSILON {
+        (t_min_num / direction, t_max_num / direction)
+    } else {
+        (t_min_num * Real::INFINITY, t_max_num * Real::INFINITY)
+    };
+
+    if t_min > t_max {
+        (t_max, t_min)
+    } else {
+        (t_min, t_max)
+    }
+}
+
 impl Add<Point> for Bounds {
     type Output = Bounds;
 
@@ -1,7 +1,5 @@
-use crate::math;
 use crate::math::Real;
 use crate::rays::{Intersection, Intersection3D, Ray};
-use crate::shapes::bounds::Bounds;
 use crate::shapes::Shape;
 use crate::tuples::points::Point;
 use crate::tuples::vectors;
@@ -9,22 +7,12 @@ use crate::tuples::vectors::Vector;
 use std::rc::Rc;
 
 pub fn intersect(cube: &Shape, ray: &Ray) -> Vec<Intersection3D> {
-    let Ray { origin, direction } = ray;
-    let Bounds { min, max } = cube.bounds();
-    let (x_t_min, x_t_max) = check_axis(origin.x, direction.x, min.x, max.x);
-    let (y_t_min, y_t_max) = check_axis(origin.y, direction.y, min.y, max.y);
-    let (z_t_min, z_t_max) = check_axis(origin.z, direction.z, min.z, max.z);
-
-    let t_min = Real::max(x_t_min, Real::max(y_t_min, z_t_min));
-    let t_max = Real::min(x_t_max, Real::min(y_t_max, z_t_max));
-
-    if t_min > t_max {
-        vec![]
-    } else {
-        vec![
+    match cube.bounds().intersect(ray) {
+        None => vec![],
+        Some((t_min, t_max)) => vec![
             Intersection::new(t_min, Rc::new(cube.clone())),
             Intersection::new(t_max, Rc::new(cube.clone())),
-        ]
+        ],
     }
 }
 
@@ -39,23 +27,6 @@ pub fn normal_at(point: Point) -> Vector {
     }
 }
 
-fn check_axis(origin: Real, direction: Real, min: Real, max: Real) -> (Real, Real) {
-    let t_min_num = min - origin;
-    let t_max_num = max - origin;
-
-    let (t_min, t_max) = if direction.abs() >= math::EPSILON {
-        (t_min_num / direction, t_max_num / direction)
-    } else {
-        (t_min_num * Real::INFINITY, t_max_num * Real::INFINITY)
-    };
-
-    if t_min > t_max {
-        (t_max, t_min)
-    } else {
-        (t_min, t_max)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::rays::Ray;
@@ -1,9 +1,11 @@
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
+use crate::matrix::{CanTransform, Matrix44, SingularMatrix};
 use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::scene::stats;
 use crate::shapes::bounds::Bounds;
-use crate::shapes::{cubes, Shape};
+use crate::shapes::{cubes, Geo, Shape};
 
 use crate::tuples::vectors::Vector;
 
@@ -21,10 +23,6 @@ impl Group {
         }
     }
 
-    pub fn empty() -> Group {
-        Group::new(vec![])
-    }
-
     pub fn contains(&self, shape: Rc<Shape>) -> bool {
         self.children.borrow().contains(&shape)
     }
@@ -37,15 +35,22 @@ impl Group {
         !self.is_empty()
     }
 
+    /// Adds a child, given a link to the shape that owns this group.
+    /// Prefer `Shape::add_child` or `GroupBuilder`, which do the linking.
     pub fn add_child(&self, parent: Weak<Shape>, child: Rc<Shape>) {
         self.children.borrow_mut().push(Rc::clone(&child));
+        match parent.upgrade() {
+            Some(parent) => {
+                child.bake(parent.world_inverse.get());
+                parent.invalidate_bounds();
+            }
+            None => self.invalidate_bounds(),
+        }
         child.set_parent(parent);
     }
 
-    pub fn add_children(&self, parent: Weak<Shape>, children: Vec<Rc<Shape>>) {
-        children
-            .into_iter()
-            .for_each(|child| self.add_child(Weak::clone(&parent), child));
+    fn invalidate_bounds(&self) {
+        self.bounds.replace(None);
     }
 
     pub(crate) fn bounds(&self) -> Bounds {
@@ -69,6 +74,7 @@ impl Group {
         // If the ray does not intersect with the bounding box,
         // do not bother checking the children
         if cubes::intersect(shape, ray).is_empty() {
+            stats::record(|stats| stats.bounding_box_rejections += 1);
             return vec![];
         }
 
@@ -87,6 +93,123 @@ impl Group {
     }
 }
 
+impl Shape {
+    pub fn as_group(&self) -> Option<&Group> {
+        match &self.geo {
+            Geo::Group(group) => Some(group),
+            _ => None,
+        }
+    }
+
+    /// Adds a child to this group and links the child to it. If the child
+    /// already belongs to another group, it is moved out of that group.
+    /// Panics if this shape isn't a group, or if the child is this group
+    /// or one of its ancestors.
+    pub fn add_child(self: &Rc<Self>, child: Rc<Shape>) {
+        let group = match self.as_group() {
+            Some(group) => group,
+            None => return not_a_group(),
+        };
+        // a group inside itself would never stop baking its transformations
+        let mut ancestor = Some(Rc::clone(self));
+        while let Some(shape) = ancestor {
+            if Rc::ptr_eq(&shape, &child) {
+                panic!("A group can't contain itself");
+            }
+            ancestor = shape.get_parent();
+        }
+        if let Some(old_parent) = child.get_parent() {
+            old_parent.remove_child(&child);
+        }
+        group.add_child(Rc::downgrade(self), child);
+    }
+
+    /// Removes a child from this group. The removed child becomes a root,
+    /// with no parent. Returns false if the child wasn't found.
+    pub fn remove_child(self: &Rc<Self>, child: &Rc<Shape>) -> bool {
+        let group = match self.as_group() {
+            Some(group) => group,
+            None => return false,
+        };
+
+        let mut children = group.children.borrow_mut();
+        let count = children.len();
+        children.retain(|c| !Rc::ptr_eq(c, child));
+        if children.len() == count {
+            return false;
+        }
+        drop(children);
+
+        child.set_parent(Weak::new());
+        child.finalize();
+        self.invalidate_bounds();
+        true
+    }
+
+    /// Moves the child into a new group
+    pub fn reparent(child: &Rc<Shape>, new_parent: &Rc<Shape>) {
+        new_parent.add_child(Rc::clone(child));
+    }
+
+    /// Clears the cached bounds of this group and of all its ancestors,
+    /// since they all depend on this group's children.
+    pub(crate) fn invalidate_bounds(&self) {
+        if let Some(group) = self.as_group() {
+            group.invalidate_bounds();
+        }
+        if let Some(parent) = self.get_parent() {
+            parent.invalidate_bounds();
+        }
+    }
+}
+
+/// Builds a group from its children, linking each child to the group.
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    transformation: Matrix44,
+    children: Vec<Rc<Shape>>,
+}
+
+impl GroupBuilder {
+    pub fn new() -> GroupBuilder {
+        GroupBuilder {
+            transformation: Matrix44::id(),
+            children: vec![],
+        }
+    }
+
+    pub fn child<S: Into<Rc<Shape>>>(mut self, child: S) -> GroupBuilder {
+        self.children.push(child.into());
+        self
+    }
+
+    pub fn children<S: Into<Rc<Shape>>>(mut self, children: Vec<S>) -> GroupBuilder {
+        self.children
+            .extend(children.into_iter().map(|child| child.into()));
+        self
+    }
+
+    pub fn build(self) -> Rc<Shape> {
+        let group = Rc::new(Shape::empty_group().set_transformation(self.transformation));
+        for child in self.children {
+            group.add_child(child);
+        }
+        group
+    }
+}
+
+impl CanTransform for GroupBuilder {
+    fn get_transformation(&self) -> &Matrix44 {
+        &self.transformation
+    }
+
+    fn try_set_transformation(mut self, transformation: Matrix44) -> Result<Self, SingularMatrix> {
+        transformation.try_inverse()?;
+        self.transformation = transformation;
+        Ok(self)
+    }
+}
+
 /// Probably never gonna be needed.
 pub fn normal_at() -> Vector {
     panic!("Groups have no normal vector")
@@ -0,0 +1,312 @@
+use crate::canvas::Canvas;
+use crate::math;
+use crate::math::Real;
+use crate::parsers;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::scene::stats;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::triangles::{moller_trumbore, Triangle};
+use crate::shapes::uvs::Uv;
+use crate::shapes::{cubes, Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use crate::tuples::{points, vectors};
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+/// A terrain over the unit square of the xz plane, sampled on a regular
+/// grid. Each grid cell is made of two triangles, so the surface is the
+/// same as the usual mesh, without storing the triangles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightField {
+    /// Number of samples along the x axis
+    columns: usize,
+
+    /// Number of samples along the z axis
+    rows: usize,
+
+    heights: Rc<Vec<Real>>,
+    normals: Rc<Vec<Vector>>,
+    bounds: Bounds,
+}
+
+impl HeightField {
+    /// Creates a heightfield from the given samples, stored row by row.
+    ///
+    /// Panics with fewer than 2 x 2 samples, which don't make a surface,
+    /// or if there isn't one height per sample.
+    pub fn new(columns: usize, rows: usize, heights: Vec<Real>) -> HeightField {
+        assert!(
+            columns >= 2 && rows >= 2,
+            "A heightfield needs at least 2 x 2 samples"
+        );
+        assert!(
+            heights.len() == columns * rows,
+            "A heightfield needs one height per sample"
+        );
+
+        let (min, max) = heights
+            .iter()
+            .fold((Real::INFINITY, -Real::INFINITY), |(min, max), &h| {
+                (min.min(h), max.max(h))
+            });
+        let mut field = HeightField {
+            columns,
+            rows,
+            heights: Rc::new(heights),
+            normals: Rc::new(vec![]),
+            bounds: Bounds::new(points::new(0.0, min, 0.0), points::new(1.0, max, 1.0)),
+        };
+        field.normals = Rc::new(field.compute_normals());
+        field
+    }
+
+    /// Samples the function at each grid point. The function receives the
+    /// x and z coordinates, both within 0..1. Panics with fewer than 2 x 2
+    /// samples, like `new`.
+    pub fn from_fn<F>(columns: usize, rows: usize, f: F) -> HeightField
+    where
+        F: Fn(Real, Real) -> Real,
+    {
+        let heights = (0..rows)
+            .flat_map(|row| (0..columns).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                f(
+                    col as Real / (columns - 1) as Real,
+                    row as Real / (rows - 1) as Real,
+                )
+            })
+            .collect();
+        HeightField::new(columns, rows, heights)
+    }
+
+    /// Uses the brightness of each pixel as the height, from 0 to 1.
+    /// The top row of the image is at z = 0. Fails for images smaller
+    /// than 2 x 2 pixels.
+    pub fn from_canvas(image: &Canvas) -> io::Result<HeightField> {
+        if image.width < 2 || image.height < 2 {
+            return Err(parsers::invalid_data(
+                "A heightmap needs at least 2 x 2 pixels",
+            ));
+        }
+        let heights = (0..image.height)
+            .flat_map(|y| (0..image.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let color = image[(x, y)];
+                0.2126 * color.red_value()
+                    + 0.7152 * color.green_value()
+                    + 0.0722 * color.blue_value()
+            })
+            .collect();
+        Ok(HeightField::new(image.width, image.height, heights))
+    }
+
+    /// Loads a grayscale heightmap with any of the supported image formats
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<HeightField> {
+        HeightField::from_canvas(&Canvas::load(path)?)
+    }
+
+    pub fn to_geo(&self) -> Geo {
+        Geo::HeightField(self.clone())
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The samples, stored row by row
+    pub fn heights(&self) -> &[Real] {
+        &self.heights
+    }
+
+    /// The two triangles of each grid cell, with the normals of the samples
+    pub fn to_triangles(&self) -> Vec<Triangle> {
+        let corner = |col: usize, row: usize| {
+            (
+                self.vertex(col, row),
+                self.normals[row * self.columns + col],
+            )
+        };
+        (0..self.rows - 1)
+            .flat_map(|row| (0..self.columns - 1).map(move |col| (col, row)))
+            .flat_map(|(col, row)| {
+                let (p00, p10) = (corner(col, row), corner(col + 1, row));
+                let (p01, p11) = (corner(col, row + 1), corner(col + 1, row + 1));
+                [(p00, p10, p11), (p00, p11, p01)]
+                    .map(|(a, b, c)| Triangle::smooth(a.0, b.0, c.0, a.1, b.1, c.1))
+            })
+            .collect()
+    }
+
+    fn height(&self, col: usize, row: usize) -> Real {
+        self.heights[row * self.columns + col]
+    }
+
+    fn vertex(&self, col: usize, row: usize) -> Point {
+        points::new(
+            col as Real / (self.columns - 1) as Real,
+            self.height(col, row),
+            row as Real / (self.rows - 1) as Real,
+        )
+    }
+
+    /// The normal at each sample, from the slopes to its neighbors
+    fn compute_normals(&self) -> Vec<Vector> {
+        let slope = |low: usize, high: usize, count: usize, height: &dyn Fn(usize) -> Real| {
+            let run = (high - low) as Real / (count - 1) as Real;
+            (height(high) - height(low)) / run
+        };
+
+        (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let dx = slope(
+                    col.saturating_sub(1),
+                    (col + 1).min(self.columns - 1),
+                    self.columns,
+                    &|c| self.height(c, row),
+                );
+                let dz = slope(
+                    row.saturating_sub(1),
+                    (row + 1).min(self.rows - 1),
+                    self.rows,
+                    &|r| self.height(col, r),
+                );
+                vectors::new(-dx, 1.0, -dz).normalize()
+            })
+            .collect()
+    }
+
+    /// The grid cell containing the point, and the point's position within it
+    fn cell_at(&self, x: Real, z: Real) -> ((usize, usize), (Real, Real)) {
+        let locate = |value: Real, count: usize| {
+            let scaled = value * (count - 1) as Real;
+            let cell = (scaled.floor().max(0.0) as usize).min(count - 2);
+            (cell, scaled - cell as Real)
+        };
+        let (col, fx) = locate(x, self.columns);
+        let (row, fz) = locate(z, self.rows);
+        ((col, row), (fx, fz))
+    }
+
+    /// Walks the grid cells under the ray (a 2D DDA over the xz plane),
+    /// from where it enters the bounding box to where it leaves it, and
+    /// collects the hits in each cell
+    pub fn intersect(&self, field: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        let (t_min, t_max) = match cubes::intersect(field, ray).as_slice() {
+            [first, last] => (first.t, last.t),
+            _ => return vec![],
+        };
+
+        let start = ray.position(t_min);
+        let ((mut col, mut row), _) = self.cell_at(start.x, start.z);
+
+        // the parameters of the next cell crossings along each axis,
+        // and the distance (in t) between crossings
+        let axis = |origin: Real, direction: Real, cell: usize, count: usize| {
+            let cells = (count - 1) as Real;
+            let direction = direction * cells;
+            if direction.abs() < math::EPSILON {
+                return (0, Real::INFINITY, Real::INFINITY);
+            }
+            let step: isize = if direction > 0.0 { 1 } else { -1 };
+            let boundary = (cell as isize + (step > 0) as isize) as Real;
+            let next = t_min + (boundary - origin * cells) / direction;
+            (step, next, 1.0 / direction.abs())
+        };
+        let (step_x, mut next_x, delta_x) = axis(start.x, ray.direction.x, col, self.columns);
+        let (step_z, mut next_z, delta_z) = axis(start.z, ray.direction.z, row, self.rows);
+
+        let mut ts: Vec<Real> = vec![];
+        loop {
+            for t in self.intersect_cell(ray, col, row) {
+                // a ray through an edge hits the triangles on both sides
+                if ts
+                    .last()
+                    .is_none_or(|last| (t - last).abs() >= math::EPSILON)
+                {
+                    ts.push(t);
+                }
+            }
+
+            let advance = |cell: &mut usize, step: isize, count: usize| {
+                let next = *cell as isize + step;
+                if next < 0 || next > count as isize - 2 {
+                    return false;
+                }
+                *cell = next as usize;
+                true
+            };
+            let (inside, entered) = if next_x < next_z {
+                let entered = next_x;
+                next_x += delta_x;
+                (advance(&mut col, step_x, self.columns), entered)
+            } else {
+                let entered = next_z;
+                next_z += delta_z;
+                (advance(&mut row, step_z, self.rows), entered)
+            };
+            if !inside || entered > t_max {
+                break;
+            }
+        }
+
+        let field = Rc::new(field.clone());
+        ts.into_iter()
+            .map(|t| Intersection::new(t, Rc::clone(&field)))
+            .collect()
+    }
+
+    /// The hits with the two triangles of the cell, in order
+    fn intersect_cell(&self, ray: &Ray, col: usize, row: usize) -> Vec<Real> {
+        let p00 = self.vertex(col, row);
+        let p10 = self.vertex(col + 1, row);
+        let p01 = self.vertex(col, row + 1);
+        let p11 = self.vertex(col + 1, row + 1);
+
+        stats::record(|stats| stats.add_tests("triangle", 2));
+        let mut ts: Vec<Real> = [(p00, p10, p11), (p00, p11, p01)]
+            .iter()
+            .filter_map(|&(p1, p2, p3)| {
+                moller_trumbore(ray, p1, (p2 - p1).to_vector(), (p3 - p1).to_vector())
+            })
+            .map(|(t, _, _)| t)
+            .collect();
+        ts.sort_by(|a, b| math::order_reals(*a, *b));
+        ts
+    }
+
+    /// Interpolates the normals of the corners of the triangle containing the point
+    pub fn normal_at(&self, point: Point) -> Vector {
+        let ((col, row), (fx, fz)) = self.cell_at(point.x, point.z);
+        let normal = |c: usize, r: usize| self.normals[r * self.columns + c];
+
+        if fx >= fz {
+            normal(col, row) * (1.0 - fx)
+                + normal(col + 1, row) * (fx - fz)
+                + normal(col + 1, row + 1) * fz
+        } else {
+            normal(col, row) * (1.0 - fz)
+                + normal(col, row + 1) * (fz - fx)
+                + normal(col + 1, row + 1) * fx
+        }
+    }
+
+    /// Stretches the texture over the whole terrain
+    pub fn uv_at(&self, point: Point) -> Uv {
+        (point.x, point.z)
+    }
+}
@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use crate::materials::Material;
+use crate::matrix::Matrix44;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::scene::stats;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::{cubes, Geo, Shape};
+
+/// Places a shared shape (usually a heavy group, like a mesh) in the scene
+/// without copying it. Each instance has its own transformation, and can
+/// optionally replace the materials of the shared shape.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instance {
+    pub shape: Rc<Shape>,
+    pub material: Option<Material>,
+}
+
+impl Instance {
+    pub fn new(shape: Rc<Shape>, material: Option<Material>) -> Instance {
+        Instance { shape, material }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.shape.parent_space_bounds()
+    }
+
+    pub fn intersect(&self, instance: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        // like groups, skip the shared shape if the bounding box is missed
+        if cubes::intersect(instance, ray).is_empty() {
+            return vec![];
+        }
+        intersect_placed(
+            &self.shape,
+            ray,
+            instance.world_inverse.get(),
+            self.material.as_ref(),
+        )
+    }
+}
+
+/// Intersects a shape inside a shared one, given the ray in the space of
+/// its parent and the inverse of that space's world transformation. The
+/// shared shape can be used elsewhere, so the transformations baked into
+/// it are ignored and those of the hit objects are computed along the way.
+fn intersect_placed(
+    shape: &Shape,
+    ray: &Ray,
+    parent_inverse: Matrix44,
+    material: Option<&Material>,
+) -> Vec<Intersection3D> {
+    let world_inverse = shape.inverse * parent_inverse;
+    let local_ray = || {
+        stats::record(|stats| stats.tested(shape.geo.kind()));
+        ray.transform(shape.inverse)
+    };
+
+    match &shape.geo {
+        Geo::Group(group) => {
+            let ray = local_ray();
+            if cubes::intersect(shape, &ray).is_empty() {
+                stats::record(|stats| stats.bounding_box_rejections += 1);
+                return vec![];
+            }
+            let mut xs: Vec<_> = group
+                .children
+                .borrow()
+                .iter()
+                .flat_map(|child| intersect_placed(child, &ray, world_inverse, material))
+                .collect();
+            xs.sort_by(Intersection::compare);
+            xs
+        }
+        Geo::Instance(instance) => {
+            let ray = local_ray();
+            if cubes::intersect(shape, &ray).is_empty() {
+                return vec![];
+            }
+            // the outer instance's material wins over the nested one's
+            let material = material.or(instance.material.as_ref());
+            intersect_placed(&instance.shape, &ray, world_inverse, material)
+        }
+        _ => shape
+            .intersect(ray)
+            .into_iter()
+            .map(|hit| {
+                let kind = hit.get_kind();
+                let mut object = Rc::try_unwrap(hit.object).unwrap_or_else(|rc| (*rc).clone());
+                object.bake_world_inverse(parent_inverse);
+                if let Some(material) = material {
+                    object.material = material.clone();
+                }
+                Intersection::new_with_kind(hit.t, Rc::new(object), kind)
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,377 @@
+use crate::math;
+use crate::math::Real;
+use crate::rays::{Intersection, Intersection3D, IntersectionKind, Ray};
+use crate::scene::stats;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::triangles::{barycentric, moller_trumbore, Triangle};
+use crate::shapes::uvs::Uv;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// The most triangles kept in a leaf of the BVH
+const LEAF_SIZE: usize = 4;
+
+/// The corners of a triangle in a mesh, as indices into the mesh's
+/// shared buffers. Normals and texture coordinates are optional, and
+/// have their own indices, like in OBJ files.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MeshTriangle {
+    pub vertices: [u32; 3],
+    pub normals: Option<[u32; 3]>,
+    pub uvs: Option<[u32; 3]>,
+}
+
+/// A triangle mesh whose vertices, normals and texture coordinates are
+/// shared by its triangles. This takes far less memory than a group of
+/// `Triangle` shapes, which each have their own copies of their points,
+/// transformation and material. Triangles are found with a BVH.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    data: Rc<MeshData>,
+}
+
+#[derive(Debug, PartialEq)]
+struct MeshData {
+    vertices: Vec<Point>,
+    normals: Vec<Vector>,
+    uvs: Vec<Uv>,
+    triangles: Vec<MeshTriangle>,
+
+    /// The nodes of the BVH, with the root first
+    nodes: Vec<BvhNode>,
+
+    /// The triangles, ordered so that each leaf refers to a range
+    order: Vec<u32>,
+}
+
+/// A node of the flattened BVH. A leaf holds the triangles
+/// `order[first..first + count]`. An inner node (with a count of zero)
+/// has its left child right after it, and its right child at `first`.
+#[derive(Debug, PartialEq)]
+struct BvhNode {
+    bounds: Bounds,
+    first: u32,
+    count: u32,
+}
+
+fn to_index(index: usize) -> u32 {
+    u32::try_from(index).expect("Too many elements in the mesh")
+}
+
+fn to_indices(indices: [usize; 3]) -> [u32; 3] {
+    indices.map(to_index)
+}
+
+impl MeshTriangle {
+    pub fn new(vertices: [usize; 3]) -> MeshTriangle {
+        MeshTriangle {
+            vertices: to_indices(vertices),
+            normals: None,
+            uvs: None,
+        }
+    }
+
+    /// Gives each corner its own normal, making the triangle smooth
+    pub fn normals(mut self, normals: [usize; 3]) -> MeshTriangle {
+        self.normals = Some(to_indices(normals));
+        self
+    }
+
+    pub fn uvs(mut self, uvs: [usize; 3]) -> MeshTriangle {
+        self.uvs = Some(to_indices(uvs));
+        self
+    }
+}
+
+impl Mesh {
+    /// Builds a mesh and its BVH. The indices of the triangles must be
+    /// within the given buffers.
+    pub fn new(
+        vertices: Vec<Point>,
+        normals: Vec<Vector>,
+        uvs: Vec<Uv>,
+        triangles: Vec<MeshTriangle>,
+    ) -> Mesh {
+        let in_range = |indices: Option<[u32; 3]>, len: usize| {
+            indices.is_none_or(|indices| indices.iter().all(|&i| (i as usize) < len))
+        };
+        assert!(
+            triangles.iter().all(|tri| {
+                in_range(Some(tri.vertices), vertices.len())
+                    && in_range(tri.normals, normals.len())
+                    && in_range(tri.uvs, uvs.len())
+            }),
+            "A mesh triangle refers to a missing vertex, normal or uv"
+        );
+
+        let mut data = MeshData {
+            order: (0..to_index(triangles.len())).collect(),
+            vertices,
+            normals,
+            uvs,
+            triangles,
+            nodes: vec![],
+        };
+        if !data.triangles.is_empty() {
+            let mut order = std::mem::take(&mut data.order);
+            data.build(&mut order, 0);
+            data.order = order;
+        }
+        Mesh {
+            data: Rc::new(data),
+        }
+    }
+
+    pub fn to_geo(&self) -> Geo {
+        Geo::Mesh(self.clone())
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.triangles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.triangles.is_empty()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.data
+            .nodes
+            .first()
+            .map_or(Bounds::empty(), |root| root.bounds)
+    }
+
+    /// Copies the triangle at the given index into a standalone `Triangle`
+    pub fn triangle(&self, index: usize) -> Triangle {
+        let data = &self.data;
+        let tri = &data.triangles[index];
+        let [p1, p2, p3] = data.points(tri);
+        let triangle = match tri.normals {
+            None => Triangle::regular(p1, p2, p3),
+            Some([n1, n2, n3]) => {
+                let normal = |i: u32| data.normals[i as usize];
+                Triangle::smooth(p1, p2, p3, normal(n1), normal(n2), normal(n3))
+            }
+        };
+        match tri.uvs {
+            None => triangle,
+            Some(uvs) => triangle.with_uvs(uvs.map(|i| data.uvs[i as usize])),
+        }
+    }
+
+    /// Finds the hits with every triangle the ray crosses, walking only
+    /// the BVH nodes whose boxes the ray goes through
+    pub fn intersect(&self, mesh: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        let data = &self.data;
+        if data.nodes.is_empty() {
+            return vec![];
+        }
+
+        let mesh = Rc::new(mesh.clone());
+        let mut xs = vec![];
+        let mut stack = vec![0];
+        // counted here and recorded once, to keep the loop cheap
+        let (mut node_tests, mut triangle_tests) = (0, 0);
+        while let Some(i) = stack.pop() {
+            let node = &data.nodes[i];
+            node_tests += 1;
+            if node.bounds.intersect(ray).is_none() {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first as usize);
+                stack.push(i + 1);
+                continue;
+            }
+
+            triangle_tests += node.count as u64;
+            for &index in data.leaf(node) {
+                let index = index as usize;
+                let [p1, p2, p3] = data.points(&data.triangles[index]);
+                let edges = ((p2 - p1).to_vector(), (p3 - p1).to_vector());
+                if let Some((t, u, v)) = moller_trumbore(ray, p1, edges.0, edges.1) {
+                    let kind = IntersectionKind::Triangle { u, v, index };
+                    xs.push(Intersection::new_with_kind(t, Rc::clone(&mesh), kind));
+                }
+            }
+        }
+
+        stats::record(|stats| {
+            stats.add_tests("bvh node", node_tests);
+            stats.add_tests("triangle", triangle_tests);
+        });
+
+        xs.sort_by(|a, b| math::order_reals(a.t, b.t));
+        xs
+    }
+
+    /// Interpolates the normals of the hit triangle, or uses the normal
+    /// of its plane if it has none
+    pub fn normal_at(&self, point: Point, hit: &Intersection3D) -> Vector {
+        let (index, u, v) = match hit.get_kind() {
+            IntersectionKind::Triangle { u, v, index } if index < self.len() => (index, u, v),
+            _ => match self.data.locate(point) {
+                Some(found) => found,
+                None => return self.data.face_normal(0),
+            },
+        };
+
+        match self.data.triangles[index].normals {
+            None => self.data.face_normal(index),
+            Some(normals) => {
+                let [n1, n2, n3] = normals.map(|i| self.data.normals[i as usize]);
+                n2 * u + n3 * v + n1 * (1.0 - u - v)
+            }
+        }
+    }
+
+    /// Interpolates the texture coordinates of the triangle containing
+    /// the point. Like `Triangle`, triangles without texture coordinates
+    /// use the barycentric coordinates instead.
+    pub fn uv_at(&self, point: Point) -> Uv {
+        let (index, u, v) = match self.data.locate(point) {
+            Some(found) => found,
+            None => return (0.0, 0.0),
+        };
+
+        match self.data.triangles[index].uvs {
+            None => (u, v),
+            Some(uvs) => {
+                let [t1, t2, t3] = uvs.map(|i| self.data.uvs[i as usize]);
+                let w = 1.0 - u - v;
+                (
+                    t1.0 * w + t2.0 * u + t3.0 * v,
+                    t1.1 * w + t2.1 * u + t3.1 * v,
+                )
+            }
+        }
+    }
+}
+
+impl MeshData {
+    fn points(&self, tri: &MeshTriangle) -> [Point; 3] {
+        tri.vertices.map(|i| self.vertices[i as usize])
+    }
+
+    fn leaf(&self, node: &BvhNode) -> &[u32] {
+        &self.order[node.first as usize..(node.first + node.count) as usize]
+    }
+
+    /// The normal of the triangle's plane, following the winding of
+    /// `Triangle::regular`
+    fn face_normal(&self, index: usize) -> Vector {
+        let [p1, p2, p3] = self.points(&self.triangles[index]);
+        (p3 - p1)
+            .to_vector()
+            .cross((p2 - p1).to_vector())
+            .normalize()
+    }
+
+    fn centroid(&self, index: u32) -> Point {
+        let [p1, p2, p3] = self.points(&self.triangles[index as usize]);
+        let sum = p1.to_vector() + p2.to_vector() + p3.to_vector();
+        (sum / 3.0).to_point()
+    }
+
+    /// Builds the nodes for the triangles of `order`, which start at
+    /// `offset` in the full order. Large nodes are split in half at the
+    /// median of the triangles' centroids, along their longest axis.
+    fn build(&mut self, order: &mut [u32], offset: usize) {
+        let bounds = order.iter().fold(Bounds::empty(), |bounds, &index| {
+            let [p1, p2, p3] = self.points(&self.triangles[index as usize]);
+            bounds + p1 + p2 + p3
+        });
+
+        let node = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds,
+            first: to_index(offset),
+            count: to_index(order.len()),
+        });
+        if order.len() <= LEAF_SIZE {
+            return;
+        }
+
+        let centers = order.iter().fold(Bounds::empty(), |bounds, &index| {
+            bounds + self.centroid(index)
+        });
+        let extent = centers.max - centers.min;
+        let axis = |point: Point| {
+            if extent.x >= extent.y && extent.x >= extent.z {
+                point.x
+            } else if extent.y >= extent.z {
+                point.y
+            } else {
+                point.z
+            }
+        };
+
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |&a, &b| {
+            math::order_reals(axis(self.centroid(a)), axis(self.centroid(b)))
+        });
+
+        let (left, right) = order.split_at_mut(middle);
+        self.build(left, offset);
+        let right_node = self.nodes.len();
+        self.build(right, offset + middle);
+        self.nodes[node].first = to_index(right_node);
+        self.nodes[node].count = 0;
+    }
+
+    /// Finds the triangle the point lies on, with the barycentric
+    /// coordinates of the point. This is needed where only the point is
+    /// known, such as when mapping textures.
+    fn locate(&self, point: Point) -> Option<(usize, Real, Real)> {
+        let near = |bounds: &Bounds| {
+            let between = |p: Real, min: Real, max: Real| {
+                p >= min - math::EPSILON && p <= max + math::EPSILON
+            };
+            between(point.x, bounds.min.x, bounds.max.x)
+                && between(point.y, bounds.min.y, bounds.max.y)
+                && between(point.z, bounds.min.z, bounds.max.z)
+        };
+
+        let mut best: Option<(Real, (usize, Real, Real))> = None;
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !near(&node.bounds) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first as usize);
+                stack.push(i + 1);
+                continue;
+            }
+
+            for &index in self.leaf(node) {
+                let index = index as usize;
+                let [p1, p2, p3] = self.points(&self.triangles[index]);
+                let (edge1, edge2) = ((p2 - p1).to_vector(), (p3 - p1).to_vector());
+                let (u, v) = barycentric(p1, edge1, edge2, point);
+                let inside =
+                    u >= -math::EPSILON && v >= -math::EPSILON && u + v <= 1.0 + math::EPSILON;
+                let distance = (point - p1).to_vector().dot(self.face_normal(index)).abs();
+                if inside
+                    && distance.is_finite()
+                    && best.is_none_or(|(closest, _)| distance < closest)
+                {
+                    best = Some((distance, (index, u, v)));
+                }
+            }
+        }
+        best.map(|(_, found)| found)
+    }
+}
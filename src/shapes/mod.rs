@@ -1,18 +1,42 @@
 use crate::materials::Material;
-use crate::matrix::{CanTransform, Matrix};
+use crate::math::Real;
+use crate::matrix::{CanTransform, Matrix44, SingularMatrix};
 use crate::rays::{Intersection3D, Ray};
+use crate::scene::stats;
+use crate::shapes::blobs::Blob;
 use crate::shapes::bounds::Bounds;
 use crate::shapes::cylinders::CylLike;
-use crate::shapes::groups::Group;
+use crate::shapes::groups::{Group, GroupBuilder};
+use crate::shapes::heightfields::HeightField;
+use crate::shapes::instances::Instance;
+use crate::shapes::meshes::Mesh;
+use crate::shapes::planes::{Disk, Rect};
+use crate::shapes::sdfs::{DistanceField, Sdf};
+use crate::shapes::tori::Torus;
 use crate::shapes::triangles::Triangle;
+use crate::shapes::uvs::{Uv, UvMapping};
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 
 #[derive(Debug, Clone)]
 pub struct Object<G> {
-    pub transformation: Matrix,
+    /// Only changed through `CanTransform`, which keeps the inverses in sync
+    transformation: Matrix44,
+
+    /// The inverse of the transformation, and its transpose. They are
+    /// updated together with the transformation.
+    inverse: Matrix44,
+    inverse_transpose: Matrix44,
+
+    /// The inverse of the full world transformation (this object's and all
+    /// of its ancestors'), and its transpose. They are baked once when the
+    /// object is added to a group or when the scene is finalized, so shading
+    /// doesn't need to walk up the parents.
+    world_inverse: Cell<Matrix44>,
+    world_inverse_transpose: Cell<Matrix44>,
+
     pub material: Material,
     pub geo: G,
     pub parent: RefCell<Weak<Object<G>>>,
@@ -27,6 +51,14 @@ pub enum Geo {
     Cylinder(CylLike),
     Group(Group),
     Triangle(Triangle),
+    Instance(Instance),
+    Torus(Torus),
+    Disk(Disk),
+    Rect(Rect),
+    DistanceField(DistanceField),
+    Blob(Blob),
+    HeightField(HeightField),
+    Mesh(Mesh),
 }
 
 pub type Shape = Object<Geo>;
@@ -37,21 +69,21 @@ impl<G> Object<G> {
     }
 
     pub fn world_to_object(&self, world_point: Point) -> Point {
-        let point = match self.get_parent() {
-            None => world_point,
-            Some(parent) => parent.world_to_object(world_point),
-        };
-        self.transformation.inverse_or_id44() * point
+        self.world_inverse.get() * world_point
     }
 
     pub fn normal_to_world(&self, normal: Vector) -> Vector {
-        let normal = (self.transformation.inverse_or_id44().transpose() * normal)
+        (self.world_inverse_transpose.get() * normal)
             .to_vector()
-            .normalize();
-        match self.get_parent() {
-            None => normal,
-            Some(parent) => parent.normal_to_world(normal),
-        }
+            .normalize()
+    }
+
+    /// Bakes the world transformation, given the inverse of the parent's
+    /// world transformation
+    fn bake_world_inverse(&self, parent_world_inverse: Matrix44) {
+        let world_inverse = self.inverse * parent_world_inverse;
+        self.world_inverse.set(world_inverse);
+        self.world_inverse_transpose.set(world_inverse.transpose());
     }
 
     pub fn get_parent(&self) -> Option<Rc<Object<G>>> {
@@ -59,10 +91,21 @@ impl<G> Object<G> {
     }
 }
 
+impl<G: UvMapping> Object<G> {
+    /// Finds the texture coordinates of a point on the surface
+    pub fn uv_at(&self, world_point: Point) -> Uv {
+        self.geo.uv_at(self.world_to_object(world_point))
+    }
+}
+
 impl Shape {
     pub fn new(geo: Geo) -> Shape {
         Object {
-            transformation: Matrix::id44(),
+            transformation: Matrix44::id(),
+            inverse: Matrix44::id(),
+            inverse_transpose: Matrix44::id(),
+            world_inverse: Cell::new(Matrix44::id()),
+            world_inverse_transpose: Cell::new(Matrix44::id()),
             material: Material::default(),
             geo,
             parent: RefCell::new(Weak::new()),
@@ -93,9 +136,37 @@ impl Shape {
         CylLike::cone().to_shape()
     }
 
-    pub fn group(objects: Vec<Shape>) -> Shape {
-        let objects: Vec<_> = objects.into_iter().map(|obj| Rc::new(obj)).collect();
-        Shape::from_group(Group::new(objects))
+    pub fn torus(major_radius: Real, minor_radius: Real) -> Shape {
+        Torus::new(major_radius, minor_radius).to_shape()
+    }
+
+    pub fn disk(radius: Real) -> Shape {
+        Disk::new(radius).to_shape()
+    }
+
+    pub fn annulus(radius: Real, inner_radius: Real) -> Shape {
+        Disk::new(radius).inner_radius(inner_radius).to_shape()
+    }
+
+    /// An axis-aligned rectangle on the xz plane, centered at the origin
+    pub fn rect(width: Real, depth: Real) -> Shape {
+        Rect::centered(width, depth).to_shape()
+    }
+
+    /// A shape defined by a signed distance function, using the default
+    /// tracing settings. See `DistanceField` to configure them.
+    pub fn sdf(sdf: Sdf) -> Shape {
+        DistanceField::new(sdf).to_shape()
+    }
+
+    pub fn mesh(mesh: Mesh) -> Shape {
+        mesh.to_shape()
+    }
+
+    /// Groups the shapes and links them to the group, like `GroupBuilder`,
+    /// which also sets the transformation of the group
+    pub fn group(objects: Vec<Shape>) -> Rc<Shape> {
+        GroupBuilder::new().children(objects).build()
     }
 
     pub fn triangle(p1: Point, p2: Point, p3: Point) -> Shape {
@@ -113,16 +184,48 @@ impl Shape {
         Shape::new(Geo::Triangle(Triangle::smooth(p1, p2, p3, n1, n2, n3)))
     }
 
+    /// Shares the given shape instead of copying it. The instance has
+    /// its own transformation, and keeps the shape's materials.
+    pub fn instance(shape: Rc<Shape>) -> Shape {
+        Shape::new(Geo::Instance(Instance::new(shape, None)))
+    }
+
+    /// Like `instance`, but all the instanced shapes use the given material
+    pub fn instance_with_material(shape: Rc<Shape>, material: Material) -> Shape {
+        Shape::new(Geo::Instance(Instance::new(shape, Some(material))))
+    }
+
     pub fn empty_group() -> Shape {
-        Shape::group(vec![])
+        Shape::from_group(Group::new(vec![]))
     }
 
     pub fn from_group(group: Group) -> Shape {
-        Shape::new(Geo::Group(group))
+        let shape = Shape::new(Geo::Group(group));
+        shape.finalize();
+        shape
+    }
+
+    /// Bakes the world transformations of this object and all its
+    /// descendants, treating this object as a root of the scene.
+    /// This needs to be called again if the object is transformed after
+    /// its children were added.
+    pub fn finalize(&self) {
+        self.bake(Matrix44::id());
+    }
+
+    pub(crate) fn bake(&self, parent_world_inverse: Matrix44) {
+        self.bake_world_inverse(parent_world_inverse);
+        if let Geo::Group(group) = &self.geo {
+            let world_inverse = self.world_inverse.get();
+            for child in group.children.borrow().iter() {
+                child.bake(world_inverse);
+            }
+        }
     }
 
     pub fn intersect(&self, ray: &Ray) -> Vec<Intersection3D> {
-        let local_ray = ray.transform(self.transformation.inverse_or_id44());
+        stats::record(|stats| stats.tested(self.geo.kind()));
+        let local_ray = ray.transform(self.inverse);
 
         match self.geo {
             Geo::Sphere => spheres::intersect(self, &local_ray),
@@ -132,6 +235,14 @@ impl Shape {
             Geo::Cylinder(CylLike { cone, .. }) => cylinders::intersect(self, &local_ray, cone),
             Geo::Group(ref group) => group.intersect(self, &local_ray),
             Geo::Triangle(ref tri) => tri.intersect(self, &local_ray),
+            Geo::Instance(ref instance) => instance.intersect(self, &local_ray),
+            Geo::Torus(ref torus) => torus.intersect(self, &local_ray),
+            Geo::Disk(ref disk) => disk.intersect(self, &local_ray),
+            Geo::Rect(ref rect) => rect.intersect(self, &local_ray),
+            Geo::DistanceField(ref field) => field.intersect(self, &local_ray),
+            Geo::Blob(ref blob) => blob.intersect(self, &local_ray),
+            Geo::HeightField(ref field) => field.intersect(self, &local_ray),
+            Geo::Mesh(ref mesh) => mesh.intersect(self, &local_ray),
         }
     }
 
@@ -141,13 +252,18 @@ impl Shape {
         let local_normal = match self.geo {
             Geo::Sphere => spheres::normal_at(local_point),
             Geo::TestShape => test::normal_at(local_point),
-            Geo::Plane => planes::normal_at(),
+            Geo::Plane | Geo::Disk(_) | Geo::Rect(_) => planes::normal_at(),
             Geo::Cube => cubes::normal_at(local_point),
             Geo::Cylinder(CylLike { min, max, cone, .. }) => {
                 cylinders::normal_at(local_point, min, max, cone)
             }
-            Geo::Group(_) => groups::normal_at(),
+            Geo::Group(_) | Geo::Instance(_) => groups::normal_at(),
             Geo::Triangle(ref tri) => tri.get_normal(hit),
+            Geo::Torus(ref torus) => torus.normal_at(local_point),
+            Geo::DistanceField(ref field) => field.normal_at(local_point),
+            Geo::Blob(ref blob) => blob.normal_at(local_point),
+            Geo::HeightField(ref field) => field.normal_at(local_point),
+            Geo::Mesh(ref mesh) => mesh.normal_at(local_point, hit),
         };
 
         self.normal_to_world(local_normal)
@@ -195,16 +311,44 @@ impl Geo {
         }
         return false;
     }
+
+    /// The name of the kind of shape, as shown in the render statistics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Geo::Sphere => "sphere",
+            Geo::TestShape => "test",
+            Geo::Plane => "plane",
+            Geo::Cube => "cube",
+            Geo::Cylinder(cyl) if cyl.is_cone() => "cone",
+            Geo::Cylinder(_) => "cylinder",
+            Geo::Group(_) => "group",
+            Geo::Triangle(_) => "triangle",
+            Geo::Instance(_) => "instance",
+            Geo::Torus(_) => "torus",
+            Geo::Disk(_) => "disk",
+            Geo::Rect(_) => "rect",
+            Geo::DistanceField(_) => "distance field",
+            Geo::Blob(_) => "blob",
+            Geo::HeightField(_) => "heightfield",
+            Geo::Mesh(_) => "mesh",
+        }
+    }
 }
 
 impl<S> CanTransform for Object<S> {
-    fn get_transformation(&self) -> &Matrix {
+    fn get_transformation(&self) -> &Matrix44 {
         &self.transformation
     }
 
-    fn set_transformation(mut self, transformation: Matrix) -> Self {
+    fn try_set_transformation(mut self, transformation: Matrix44) -> Result<Self, SingularMatrix> {
+        self.inverse = transformation.try_inverse()?;
+        self.inverse_transpose = self.inverse.transpose();
         self.transformation = transformation;
-        self
+
+        // until it's added to a group, the object is its own root
+        self.world_inverse.set(self.inverse);
+        self.world_inverse_transpose.set(self.inverse_transpose);
+        Ok(self)
     }
 }
 
@@ -231,10 +375,19 @@ mod test {
 #[cfg(test)]
 mod tests;
 
+pub mod blobs;
 mod bounds;
 mod cubes;
 pub mod cylinders;
 pub mod groups;
-mod planes;
+pub mod heightfields;
+pub mod instances;
+pub mod meshes;
+pub mod patches;
+pub mod planes;
+pub mod sdfs;
 pub mod spheres;
+pub mod subdivision;
+pub mod tori;
 pub mod triangles;
+pub mod uvs;
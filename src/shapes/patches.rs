@@ -0,0 +1,119 @@
+use crate::math::Real;
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::Shape;
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use std::rc::Rc;
+
+/// A bicubic Bezier patch, defined by a 4 x 4 grid of control points
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BezierPatch {
+    pub control_points: [[Point; 4]; 4],
+}
+
+/// The cubic Bernstein polynomials, and their derivatives
+fn bernstein(t: Real) -> ([Real; 4], [Real; 4]) {
+    let s = 1.0 - t;
+    (
+        [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t],
+        [
+            -3.0 * s * s,
+            3.0 * s * s - 6.0 * t * s,
+            6.0 * t * s - 3.0 * t * t,
+            3.0 * t * t,
+        ],
+    )
+}
+
+impl BezierPatch {
+    pub fn new(control_points: [[Point; 4]; 4]) -> BezierPatch {
+        BezierPatch { control_points }
+    }
+
+    /// Evaluates the surface point, and its partial derivatives in `u` and `v`.
+    /// `u` runs along the rows of the control points, and `v` along the columns.
+    fn evaluate(&self, u: Real, v: Real) -> (Point, Vector, Vector) {
+        let (bu, du) = bernstein(u);
+        let (bv, dv) = bernstein(v);
+
+        let mut point = Vector::zero();
+        let mut d_u = Vector::zero();
+        let mut d_v = Vector::zero();
+        for (i, row) in self.control_points.iter().enumerate() {
+            for (j, control) in row.iter().enumerate() {
+                let control = control.to_vector();
+                point = point + control * (bu[i] * bv[j]);
+                d_u = d_u + control * (du[i] * bv[j]);
+                d_v = d_v + control * (bu[i] * dv[j]);
+            }
+        }
+        (point.to_point(), d_u, d_v)
+    }
+
+    pub fn point_at(&self, u: Real, v: Real) -> Point {
+        self.evaluate(u, v).0
+    }
+
+    /// The normal follows the winding of the tessellated triangles.
+    /// Where the patch is degenerate (e.g. the rows of control points
+    /// collapse into a pole), the normal is taken slightly inside the patch.
+    pub fn normal_at(&self, u: Real, v: Real) -> Vector {
+        let (_, d_u, d_v) = self.evaluate(u, v);
+        let normal = d_v.cross(d_u);
+        if normal.magnitude() > 1e-9 {
+            return normal.normalize();
+        }
+
+        let nudge = |t: Real| t + (0.5 - t) * 1e-4;
+        let (_, d_u, d_v) = self.evaluate(nudge(u), nudge(v));
+        let normal = d_v.cross(d_u);
+        if normal.magnitude() > 0.0 {
+            normal.normalize()
+        } else {
+            normal
+        }
+    }
+
+    /// Splits the patch into a `level` x `level` grid of quads, each made of
+    /// two smooth triangles. Triangles that collapse into lines are dropped.
+    pub fn tessellate(&self, level: usize) -> Vec<Shape> {
+        let level = level.max(1);
+        let step = 1.0 / level as Real;
+        let samples: Vec<Vec<(Point, Vector)>> = (0..=level)
+            .map(|i| {
+                (0..=level)
+                    .map(|j| {
+                        let (u, v) = (i as Real * step, j as Real * step);
+                        (self.point_at(u, v), self.normal_at(u, v))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut triangles = vec![];
+        for i in 0..level {
+            for j in 0..level {
+                let corners = [
+                    [samples[i][j], samples[i + 1][j], samples[i + 1][j + 1]],
+                    [samples[i][j], samples[i + 1][j + 1], samples[i][j + 1]],
+                ];
+                for [(p1, n1), (p2, n2), (p3, n3)] in corners {
+                    let area = (p2 - p1).to_vector().cross((p3 - p1).to_vector());
+                    if area.magnitude() > 1e-12 {
+                        triangles.push(Shape::smooth_triangle(p1, p2, p3, n1, n2, n3));
+                    }
+                }
+            }
+        }
+        triangles
+    }
+}
+
+/// Tessellates all the patches into a single group
+pub fn to_group(patches: &[BezierPatch], level: usize) -> Rc<Shape> {
+    let triangles = patches
+        .iter()
+        .flat_map(|patch| patch.tessellate(level))
+        .collect();
+    GroupBuilder::new().children(triangles).build()
+}
@@ -1,10 +1,30 @@
 use crate::math;
+use crate::math::Real;
 use crate::rays::{Intersection3D, Ray};
-use crate::shapes::Shape;
-use crate::tuples::vectors;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::uvs::Uv;
+use crate::shapes::{Geo, Shape};
+use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
+use crate::tuples::{points, vectors};
 use std::rc::Rc;
 
+/// A disk centered at the origin, lying on the xz plane. A positive inner
+/// radius turns it into an annulus (a disk with a hole).
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+pub struct Disk {
+    pub radius: Real,
+    pub inner_radius: Real,
+}
+
+/// An axis-aligned rectangle on the xz plane, spanning from `min` to `max`
+/// (both given as (x, z) pairs)
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+pub struct Rect {
+    pub min: (Real, Real),
+    pub max: (Real, Real),
+}
+
 /// Computes the normal-at function for a plane.
 /// Note: The plane has no curvature, so it's normal vector
 /// should be the same regardless of the location
@@ -13,10 +33,119 @@ pub fn normal_at() -> Vector {
 }
 
 pub fn intersect(plane: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+    intersect_if(plane, ray, |_| true)
+}
+
+/// Intersects the ray with the xz plane, keeping the hit only if
+/// its point satisfies the predicate. The bounded flat shapes are all
+/// built on top of this.
+fn intersect_if<F>(shape: &Shape, ray: &Ray, contains: F) -> Vec<Intersection3D>
+where
+    F: FnOnce(Point) -> bool,
+{
     if ray.direction.y.abs() < math::EPSILON {
-        vec![]
+        return vec![];
+    }
+
+    let t = -ray.origin.y / ray.direction.y;
+    if contains(ray.position(t)) {
+        vec![Intersection3D::new(t, Rc::new(shape.clone()))]
     } else {
-        let t = -ray.origin.y / ray.direction.y;
-        vec![Intersection3D::new(t, Rc::new(plane.clone()))]
+        vec![]
+    }
+}
+
+impl Disk {
+    pub fn new(radius: Real) -> Disk {
+        Disk {
+            radius,
+            inner_radius: 0.0,
+        }
+    }
+
+    pub fn inner_radius(mut self, inner_radius: Real) -> Disk {
+        self.inner_radius = inner_radius;
+        self
+    }
+
+    pub fn to_geo(self) -> Geo {
+        Geo::Disk(self)
+    }
+
+    pub fn to_shape(self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            points::new(-self.radius, 0.0, -self.radius),
+            points::new(self.radius, 0.0, self.radius),
+        )
+    }
+
+    pub fn intersect(&self, disk: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        intersect_if(disk, ray, |point| {
+            let distance = point.x * point.x + point.z * point.z;
+            distance <= self.radius * self.radius
+                && distance >= self.inner_radius * self.inner_radius
+        })
+    }
+
+    /// Maps the square around the disk onto the whole texture
+    pub fn uv_at(&self, point: Point) -> Uv {
+        let coord = |value: Real| (value / self.radius + 1.0) / 2.0;
+        (coord(point.x), coord(point.z))
+    }
+}
+
+impl Rect {
+    pub fn new(min: (Real, Real), max: (Real, Real)) -> Rect {
+        Rect { min, max }
+    }
+
+    /// A rectangle centered at the origin
+    pub fn centered(width: Real, depth: Real) -> Rect {
+        Rect::new((-width / 2.0, -depth / 2.0), (width / 2.0, depth / 2.0))
+    }
+
+    pub fn to_geo(self) -> Geo {
+        Geo::Rect(self)
+    }
+
+    pub fn to_shape(self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let Rect {
+            min: (min_x, min_z),
+            max: (max_x, max_z),
+        } = *self;
+        Bounds::new(
+            points::new(min_x, 0.0, min_z),
+            points::new(max_x, 0.0, max_z),
+        )
+    }
+
+    pub fn intersect(&self, rect: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        let Rect {
+            min: (min_x, min_z),
+            max: (max_x, max_z),
+        } = *self;
+        intersect_if(rect, ray, |point| {
+            min_x <= point.x && point.x <= max_x && min_z <= point.z && point.z <= max_z
+        })
+    }
+
+    /// Stretches the texture over the whole rectangle
+    pub fn uv_at(&self, point: Point) -> Uv {
+        let Rect {
+            min: (min_x, min_z),
+            max: (max_x, max_z),
+        } = *self;
+        (
+            (point.x - min_x) / (max_x - min_x),
+            (point.z - min_z) / (max_z - min_z),
+        )
     }
 }
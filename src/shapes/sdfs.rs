@@ -0,0 +1,358 @@
+use crate::math::Real;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::shapes::bounds::Bounds;
+use crate::shapes::{cubes, Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use crate::tuples::{points, vectors};
+use std::rc::Rc;
+
+/// A signed distance function, built as an expression tree. The distance
+/// is negative inside the surface, zero on it, and positive outside.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sdf {
+    Sphere {
+        radius: Real,
+    },
+
+    /// An axis-aligned box centered at the origin
+    Cuboid {
+        half_extents: Vector,
+    },
+
+    /// A torus lying on the xz plane
+    Torus {
+        major_radius: Real,
+        minor_radius: Real,
+    },
+
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    Difference(Box<Sdf>, Box<Sdf>),
+
+    /// A union that blends the two surfaces where they are closer
+    /// than `k` to each other
+    SmoothUnion(Box<Sdf>, Box<Sdf>, Real),
+
+    Translate(Box<Sdf>, Vector),
+
+    /// Inflates the surface, rounding off its edges
+    Round(Box<Sdf>, Real),
+
+    /// Twists the shape around the y axis by `rate` radians per unit of height
+    Twist(Box<Sdf>, Real),
+
+    /// Repeats the shape infinitely, with the given period along each axis.
+    /// A period of zero disables the repetition on that axis.
+    Repeat(Box<Sdf>, Vector),
+}
+
+impl Sdf {
+    pub fn sphere(radius: Real) -> Sdf {
+        Sdf::Sphere { radius }
+    }
+
+    pub fn cuboid(x: Real, y: Real, z: Real) -> Sdf {
+        Sdf::Cuboid {
+            half_extents: vectors::new(x, y, z),
+        }
+    }
+
+    pub fn torus(major_radius: Real, minor_radius: Real) -> Sdf {
+        Sdf::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn union(self, other: Sdf) -> Sdf {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersection(self, other: Sdf) -> Sdf {
+        Sdf::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn difference(self, other: Sdf) -> Sdf {
+        Sdf::Difference(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, k: Real) -> Sdf {
+        Sdf::SmoothUnion(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn translate(self, x: Real, y: Real, z: Real) -> Sdf {
+        Sdf::Translate(Box::new(self), vectors::new(x, y, z))
+    }
+
+    pub fn round(self, radius: Real) -> Sdf {
+        Sdf::Round(Box::new(self), radius)
+    }
+
+    pub fn twist(self, rate: Real) -> Sdf {
+        Sdf::Twist(Box::new(self), rate)
+    }
+
+    pub fn repeat(self, x: Real, y: Real, z: Real) -> Sdf {
+        Sdf::Repeat(Box::new(self), vectors::new(x, y, z))
+    }
+
+    pub fn distance(&self, point: Point) -> Real {
+        match self {
+            Sdf::Sphere { radius } => point.to_vector().magnitude() - radius,
+            Sdf::Cuboid { half_extents } => {
+                let q = vectors::new(
+                    point.x.abs() - half_extents.x,
+                    point.y.abs() - half_extents.y,
+                    point.z.abs() - half_extents.z,
+                );
+                let outside = vectors::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0));
+                outside.magnitude() + q.x.max(q.y).max(q.z).min(0.0)
+            }
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = (point.x * point.x + point.z * point.z).sqrt() - major_radius;
+                (ring * ring + point.y * point.y).sqrt() - minor_radius
+            }
+            Sdf::Union(a, b) => a.distance(point).min(b.distance(point)),
+            Sdf::Intersection(a, b) => a.distance(point).max(b.distance(point)),
+            Sdf::Difference(a, b) => a.distance(point).max(-b.distance(point)),
+            Sdf::SmoothUnion(a, b, k) => {
+                let (d1, d2) = (a.distance(point), b.distance(point));
+                let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+                d2 + (d1 - d2) * h - k * h * (1.0 - h)
+            }
+            Sdf::Translate(sdf, offset) => sdf.distance(point - *offset),
+            Sdf::Round(sdf, radius) => sdf.distance(point) - radius,
+            Sdf::Twist(sdf, rate) => {
+                let (sin, cos) = (-rate * point.y).sin_cos();
+                let twisted = points::new(
+                    cos * point.x - sin * point.z,
+                    point.y,
+                    sin * point.x + cos * point.z,
+                );
+
+                // twisting stretches the space away from the axis, so the
+                // distance is scaled down to keep the tracing from overshooting
+                let stretch = rate * (point.x * point.x + point.z * point.z).sqrt();
+                sdf.distance(twisted) / (1.0 + stretch * stretch).sqrt()
+            }
+            Sdf::Repeat(sdf, period) => {
+                let wrap = |value: Real, period: Real| {
+                    if period == 0.0 {
+                        value
+                    } else {
+                        value - period * (value / period).round()
+                    }
+                };
+                sdf.distance(points::new(
+                    wrap(point.x, period.x),
+                    wrap(point.y, period.y),
+                    wrap(point.z, period.z),
+                ))
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            Sdf::Sphere { radius } => cube_bounds(*radius),
+            Sdf::Cuboid { half_extents: h } => {
+                Bounds::new(points::new(-h.x, -h.y, -h.z), points::new(h.x, h.y, h.z))
+            }
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let outer = major_radius + minor_radius;
+                Bounds::new(
+                    points::new(-outer, -minor_radius, -outer),
+                    points::new(outer, *minor_radius, outer),
+                )
+            }
+            Sdf::Union(a, b) => a.bounds() + b.bounds(),
+            Sdf::Intersection(a, b) => overlap(a.bounds(), b.bounds()),
+            Sdf::Difference(a, _) => a.bounds(),
+            Sdf::SmoothUnion(a, b, k) => grow(a.bounds() + b.bounds(), *k),
+            Sdf::Translate(sdf, offset) => {
+                let Bounds { min, max } = sdf.bounds();
+                Bounds::new(min + *offset, max + *offset)
+            }
+            Sdf::Round(sdf, radius) => grow(sdf.bounds(), *radius),
+            Sdf::Twist(sdf, _) => {
+                // any rotation around the y axis stays within this radius
+                let Bounds { min, max } = sdf.bounds();
+                let radius =
+                    Real::max(min.x.abs(), max.x.abs()).hypot(Real::max(min.z.abs(), max.z.abs()));
+                Bounds::new(
+                    points::new(-radius, min.y, -radius),
+                    points::new(radius, max.y, radius),
+                )
+            }
+            Sdf::Repeat(sdf, period) => {
+                let Bounds { min, max } = sdf.bounds();
+                let axis = |min: Real, max: Real, period: Real| {
+                    if period == 0.0 {
+                        (min, max)
+                    } else {
+                        (-Real::INFINITY, Real::INFINITY)
+                    }
+                };
+                let (min_x, max_x) = axis(min.x, max.x, period.x);
+                let (min_y, max_y) = axis(min.y, max.y, period.y);
+                let (min_z, max_z) = axis(min.z, max.z, period.z);
+                Bounds::new(
+                    points::new(min_x, min_y, min_z),
+                    points::new(max_x, max_y, max_z),
+                )
+            }
+        }
+    }
+}
+
+fn cube_bounds(half_size: Real) -> Bounds {
+    Bounds::from_min(points::new(-half_size, -half_size, -half_size))
+}
+
+fn grow(bounds: Bounds, amount: Real) -> Bounds {
+    let offset = vectors::new(amount, amount, amount);
+    Bounds::new(bounds.min - offset, bounds.max + offset)
+}
+
+fn overlap(a: Bounds, b: Bounds) -> Bounds {
+    Bounds::new(
+        points::new(
+            a.min.x.max(b.min.x),
+            a.min.y.max(b.min.y),
+            a.min.z.max(b.min.z),
+        ),
+        points::new(
+            a.max.x.min(b.max.x),
+            a.max.y.min(b.max.y),
+            a.max.z.min(b.max.z),
+        ),
+    )
+}
+
+/// A shape whose surface is the zero set of a signed distance function.
+/// Rays are intersected by sphere tracing: the ray keeps advancing by the
+/// distance to the surface until that distance drops below `epsilon`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceField {
+    pub sdf: Rc<Sdf>,
+
+    /// How close to the surface counts as a hit
+    pub epsilon: Real,
+
+    /// The maximum number of steps taken along a ray
+    pub max_steps: usize,
+
+    /// Rays are not traced farther than this (in object space) when the
+    /// shape is unbounded
+    pub max_distance: Real,
+
+    bounds: Bounds,
+}
+
+impl DistanceField {
+    pub fn new(sdf: Sdf) -> DistanceField {
+        DistanceField {
+            bounds: sdf.bounds(),
+            sdf: Rc::new(sdf),
+            epsilon: 1e-7,
+            max_steps: 256,
+            max_distance: 1000.0,
+        }
+    }
+
+    pub fn epsilon(mut self, epsilon: Real) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn max_distance(mut self, max_distance: Real) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn to_geo(&self) -> Geo {
+        Geo::DistanceField(self.clone())
+    }
+
+    pub fn to_shape(&self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Finds every crossing of the surface between the points where the ray
+    /// enters and leaves the bounding box
+    pub fn intersect(&self, field: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        let (t_min, t_max) = match cubes::intersect(field, ray).as_slice() {
+            [first, last] => (first.t, last.t),
+            _ => return vec![],
+        };
+
+        // the tracing is done in units of distance, along the normalized
+        // direction, since the ray's direction is scaled by the transformations
+        let speed = ray.direction.magnitude();
+        let direction = ray.direction / speed;
+        let start = if t_min.is_finite() {
+            t_min * speed
+        } else {
+            0.0
+        };
+        let end = if t_max.is_finite() {
+            t_max * speed
+        } else {
+            start + self.max_distance
+        };
+
+        let field = Rc::new(field.clone());
+        let mut xs = vec![];
+        let mut s = start;
+        let mut on_surface = false;
+
+        for _ in 0..self.max_steps {
+            if s > end {
+                break;
+            }
+            let distance = self.sdf.distance(ray.origin + direction * s).abs();
+            if distance < self.epsilon {
+                if !on_surface {
+                    xs.push(Intersection::new(s / speed, Rc::clone(&field)));
+                    on_surface = true;
+                }
+                // push through the surface, so the tracing can continue
+                // on the other side
+                s += self.epsilon;
+            } else {
+                on_surface = false;
+                s += distance;
+            }
+        }
+        xs
+    }
+
+    /// Estimates the gradient of the distance function with central differences
+    pub fn normal_at(&self, point: Point) -> Vector {
+        const DELTA: Real = 1e-5;
+        let gradient = |axis: Vector| {
+            self.sdf.distance(point + axis * DELTA) - self.sdf.distance(point - axis * DELTA)
+        };
+        vectors::new(
+            gradient(vectors::new(1.0, 0.0, 0.0)),
+            gradient(vectors::new(0.0, 1.0, 0.0)),
+            gradient(vectors::new(0.0, 0.0, 1.0)),
+        )
+    }
+}
@@ -0,0 +1,343 @@
+use crate::math::Real;
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::meshes::{Mesh, MeshTriangle};
+use crate::shapes::Shape;
+use crate::tuples::points::Point;
+use crate::tuples::vectors;
+use crate::tuples::vectors::Vector;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// A polygon mesh with shared, 0-based vertex indices. This is the input
+/// and output of the subdivision schemes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyMesh {
+    pub vertices: Vec<Point>,
+    pub faces: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Subdivision {
+    /// Splits each triangle into four. Other polygons are triangulated first.
+    Loop,
+
+    /// Splits each n-sided face into n quads
+    CatmullClark,
+}
+
+/// For each edge (keyed by its sorted endpoints), the faces sharing it
+type EdgeFaces = BTreeMap<(usize, usize), Vec<usize>>;
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn average(points: &[Point]) -> Point {
+    let sum = points
+        .iter()
+        .fold(Vector::zero(), |sum, point| sum + point.to_vector());
+    (sum / points.len() as Real).to_point()
+}
+
+fn weighted(terms: &[(Point, Real)]) -> Point {
+    terms
+        .iter()
+        .fold(Vector::zero(), |sum, (point, weight)| {
+            sum + point.to_vector() * *weight
+        })
+        .to_point()
+}
+
+impl PolyMesh {
+    /// Faces with fewer than 3 distinct vertices are left out, since they
+    /// have no area and the subdivision schemes can't handle them
+    pub fn new(vertices: Vec<Point>, faces: Vec<Vec<usize>>) -> PolyMesh {
+        let faces = faces
+            .into_iter()
+            .filter(|face| {
+                let distinct = face
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, v)| !face[..*i].contains(v))
+                    .count();
+                distinct >= 3
+            })
+            .collect();
+        PolyMesh { vertices, faces }
+    }
+
+    pub fn subdivide(&self, scheme: Subdivision, levels: usize) -> PolyMesh {
+        (0..levels).fold(self.clone(), |mesh, _| match scheme {
+            Subdivision::Loop => mesh.loop_step(),
+            Subdivision::CatmullClark => mesh.catmull_clark_step(),
+        })
+    }
+
+    /// Fans each face into triangles
+    pub fn triangulate(&self) -> PolyMesh {
+        let faces = self
+            .faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |i| vec![face[0], face[i], face[i + 1]]))
+            .collect();
+        PolyMesh::new(self.vertices.clone(), faces)
+    }
+
+    fn edge_faces(&self) -> EdgeFaces {
+        let mut edges: EdgeFaces = BTreeMap::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                edges.entry(edge_key(a, b)).or_default().push(f);
+            }
+        }
+        edges
+    }
+
+    /// The neighbors of each vertex, and whether the vertex is on a boundary
+    fn neighbors(&self, edges: &EdgeFaces) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let mut neighbors = vec![(vec![], vec![]); self.vertices.len()];
+        for (&(a, b), faces) in edges {
+            neighbors[a].0.push(b);
+            neighbors[b].0.push(a);
+            if faces.len() == 1 {
+                neighbors[a].1.push(b);
+                neighbors[b].1.push(a);
+            }
+        }
+        neighbors
+    }
+
+    /// Boundary vertices only follow their boundary neighbors, so open
+    /// edges stay sharp instead of shrinking. Returns `None` for interior
+    /// vertices, which are left to the subdivision scheme.
+    fn boundary_point(&self, vertex: usize, around: &[usize], boundary: &[usize]) -> Option<Point> {
+        if around.is_empty() {
+            // unused vertices are kept as they are
+            return Some(self.vertices[vertex]);
+        }
+        match boundary {
+            [a, b] => Some(weighted(&[
+                (self.vertices[vertex], 0.75),
+                (self.vertices[*a], 0.125),
+                (self.vertices[*b], 0.125),
+            ])),
+            [] => None,
+            // non-manifold corners are kept in place
+            _ => Some(self.vertices[vertex]),
+        }
+    }
+
+    fn loop_step(&self) -> PolyMesh {
+        let mesh = self.triangulate();
+        let edges = mesh.edge_faces();
+        let neighbors = mesh.neighbors(&edges);
+
+        let mut vertices: Vec<Point> = neighbors
+            .iter()
+            .enumerate()
+            .map(|(v, (around, boundary))| {
+                mesh.boundary_point(v, around, boundary).unwrap_or_else(|| {
+                    let n = around.len() as Real;
+                    let beta = if around.len() == 3 {
+                        3.0 / 16.0
+                    } else {
+                        3.0 / (8.0 * n)
+                    };
+                    let mut terms = vec![(mesh.vertices[v], 1.0 - n * beta)];
+                    terms.extend(around.iter().map(|&u| (mesh.vertices[u], beta)));
+                    weighted(&terms)
+                })
+            })
+            .collect();
+
+        let mut edge_points = HashMap::new();
+        for (&(a, b), faces) in &edges {
+            let (pa, pb) = (mesh.vertices[a], mesh.vertices[b]);
+            let point = if let [f1, f2] = faces[..] {
+                let opposite = |f: usize| {
+                    let face = &mesh.faces[f];
+                    mesh.vertices[*face.iter().find(|&&v| v != a && v != b).unwrap()]
+                };
+                weighted(&[
+                    (pa, 0.375),
+                    (pb, 0.375),
+                    (opposite(f1), 0.125),
+                    (opposite(f2), 0.125),
+                ])
+            } else {
+                average(&[pa, pb])
+            };
+            edge_points.insert((a, b), vertices.len());
+            vertices.push(point);
+        }
+
+        let faces = mesh
+            .faces
+            .iter()
+            .flat_map(|face| {
+                let (a, b, c) = (face[0], face[1], face[2]);
+                let ab = edge_points[&edge_key(a, b)];
+                let bc = edge_points[&edge_key(b, c)];
+                let ca = edge_points[&edge_key(c, a)];
+                vec![
+                    vec![a, ab, ca],
+                    vec![ab, b, bc],
+                    vec![ca, bc, c],
+                    vec![ab, bc, ca],
+                ]
+            })
+            .collect();
+
+        PolyMesh::new(vertices, faces)
+    }
+
+    fn catmull_clark_step(&self) -> PolyMesh {
+        let edges = self.edge_faces();
+        let neighbors = self.neighbors(&edges);
+
+        let face_points: Vec<Point> = self
+            .faces
+            .iter()
+            .map(|face| average(&face.iter().map(|&v| self.vertices[v]).collect::<Vec<_>>()))
+            .collect();
+
+        let mut vertex_faces = vec![vec![]; self.vertices.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            for &v in face {
+                vertex_faces[v].push(f);
+            }
+        }
+
+        let mut vertices: Vec<Point> = neighbors
+            .iter()
+            .enumerate()
+            .map(|(v, (around, boundary))| {
+                self.boundary_point(v, around, boundary).unwrap_or_else(|| {
+                    // (F + 2R + (n - 3)P) / n
+                    let n = around.len() as Real;
+                    let faces: Vec<_> = vertex_faces[v].iter().map(|&f| face_points[f]).collect();
+                    let midpoints: Vec<_> = around
+                        .iter()
+                        .map(|&u| average(&[self.vertices[v], self.vertices[u]]))
+                        .collect();
+                    weighted(&[
+                        (average(&faces), 1.0 / n),
+                        (average(&midpoints), 2.0 / n),
+                        (self.vertices[v], (n - 3.0) / n),
+                    ])
+                })
+            })
+            .collect();
+
+        let face_start = vertices.len();
+        vertices.extend(&face_points);
+
+        let mut edge_points = HashMap::new();
+        for (&(a, b), faces) in &edges {
+            let (pa, pb) = (self.vertices[a], self.vertices[b]);
+            let point = if let [f1, f2] = faces[..] {
+                average(&[pa, pb, face_points[f1], face_points[f2]])
+            } else {
+                average(&[pa, pb])
+            };
+            edge_points.insert((a, b), vertices.len());
+            vertices.push(point);
+        }
+
+        let faces = self
+            .faces
+            .iter()
+            .enumerate()
+            .flat_map(|(f, face)| {
+                let n = face.len();
+                let edge_points = &edge_points;
+                (0..n).map(move |i| {
+                    let (prev, v, next) = (face[(i + n - 1) % n], face[i], face[(i + 1) % n]);
+                    vec![
+                        v,
+                        edge_points[&edge_key(v, next)],
+                        face_start + f,
+                        edge_points[&edge_key(prev, v)],
+                    ]
+                })
+            })
+            .collect();
+
+        PolyMesh::new(vertices, faces)
+    }
+
+    /// The normal of each vertex is the average of the normals of the
+    /// triangles around it, weighted by their areas. The normals follow
+    /// the same winding as `Triangle::regular`.
+    pub fn vertex_normals(&self) -> Vec<Vector> {
+        let mut normals = vec![Vector::zero(); self.vertices.len()];
+        for face in self.triangulate().faces {
+            let (p1, p2, p3) = (
+                self.vertices[face[0]],
+                self.vertices[face[1]],
+                self.vertices[face[2]],
+            );
+            let normal = (p3 - p1).to_vector().cross((p2 - p1).to_vector());
+            for v in face {
+                normals[v] = normals[v] + normal;
+            }
+        }
+        normals
+            .into_iter()
+            .map(|normal| {
+                if normal.magnitude() == 0.0 {
+                    vectors::new(0.0, 1.0, 0.0)
+                } else {
+                    normal.normalize()
+                }
+            })
+            .collect()
+    }
+
+    /// Converts the mesh into a group of smooth triangles
+    pub fn to_group(&self) -> Rc<Shape> {
+        let normals = self.vertex_normals();
+        let triangles = self
+            .triangulate()
+            .faces
+            .into_iter()
+            .map(|face| {
+                let (a, b, c) = (face[0], face[1], face[2]);
+                Shape::smooth_triangle(
+                    self.vertices[a],
+                    self.vertices[b],
+                    self.vertices[c],
+                    normals[a],
+                    normals[b],
+                    normals[c],
+                )
+            })
+            .collect();
+        GroupBuilder::new().children(triangles).build()
+    }
+
+    /// Converts the mesh into a `Mesh` of smooth triangles, sharing the
+    /// vertices and their normals instead of copying them per triangle
+    pub fn to_indexed(&self) -> Mesh {
+        let triangles = self
+            .triangulate()
+            .faces
+            .into_iter()
+            .map(|face| {
+                let corners = [face[0], face[1], face[2]];
+                MeshTriangle::new(corners).normals(corners)
+            })
+            .collect();
+        Mesh::new(
+            self.vertices.clone(),
+            self.vertex_normals(),
+            vec![],
+            triangles,
+        )
+    }
+}
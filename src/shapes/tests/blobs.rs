@@ -0,0 +1,172 @@
+use crate::materials::Material;
+use crate::math;
+use crate::math::Real;
+use crate::matrix::CanTransform;
+use crate::patterns::Pattern;
+use crate::rays::Ray;
+use crate::shapes::blobs::Blob;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::points::Point;
+use crate::tuples::{points, vectors};
+
+fn ts(shape: &Shape, ray: &Ray) -> Vec<Real> {
+    shape
+        .intersect(ray)
+        .iter()
+        .map(|x| math::round_to_5(x.t))
+        .collect()
+}
+
+/// With a threshold of 1/4, a single metaball's surface is at r/sqrt(2)
+fn single() -> Blob {
+    Blob::new(0.25).metaball(Point::origin(), 2.0, 1.0)
+}
+
+fn pair() -> Blob {
+    Blob::new(0.25)
+        .metaball(points::new(-1.0, 0.0, 0.0), 1.5, 1.0)
+        .metaball(points::new(1.0, 0.0, 0.0), 1.5, 1.0)
+}
+
+#[test]
+fn test_intersecting_a_single_metaball() {
+    let blob = single().to_shape();
+    let surface = math::round_to_5(2.0_f64.sqrt());
+
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(
+        ts(&blob, &ray),
+        vec![
+            math::round_to_5(5.0 - surface),
+            math::round_to_5(5.0 + surface)
+        ]
+    );
+
+    let ray = Ray::new(points::new(0.0, 1.5, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(blob.intersect(&ray).is_empty());
+}
+
+/// A large blob has a tiny leading coefficient along its object-space ray,
+/// which must not be mistaken for a lower-degree polynomial
+#[test]
+fn test_intersecting_a_scaled_up_blob() {
+    let blob = single().to_shape().scale_all(200.0);
+    let surface = 200.0 * 2.0_f64.sqrt();
+    let ray = Ray::new(points::new(0.0, 0.0, -1000.0), vectors::new(0.0, 0.0, 1.0));
+    let xs = blob.intersect(&ray);
+    assert_eq!(xs.len(), 2);
+    assert!((xs[0].t - (1000.0 - surface)).abs() < 1e-4);
+    assert!((xs[1].t - (1000.0 + surface)).abs() < 1e-4);
+
+    let blob = Blob::new(0.25)
+        .metaball(Point::origin(), 400.0, 1.0)
+        .to_shape();
+    let surface = 400.0 / 2.0_f64.sqrt();
+    let xs = blob.intersect(&ray);
+    assert_eq!(xs.len(), 2);
+    assert!((xs[0].t - (1000.0 - surface)).abs() < 1e-4);
+}
+
+/// The field of two metaballs merges them into a single surface
+#[test]
+fn test_metaballs_blend() {
+    let blob = pair().to_shape();
+
+    // neither metaball alone reaches the middle point above the centers
+    let ray = Ray::new(points::new(0.0, 5.0, 0.0), vectors::new(0.0, -1.0, 0.0));
+    let xs = ts(&blob, &ray);
+    assert_eq!(xs.len(), 2);
+    assert_eq!(xs[0], math::round_to_5(10.0 - xs[1]));
+
+    let apart = Blob::new(0.25)
+        .metaball(points::new(-2.0, 0.0, 0.0), 1.5, 1.0)
+        .metaball(points::new(2.0, 0.0, 0.0), 1.5, 1.0)
+        .to_shape();
+    assert!(apart.intersect(&ray).is_empty());
+
+    let ray = Ray::new(points::new(-5.0, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&apart, &ray).len(), 4);
+}
+
+#[test]
+fn test_negative_metaballs_carve() {
+    let blob = single()
+        .metaball(points::new(0.0, 0.0, -1.0), 1.0, -2.0)
+        .to_shape();
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let xs = ts(&blob, &ray);
+    assert_eq!(xs.len(), 2);
+
+    // the front is carved in, while the back is untouched
+    assert!(xs[0] > 5.0 - 2.0_f64.sqrt());
+    assert_eq!(xs[1], math::round_to_5(5.0 + 2.0_f64.sqrt()));
+}
+
+#[test]
+fn test_blob_normals() {
+    let blob = single().to_shape().translate(0.0, 1.0, 0.0);
+    let sphere = Shape::sphere().translate(0.0, 1.0, 0.0);
+    let value = math::two_sqrt_div_2();
+    let data = [
+        points::new(0.0, 2.0, 0.0),
+        points::new(0.0, 1.0, -1.0),
+        points::new(value, 1.0, value),
+    ];
+    for point in data {
+        assert_eq!(
+            blob.default_normal_at(point),
+            sphere.default_normal_at(point)
+        );
+    }
+
+    // the normal between two blended metaballs points straight up
+    let normal = pair()
+        .to_shape()
+        .default_normal_at(points::new(0.0, 0.5, 0.0));
+    assert_eq!(normal, vectors::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_blob_bounds() {
+    assert_eq!(
+        pair().to_shape().bounds(),
+        Bounds::new(points::new(-2.5, -1.5, -1.5), points::new(2.5, 1.5, 1.5))
+    );
+
+    // negative metaballs don't grow the bounds
+    let carved = single().metaball(points::new(0.0, 0.0, -5.0), 4.0, -1.0);
+    assert_eq!(carved.bounds(), single().bounds());
+}
+
+#[test]
+fn test_blob_in_a_group() {
+    let group = GroupBuilder::new()
+        .child(pair().to_shape())
+        .translate(0.0, 0.0, 5.0)
+        .build();
+    let ray = Ray::new(points::new(-1.0, 0.0, 0.0), vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(group.intersect(&ray).len(), 2);
+
+    let ray = Ray::new(points::new(-1.0, 2.0, 0.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(group.intersect(&ray).is_empty());
+}
+
+#[test]
+fn test_patterned_blob() {
+    let pattern = Pattern::stripe(Color::white(), Color::black());
+    let blob = pair()
+        .to_shape()
+        .material(Material::default().pattern(pattern));
+    let material = &blob.material;
+    assert_eq!(
+        material.color_at(&blob, points::new(0.5, 0.0, 0.0)),
+        Color::white()
+    );
+    assert_eq!(
+        material.color_at(&blob, points::new(-0.5, 0.0, 0.0)),
+        Color::black()
+    );
+}
@@ -1,7 +1,7 @@
 use crate::math;
-use crate::matrix::{rotation_y, scaling, translation, CanTransform, Matrix};
+use crate::matrix::{rotation_y, scaling, translation, CanTransform, Matrix44};
 use crate::rays::Ray;
-use crate::shapes::groups::not_a_group;
+use crate::shapes::groups::{not_a_group, Group, GroupBuilder};
 use crate::shapes::{Geo, Shape};
 use crate::tuples::points::Point;
 use crate::tuples::{points, vectors};
@@ -11,7 +11,7 @@ use std::rc::Rc;
 #[test]
 fn test_create_group() {
     let group = Shape::empty_group();
-    assert_eq!(group.transformation, Matrix::id44());
+    assert_eq!(group.transformation, Matrix44::id());
     if let Geo::Group(group) = group.geo {
         assert!(group.is_empty());
     } else {
@@ -94,7 +94,7 @@ fn test_intersect_transformed() {
     }
 }
 
-fn with_nested_object<F>(outer_trans: Matrix, inner_trans: Matrix, child_trans: Matrix, f: F)
+fn with_nested_object<F>(outer_trans: Matrix44, inner_trans: Matrix44, child_trans: Matrix44, f: F)
 where
     F: Fn(&Shape) -> (),
 {
@@ -162,3 +162,141 @@ fn test_normal_on_child() {
         },
     );
 }
+
+/// World transformations are baked, so they survive the parents being dropped
+#[test]
+fn test_world_to_object_without_parent_links() {
+    let shape = Rc::new(Shape::sphere().transform(translation(5.0, 0.0, 0.0)));
+    {
+        let outer = Rc::new(Shape::empty_group().transform(rotation_y(math::PI / 2.0)));
+        let inner = Rc::new(Shape::empty_group().transform(scaling(2.0, 2.0, 2.0)));
+
+        // children are added bottom-up this time
+        if let (Geo::Group(g1), Geo::Group(g2)) = (&outer.geo, &inner.geo) {
+            g2.add_child(Rc::downgrade(&inner), Rc::clone(&shape));
+            g1.add_child(Rc::downgrade(&outer), Rc::clone(&inner));
+        }
+    }
+
+    assert!(shape.get_parent().is_none());
+    let point = shape.world_to_object(points::new(-2.0, 0.0, -10.0));
+    assert_eq!(point, points::new(0.0, 0.0, -1.0));
+}
+
+/// Transforming a group after adding its children requires finalizing it again
+#[test]
+fn test_finalizing_transformed_groups() {
+    let children = vec![Rc::new(Shape::sphere().translate(5.0, 0.0, 0.0))];
+    let group = Shape::from_group(Group::new(children))
+        .scale(2.0, 2.0, 2.0)
+        .rotate_y(math::PI / 2.0);
+    group.finalize();
+
+    if let Geo::Group(g) = &group.geo {
+        let child = g.get_child(0);
+        let point = child.world_to_object(points::new(-2.0, 0.0, -10.0));
+        assert_eq!(point, points::new(0.0, 0.0, -1.0));
+    } else {
+        not_a_group();
+    }
+}
+
+#[test]
+fn test_group_builder_links_children() {
+    let sphere = Rc::new(Shape::sphere().translate(5.0, 0.0, 0.0));
+    let inner = GroupBuilder::new()
+        .child(Rc::clone(&sphere))
+        .scale(2.0, 2.0, 2.0)
+        .build();
+    let outer = GroupBuilder::new()
+        .child(Rc::clone(&inner))
+        .child(Shape::cube())
+        .rotate_y(math::PI / 2.0)
+        .build();
+
+    assert_eq!(sphere.get_parent(), Some(Rc::clone(&inner)));
+    assert_eq!(inner.get_parent(), Some(Rc::clone(&outer)));
+    assert_eq!(outer.as_group().unwrap().children.borrow().len(), 2);
+
+    let point = sphere.world_to_object(points::new(-2.0, 0.0, -10.0));
+    assert_eq!(point, points::new(0.0, 0.0, -1.0));
+}
+
+/// `Shape::group` links the children like the builder
+#[test]
+fn test_shape_group_links_children() {
+    let group = Shape::group(vec![Shape::sphere(), Shape::cube()]);
+    for child in group.as_group().unwrap().children.borrow().iter() {
+        assert_eq!(child.get_parent(), Some(Rc::clone(&group)));
+    }
+}
+
+#[test]
+fn test_reparenting() {
+    let sphere = Rc::new(Shape::sphere());
+    let first = GroupBuilder::new().child(Rc::clone(&sphere)).build();
+    let second = GroupBuilder::new().translate(0.0, 3.0, 0.0).build();
+
+    Shape::reparent(&sphere, &second);
+
+    assert!(first.as_group().unwrap().is_empty());
+    assert!(second.as_group().unwrap().contains(Rc::clone(&sphere)));
+    assert_eq!(sphere.get_parent(), Some(Rc::clone(&second)));
+    assert_eq!(
+        sphere.world_to_object(points::new(0.0, 4.0, 0.0)),
+        points::new(0.0, 1.0, 0.0)
+    );
+}
+
+#[test]
+#[should_panic(expected = "A group can't contain itself")]
+fn test_reparenting_into_a_descendant() {
+    let outer = Rc::new(Shape::empty_group());
+    let inner = Rc::new(Shape::empty_group());
+    outer.add_child(Rc::clone(&inner));
+
+    Shape::reparent(&outer, &inner);
+}
+
+#[test]
+#[should_panic(expected = "A group can't contain itself")]
+fn test_adding_a_group_to_itself() {
+    let group = Rc::new(Shape::empty_group());
+    group.add_child(Rc::clone(&group));
+}
+
+#[test]
+fn test_removing_children() {
+    let sphere = Rc::new(Shape::sphere());
+    let group = GroupBuilder::new()
+        .child(Rc::clone(&sphere))
+        .translate(0.0, 3.0, 0.0)
+        .build();
+
+    assert!(group.remove_child(&sphere));
+    assert!(!group.remove_child(&sphere));
+    assert!(group.as_group().unwrap().is_empty());
+    assert!(sphere.get_parent().is_none());
+
+    // the removed child no longer inherits the group's transformation
+    assert_eq!(
+        sphere.world_to_object(points::new(0.0, 4.0, 0.0)),
+        points::new(0.0, 4.0, 0.0)
+    );
+}
+
+#[test]
+fn test_bounds_invalidation() {
+    let inner = GroupBuilder::new().child(Shape::sphere()).build();
+    let outer = GroupBuilder::new().child(Rc::clone(&inner)).build();
+    assert_eq!(outer.bounds().max, points::new(1.0, 1.0, 1.0));
+
+    // adding to the inner group changes the bounds of both groups
+    let far = Rc::new(Shape::sphere().translate(5.0, 0.0, 0.0));
+    inner.add_child(Rc::clone(&far));
+    assert_eq!(inner.bounds().max, points::new(6.0, 1.0, 1.0));
+    assert_eq!(outer.bounds().max, points::new(6.0, 1.0, 1.0));
+
+    inner.remove_child(&far);
+    assert_eq!(outer.bounds().max, points::new(1.0, 1.0, 1.0));
+}
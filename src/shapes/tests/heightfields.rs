@@ -0,0 +1,190 @@
+use crate::canvas::Canvas;
+use crate::math;
+use crate::math::Real;
+use crate::matrix::CanTransform;
+use crate::rays::{Intersection, Ray};
+use crate::shapes::bounds::Bounds;
+use crate::shapes::heightfields::HeightField;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::points::Point;
+use crate::tuples::{colors, points, vectors};
+use std::io;
+use std::rc::Rc;
+
+fn hit_t(shape: &Shape, ray: &Ray) -> Option<Real> {
+    Intersection::hit(shape.intersect(ray)).map(|hit| math::round_to_5(hit.t))
+}
+
+fn terrain(x: Real, z: Real) -> Real {
+    (x * 7.0).sin() * 0.3 + (z * 5.0).cos() * 0.2
+}
+
+/// The same terrain, as a group of triangles
+fn terrain_mesh(size: usize) -> Rc<Shape> {
+    let step = 1.0 / (size - 1) as Real;
+    let vertex = |col: usize, row: usize| {
+        let (x, z) = (col as Real * step, row as Real * step);
+        points::new(x, terrain(x, z), z)
+    };
+    let triangles = (0..size - 1)
+        .flat_map(|row| (0..size - 1).map(move |col| (col, row)))
+        .flat_map(|(col, row)| {
+            let (p00, p10) = (vertex(col, row), vertex(col + 1, row));
+            let (p01, p11) = (vertex(col, row + 1), vertex(col + 1, row + 1));
+            [
+                Shape::triangle(p00, p10, p11),
+                Shape::triangle(p00, p11, p01),
+            ]
+        })
+        .collect();
+    Shape::group(triangles)
+}
+
+#[test]
+fn test_ray_hits_a_flat_field() {
+    let field = HeightField::from_fn(4, 4, |_, _| 0.5).to_shape();
+    let ray = Ray::new(points::new(0.3, 5.0, 0.7), vectors::new(0.0, -1.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(4.5));
+    assert_eq!(
+        field.default_normal_at(points::new(0.3, 0.5, 0.7)),
+        vectors::new(0.0, 1.0, 0.0)
+    );
+
+    let ray = Ray::new(points::new(1.5, 5.0, 0.5), vectors::new(0.0, -1.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), None);
+}
+
+#[test]
+fn test_ray_crosses_cells_to_a_slope() {
+    let field = HeightField::from_fn(5, 3, |x, _| x).to_shape();
+
+    let ray = Ray::new(points::new(-1.0, 0.6, 0.5), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(1.6));
+
+    // a ray from the other side passes above the slope
+    let ray = Ray::new(points::new(2.0, 1.5, 0.3), vectors::new(-1.0, 0.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), None);
+
+    let ray = Ray::new(points::new(1.5, 2.5, 0.3), vectors::new(-1.0, -2.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(1.0));
+}
+
+#[test]
+fn test_heightfield_matches_a_mesh() {
+    let field = HeightField::from_fn(9, 9, terrain).to_shape();
+    let mesh = terrain_mesh(9);
+    let rays = [
+        (points::new(-1.0, 1.0, 0.3), vectors::new(1.0, -0.7, 0.2)),
+        (points::new(2.0, 0.6, 1.5), vectors::new(-1.0, -0.3, -0.8)),
+        (points::new(0.45, 3.0, 0.55), vectors::new(0.01, -1.0, 0.02)),
+        (points::new(0.1, 0.0, -1.0), vectors::new(0.2, 0.05, 1.0)),
+        (points::new(0.5, -2.0, 0.5), vectors::new(0.1, 1.0, 0.1)),
+    ];
+    for (origin, direction) in rays {
+        let ray = Ray::new(origin, direction);
+        assert_eq!(hit_t(&field, &ray), hit_t(&mesh, &ray));
+    }
+}
+
+#[test]
+fn test_interpolated_normals() {
+    let field = HeightField::from_fn(5, 5, |x, z| x * x + z).to_shape();
+
+    // the normals at the samples come from the slopes around them
+    let normal = field.default_normal_at(points::new(0.5, 0.75, 0.5));
+    assert_eq!(normal, vectors::new(-1.0, 1.0, -1.0).normalize());
+
+    // halfway between two samples, the normal is in between
+    let normal = field.default_normal_at(points::new(0.625, 1.0, 0.5));
+    let expected = (vectors::new(-1.0, 1.0, -1.0).normalize()
+        + vectors::new(-1.5, 1.0, -1.0).normalize())
+    .normalize();
+    assert_eq!(normal, expected);
+}
+
+/// Like the other shapes, the field reports where the ray enters and leaves
+/// it, including the crossings behind the ray's origin
+#[test]
+fn test_ray_crosses_a_ridge() {
+    let field = HeightField::from_fn(5, 5, |x, _| 1.0 - (2.0 * x - 1.0).abs()).to_shape();
+    let ts = |ray: &Ray| -> Vec<Real> {
+        field
+            .intersect(ray)
+            .iter()
+            .map(|x| math::round_to_5(x.t))
+            .collect()
+    };
+
+    let ray = Ray::new(points::new(-1.0, 0.6, 0.5), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&ray), vec![1.3, 1.7]);
+    let ray = Ray::new(points::new(0.5, 0.6, 0.5), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&ray), vec![-0.2, 0.2]);
+
+    // through the edge between two cells, the ray crosses the surface once
+    let ray = Ray::new(points::new(-1.0, 0.5, 0.5), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&ray), vec![1.25, 1.75]);
+}
+
+#[test]
+#[should_panic(expected = "A heightfield needs at least 2 x 2 samples")]
+fn test_heightfield_with_a_single_row() {
+    HeightField::from_fn(4, 1, |x, _| x);
+}
+
+#[test]
+#[should_panic(expected = "A heightfield needs one height per sample")]
+fn test_heightfield_with_missing_heights() {
+    HeightField::new(2, 2, vec![0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_heightfield_bounds() {
+    let field = HeightField::from_fn(3, 3, |x, z| x - z).to_shape();
+    assert_eq!(
+        field.bounds(),
+        Bounds::new(points::new(0.0, -1.0, 0.0), points::new(1.0, 1.0, 1.0))
+    );
+
+    let scaled = field.scale(100.0, 10.0, 100.0);
+    assert_eq!(
+        scaled.parent_space_bounds(),
+        Bounds::new(
+            points::new(0.0, -10.0, 0.0),
+            points::new(100.0, 10.0, 100.0)
+        )
+    );
+}
+
+#[test]
+fn test_heightfield_from_an_image() {
+    let mut image = Canvas::new(3, 2);
+    image[(1, 0)] = Color::white();
+    image[(2, 1)] = colors::new(0.5, 0.5, 0.5);
+    let field = HeightField::from_canvas(&image).unwrap().to_shape();
+
+    let ray = Ray::new(points::new(0.5, 5.0, 0.0), vectors::new(0.0, -1.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(4.0));
+    let ray = Ray::new(points::new(1.0, 5.0, 1.0), vectors::new(0.0, -1.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(4.5));
+    let ray = Ray::new(points::new(0.0, 5.0, 1.0), vectors::new(0.0, -1.0, 0.0));
+    assert_eq!(hit_t(&field, &ray), Some(5.0));
+
+    assert_eq!(
+        field.bounds(),
+        Bounds::new(Point::origin(), points::new(1.0, 1.0, 1.0))
+    );
+}
+
+#[test]
+fn test_loading_a_heightmap() {
+    let field = HeightField::load("resources/texture.ppm").unwrap();
+    assert_eq!(field.bounds().min.x, 0.0);
+    assert!(HeightField::load("resources/missing.ppm").is_err());
+
+    // a single row or column of pixels doesn't make a surface
+    for (width, height) in [(1, 4), (4, 1)] {
+        let error = HeightField::from_canvas(&Canvas::new(width, height)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::materials::Material;
+use crate::math;
+use crate::matrix::CanTransform;
+use crate::rays::{Intersection, Ray};
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::Shape;
+use crate::tuples::colors::Color;
+use crate::tuples::{points, vectors};
+use std::rc::Rc;
+
+fn prototype() -> Rc<Shape> {
+    GroupBuilder::new()
+        .child(Shape::sphere().translate(0.0, 0.0, 2.0))
+        .scale(2.0, 2.0, 2.0)
+        .build()
+}
+
+#[test]
+fn test_intersecting_an_instance() {
+    let instance = Shape::instance(prototype()).translate(5.0, 0.0, 0.0);
+    instance.finalize();
+
+    let ray = Ray::new(points::new(5.0, 0.0, -10.0), vectors::new(0.0, 0.0, 1.0));
+    let xs = instance.intersect(&ray);
+    assert_eq!(xs.len(), 2);
+    assert_eq!(xs[0].t, 12.0);
+    assert_eq!(xs[1].t, 16.0);
+
+    let ray = Ray::new(points::new(0.0, 0.0, -10.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(instance.intersect(&ray).is_empty());
+}
+
+/// The normals of an instance match the ones of an equivalent copy
+#[test]
+fn test_instance_normals() {
+    let instance = Shape::instance(prototype()).rotate_y(math::PI / 2.0);
+    instance.finalize();
+
+    let ray = Ray::new(points::new(-10.0, 1.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    let hit = Intersection::hit(instance.intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    let normal = hit.object.normal_at(point, &hit);
+
+    let copy = Shape::sphere()
+        .translate(0.0, 0.0, 2.0)
+        .scale(2.0, 2.0, 2.0)
+        .rotate_y(math::PI / 2.0);
+    assert_eq!(normal, copy.default_normal_at(point));
+    assert_eq!(
+        hit.object.world_to_object(point),
+        copy.world_to_object(point)
+    );
+}
+
+/// Baking the shared shape inside a group doesn't move its instances
+#[test]
+fn test_prototype_in_a_group_and_an_instance() {
+    let shared = prototype();
+    let group = GroupBuilder::new()
+        .child(Rc::clone(&shared))
+        .translate(0.0, 10.0, 0.0)
+        .build();
+    let instance = Shape::instance(Rc::clone(&shared)).rotate_y(math::PI / 2.0);
+    instance.finalize();
+    group.finalize();
+
+    let ray = Ray::new(points::new(-10.0, 1.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    let hit = Intersection::hit(instance.intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    let copy = Shape::sphere()
+        .translate(0.0, 0.0, 2.0)
+        .scale(2.0, 2.0, 2.0)
+        .rotate_y(math::PI / 2.0);
+    assert_eq!(
+        hit.object.normal_at(point, &hit),
+        copy.default_normal_at(point)
+    );
+    assert_eq!(
+        hit.object.world_to_object(point),
+        copy.world_to_object(point)
+    );
+
+    // the shape in the group still follows the group
+    let ray = Ray::new(points::new(0.0, 11.0, -10.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(group.intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    let copy = Shape::sphere()
+        .translate(0.0, 0.0, 2.0)
+        .scale(2.0, 2.0, 2.0)
+        .translate(0.0, 10.0, 0.0);
+    assert_eq!(
+        hit.object.normal_at(point, &hit),
+        copy.default_normal_at(point)
+    );
+}
+
+#[test]
+fn test_instances_share_geometry() {
+    let shared = prototype();
+    let red = Material::default().color(Color::red());
+    let first = Shape::instance(Rc::clone(&shared));
+    let second =
+        Shape::instance_with_material(Rc::clone(&shared), red.clone()).translate(0.0, 10.0, 0.0);
+    second.finalize();
+
+    assert_eq!(Rc::strong_count(&shared), 3);
+
+    let ray = Ray::new(points::new(0.0, 0.0, -10.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(first.intersect(&ray)).unwrap();
+    assert_eq!(hit.object.material, Material::default());
+
+    let ray = Ray::new(points::new(0.0, 10.0, -10.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(second.intersect(&ray)).unwrap();
+    assert_eq!(hit.object.material, red);
+}
+
+#[test]
+fn test_instance_bounds() {
+    let instance = Shape::instance(prototype());
+    let bounds = instance.bounds();
+    assert_eq!(bounds.min, points::new(-2.0, -2.0, 2.0));
+    assert_eq!(bounds.max, points::new(2.0, 2.0, 6.0));
+
+    let group = Shape::group(vec![instance.translate(10.0, 0.0, 0.0)]);
+    let bounds = group.bounds();
+    assert_eq!(bounds.min, points::new(8.0, -2.0, 2.0));
+    assert_eq!(bounds.max, points::new(12.0, 2.0, 6.0));
+}
@@ -0,0 +1,217 @@
+use crate::math;
+use crate::math::Real;
+use crate::matrix::CanTransform;
+use crate::parsers;
+use crate::rays::{Intersection, IntersectionKind, Ray};
+use crate::shapes::bounds::Bounds;
+use crate::shapes::meshes::{Mesh, MeshTriangle};
+use crate::shapes::subdivision::{PolyMesh, Subdivision};
+use crate::shapes::triangles::Triangle;
+use crate::shapes::Shape;
+use crate::tuples::{points, vectors};
+
+/// Two triangles making up the unit square of the xz plane, facing up
+fn square() -> Mesh {
+    Mesh::new(
+        vec![
+            points::new(0.0, 0.0, 0.0),
+            points::new(0.0, 0.0, 1.0),
+            points::new(1.0, 0.0, 1.0),
+            points::new(1.0, 0.0, 0.0),
+        ],
+        vec![],
+        vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)],
+        vec![
+            MeshTriangle::new([0, 1, 2]).uvs([0, 1, 2]),
+            MeshTriangle::new([0, 2, 3]).uvs([0, 2, 3]),
+        ],
+    )
+}
+
+fn sphere() -> PolyMesh {
+    let cube = PolyMesh::new(
+        (0..8)
+            .map(|i| {
+                let coordinate = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                points::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect(),
+        vec![
+            vec![0, 1, 3, 2],
+            vec![4, 6, 7, 5],
+            vec![0, 4, 5, 1],
+            vec![2, 3, 7, 6],
+            vec![0, 2, 6, 4],
+            vec![1, 5, 7, 3],
+        ],
+    );
+    cube.subdivide(Subdivision::CatmullClark, 3)
+}
+
+#[test]
+fn test_mesh_bounds() {
+    assert_eq!(
+        square().bounds(),
+        Bounds::new(points::new(0.0, 0.0, 0.0), points::new(1.0, 0.0, 1.0))
+    );
+    assert_eq!(Bounds::of(&Shape::mesh(square())), square().bounds());
+}
+
+#[test]
+fn test_hits_carry_the_triangle_index() {
+    let mesh = square().to_shape();
+    let ray = Ray::new(points::new(0.75, 1.0, 0.25), vectors::new(0.0, -1.0, 0.0));
+    let xs = mesh.intersect(&ray);
+    assert_eq!(xs.len(), 1);
+    assert_eq!(xs[0].t, 1.0);
+    match xs[0].get_kind() {
+        IntersectionKind::Triangle { u, v, index } => {
+            assert_eq!(index, 1);
+            assert_eq!((u, v), (0.25, 0.5));
+        }
+        kind => panic!("Unexpected kind {:?}", kind),
+    }
+
+    let miss = Ray::new(points::new(1.5, 1.0, 0.5), vectors::new(0.0, -1.0, 0.0));
+    assert!(mesh.intersect(&miss).is_empty());
+}
+
+#[test]
+fn test_empty_mesh() {
+    let mesh = Mesh::new(vec![], vec![], vec![], vec![]);
+    assert!(mesh.is_empty());
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(mesh.to_shape().intersect(&ray).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_indices_must_be_in_range() {
+    Mesh::new(
+        vec![points::new(0.0, 0.0, 0.0)],
+        vec![],
+        vec![],
+        vec![MeshTriangle::new([0, 1, 2])],
+    );
+}
+
+#[test]
+fn test_mesh_matches_a_group_of_triangles() {
+    let sphere = sphere();
+    let mesh = sphere.to_indexed().to_shape();
+    let group = sphere.to_group();
+    assert_eq!(sphere.to_indexed().len(), 6 * 16 * 4 * 2);
+
+    for i in 0..50 {
+        let angle = i as Real * 0.7;
+        let origin = points::new(angle.cos() * 4.0, (angle * 1.3).sin(), angle.sin() * 4.0);
+        let target = points::new((angle * 2.1).sin() * 0.5, (angle * 0.3).cos() * 0.5, 0.0);
+        let ray = Ray::new(origin, (target - origin).to_vector().normalize());
+
+        let xs = mesh.intersect(&ray);
+        let expected = group.intersect(&ray);
+        let ts =
+            |xs: &[Intersection<_>]| xs.iter().map(|x| math::round_to_5(x.t)).collect::<Vec<_>>();
+        assert_eq!(ts(&xs), ts(&expected));
+
+        let (hit, expected) = (
+            Intersection::hit(xs).unwrap(),
+            Intersection::hit(expected).unwrap(),
+        );
+        let point = ray.position(hit.t);
+        assert_eq!(
+            hit.object.normal_at(point, &hit),
+            expected.object.normal_at(point, &expected)
+        );
+    }
+}
+
+#[test]
+fn test_flat_and_smooth_normals() {
+    let flat = Mesh::new(
+        vec![
+            points::new(0.0, 1.0, 0.0),
+            points::new(-1.0, 0.0, 0.0),
+            points::new(1.0, 0.0, 0.0),
+        ],
+        vec![
+            vectors::new(0.0, 1.0, 0.0),
+            vectors::new(-1.0, 0.0, 0.0),
+            vectors::new(1.0, 0.0, 0.0),
+        ],
+        vec![],
+        vec![MeshTriangle::new([0, 1, 2])],
+    );
+    let ray = Ray::new(points::new(-0.2, 0.3, -2.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(flat.to_shape().intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    let expected = Triangle::regular(
+        points::new(0.0, 1.0, 0.0),
+        points::new(-1.0, 0.0, 0.0),
+        points::new(1.0, 0.0, 0.0),
+    )
+    .get_default_normal();
+    assert_eq!(flat.normal_at(point, &hit), expected);
+    assert_eq!(flat.triangle(0).get_default_normal(), expected);
+
+    let smooth = Mesh::new(
+        vec![
+            points::new(0.0, 1.0, 0.0),
+            points::new(-1.0, 0.0, 0.0),
+            points::new(1.0, 0.0, 0.0),
+        ],
+        vec![
+            vectors::new(0.0, 1.0, 0.0),
+            vectors::new(-1.0, 0.0, 0.0),
+            vectors::new(1.0, 0.0, 0.0),
+        ],
+        vec![],
+        vec![MeshTriangle::new([0, 1, 2]).normals([0, 1, 2])],
+    );
+    let shape = smooth.to_shape().translate(0.0, 0.0, 1.0);
+    let hit = Intersection::hit(shape.intersect(&ray)).unwrap();
+    let expected = smooth.triangle(0).get_normal(&hit);
+    assert_eq!(
+        shape.normal_at(ray.position(hit.t), &hit),
+        expected.normalize()
+    );
+}
+
+#[test]
+fn test_mesh_uvs() {
+    let mesh = square().to_shape();
+    assert_eq!(mesh.uv_at(points::new(0.25, 0.0, 0.75)), (0.25, 0.75));
+    assert_eq!(mesh.uv_at(points::new(0.75, 0.0, 0.25)), (0.75, 0.25));
+    assert_eq!(
+        square().triangle(1).uv_at(points::new(0.75, 0.0, 0.25)),
+        (0.75, 0.25)
+    );
+}
+
+#[test]
+fn test_obj_as_a_mesh() {
+    let file = b"
+        v 0 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 1 0
+        vt 0 0
+        vt 1 0
+        vt 1 1
+        vt 0 1
+        vn 0 0 -1
+        f 1/1/1 2/2/1 3/3/1 4/4/1" as &[u8];
+    let parser = parsers::parse_obj(file);
+    let mesh = parser.to_indexed_mesh();
+    assert_eq!(mesh.len(), 2);
+    assert_eq!(mesh.triangle(1).get_p2(), points::new(1.0, 1.0, 0.0));
+
+    let ray = Ray::new(points::new(0.2, 0.6, -1.0), vectors::new(0.0, 0.0, 1.0));
+    let shape = mesh.to_shape();
+    let hit = Intersection::hit(shape.intersect(&ray)).unwrap();
+    let point = ray.position(hit.t);
+    assert_eq!(hit.t, 1.0);
+    assert_eq!(shape.normal_at(point, &hit), vectors::new(0.0, 0.0, -1.0));
+    let (u, v) = shape.uv_at(point);
+    assert_eq!((math::round_to_5(u), math::round_to_5(v)), (0.2, 0.6));
+}
@@ -1,7 +1,14 @@
+mod blobs;
 mod bounds;
 mod cylinders;
 mod groups;
+mod heightfields;
+mod instances;
+mod meshes;
 mod planes;
+mod sdfs;
 mod spheres;
+mod subdivision;
 mod test;
+mod tori;
 mod triangles;
@@ -1,4 +1,10 @@
+use crate::math;
+use crate::math::Real;
+use crate::matrix::CanTransform;
 use crate::rays::Ray;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::planes::{Disk, Rect};
+use crate::shapes::uvs::UvMapping;
 use crate::shapes::{Geo, Shape};
 use crate::tuples::points::Point;
 use crate::tuples::{points, vectors};
@@ -58,3 +64,99 @@ fn test_intersect_from_below() {
     assert_eq!(xs[0].t, 1.0);
     assert_eq!(xs[0].object, plane);
 }
+
+fn ray_from_above(x: Real, z: Real) -> Ray {
+    Ray::new(points::new(x, 1.0, z), vectors::new(0.0, -1.0, 0.0))
+}
+
+#[test]
+fn test_intersect_disk() {
+    let disk = Shape::disk(2.0);
+    let data = [
+        (0.0, 0.0, true),
+        (1.5, -1.0, true),
+        (0.0, 2.0, true),
+        (1.5, 1.5, false),
+        (-3.0, 0.0, false),
+    ];
+    for (x, z, hit) in data {
+        let xs = disk.intersect(&ray_from_above(x, z));
+        assert_eq!(xs.len(), hit as usize);
+    }
+
+    // a ray parallel to the disk misses it
+    let ray = Ray::new(Point::origin(), vectors::new(1.0, 0.0, 0.0));
+    assert!(disk.intersect(&ray).is_empty());
+}
+
+#[test]
+fn test_intersect_annulus() {
+    let annulus = Shape::annulus(2.0, 1.0);
+    let data = [
+        (0.0, 0.0, false),
+        (0.5, 0.5, false),
+        (1.5, 0.0, true),
+        (0.0, -1.0, true),
+    ];
+    for (x, z, hit) in data {
+        let xs = annulus.intersect(&ray_from_above(x, z));
+        assert_eq!(xs.len(), hit as usize);
+    }
+}
+
+#[test]
+fn test_intersect_rect() {
+    let rect = Rect::new((-1.0, 0.0), (3.0, 2.0)).to_shape();
+    let data = [
+        (0.0, 1.0, true),
+        (3.0, 2.0, true),
+        (-1.0, 0.5, true),
+        (0.0, -0.5, false),
+        (3.5, 1.0, false),
+    ];
+    for (x, z, hit) in data {
+        let xs = rect.intersect(&ray_from_above(x, z));
+        assert_eq!(xs.len(), hit as usize);
+    }
+}
+
+/// A rectangle standing up like a picture frame on a wall
+#[test]
+fn test_transformed_rect() {
+    let frame = Shape::rect(2.0, 1.0).rotate_x(-math::PI / 2.0);
+    let ray = Ray::new(points::new(0.5, 0.25, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let xs = frame.intersect(&ray);
+    assert_eq!(xs.len(), 1);
+    assert_eq!(xs[0].t, 5.0);
+    assert_eq!(
+        frame.default_normal_at(points::new(0.5, 0.25, 0.0)),
+        vectors::new(0.0, 0.0, -1.0)
+    );
+
+    let ray = Ray::new(points::new(0.5, 0.75, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(frame.intersect(&ray).is_empty());
+}
+
+#[test]
+fn test_flat_shapes_bounds() {
+    assert_eq!(
+        Shape::annulus(2.0, 1.0).bounds(),
+        Bounds::new(points::new(-2.0, 0.0, -2.0), points::new(2.0, 0.0, 2.0))
+    );
+    assert_eq!(
+        Rect::new((-1.0, 0.0), (3.0, 2.0)).to_shape().bounds(),
+        Bounds::new(points::new(-1.0, 0.0, 0.0), points::new(3.0, 0.0, 2.0))
+    );
+}
+
+#[test]
+fn test_flat_shapes_uvs() {
+    let disk = Disk::new(2.0).to_geo();
+    assert_eq!(disk.uv_at(Point::origin()), (0.5, 0.5));
+    assert_eq!(disk.uv_at(points::new(-2.0, 0.0, 1.0)), (0.0, 0.75));
+
+    let rect = Rect::new((-1.0, 0.0), (3.0, 2.0)).to_geo();
+    assert_eq!(rect.uv_at(points::new(-1.0, 0.0, 0.0)), (0.0, 0.0));
+    assert_eq!(rect.uv_at(points::new(0.0, 0.0, 1.5)), (0.25, 0.75));
+    assert_eq!(rect.uv_at(points::new(3.0, 0.0, 2.0)), (1.0, 1.0));
+}
@@ -0,0 +1,187 @@
+use crate::math;
+use crate::math::Real;
+use crate::matrix::CanTransform;
+use crate::rays::Ray;
+use crate::scene::World;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::sdfs::{DistanceField, Sdf};
+use crate::shapes::Shape;
+use crate::tuples::points::Point;
+use crate::tuples::{points, vectors};
+
+fn ts(shape: &Shape, ray: &Ray) -> Vec<Real> {
+    shape
+        .intersect(ray)
+        .iter()
+        .map(|x| math::round_to_5(x.t))
+        .collect()
+}
+
+#[test]
+fn test_primitive_distances() {
+    let sphere = Sdf::sphere(2.0);
+    assert_eq!(sphere.distance(Point::origin()), -2.0);
+    assert_eq!(sphere.distance(points::new(0.0, 3.0, 0.0)), 1.0);
+
+    let cuboid = Sdf::cuboid(1.0, 2.0, 3.0);
+    assert_eq!(cuboid.distance(Point::origin()), -1.0);
+    assert_eq!(cuboid.distance(points::new(0.0, 0.0, 5.0)), 2.0);
+    assert_eq!(cuboid.distance(points::new(4.0, 6.0, 0.0)), 5.0);
+
+    let torus = Sdf::torus(2.0, 0.5);
+    assert_eq!(torus.distance(points::new(2.0, 0.0, 0.0)), -0.5);
+    assert_eq!(torus.distance(points::new(0.0, 1.0, -2.0)), 0.5);
+}
+
+#[test]
+fn test_combined_distances() {
+    let a = || Sdf::sphere(1.0).translate(-1.0, 0.0, 0.0);
+    let b = || Sdf::sphere(1.0).translate(1.0, 0.0, 0.0);
+    let point = points::new(0.0, 0.5, 0.0);
+    let separate = a().distance(point);
+
+    assert_eq!(a().union(b()).distance(point), separate);
+    assert_eq!(a().intersection(b()).distance(point), separate);
+    assert_eq!(
+        a().difference(b()).distance(points::new(0.5, 0.0, 0.0)),
+        0.5
+    );
+
+    // blending pulls the surface out between the spheres
+    assert!(a().smooth_union(b(), 0.5).distance(point) < separate);
+
+    assert_eq!(
+        Sdf::cuboid(1.0, 1.0, 1.0)
+            .round(0.5)
+            .distance(points::new(0.0, 2.0, 0.0)),
+        0.5
+    );
+}
+
+#[test]
+fn test_twist_and_repeat_distances() {
+    // a quarter turn at y = 1 makes its x extent the z extent
+    let twisted = Sdf::cuboid(2.0, 2.0, 0.5).twist(math::PI / 2.0);
+    assert!(twisted.distance(points::new(0.0, 1.0, 1.5)) < 0.0);
+    assert!(twisted.distance(points::new(1.5, 1.0, 0.0)) > 0.0);
+    assert!(twisted.distance(points::new(1.5, 0.0, 0.0)) < 0.0);
+
+    let repeated = Sdf::sphere(1.0).repeat(4.0, 0.0, 0.0);
+    assert_eq!(repeated.distance(points::new(8.0, 0.0, 0.0)), -1.0);
+    assert_eq!(repeated.distance(points::new(-6.0, 0.0, 0.0)), 1.0);
+    assert_eq!(repeated.distance(points::new(0.0, 4.0, 0.0)), 3.0);
+}
+
+#[test]
+fn test_sdf_bounds() {
+    assert_eq!(
+        Sdf::sphere(1.0)
+            .translate(2.0, 0.0, 0.0)
+            .union(Sdf::cuboid(1.0, 2.0, 1.0))
+            .bounds(),
+        Bounds::new(points::new(-1.0, -2.0, -1.0), points::new(3.0, 2.0, 1.0))
+    );
+    assert_eq!(
+        Sdf::cuboid(1.0, 1.0, 1.0).round(0.5).bounds(),
+        Bounds::new(points::new(-1.5, -1.5, -1.5), points::new(1.5, 1.5, 1.5))
+    );
+
+    let repeated = Sdf::sphere(1.0).repeat(4.0, 0.0, 0.0).bounds();
+    assert_eq!(repeated.min.x, -Real::INFINITY);
+    assert_eq!(repeated.max.y, 1.0);
+}
+
+/// Sphere tracing a sphere finds the same points as the analytic sphere
+#[test]
+fn test_tracing_a_sphere() {
+    let field = Shape::sdf(Sdf::sphere(1.0));
+    let sphere = Shape::sphere();
+    let data = [
+        (points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0)),
+        (points::new(0.0, 0.5, -5.0), vectors::new(0.0, 0.0, 1.0)),
+        (Point::origin(), vectors::new(0.0, 0.0, 1.0)),
+        (points::new(0.0, 0.0, 5.0), vectors::new(0.0, 0.0, 1.0)),
+        (points::new(0.0, 2.0, -5.0), vectors::new(0.0, 0.0, 1.0)),
+    ];
+    for (origin, direction) in data {
+        let ray = Ray::new(origin, direction);
+        assert_eq!(ts(&field, &ray), ts(&sphere, &ray));
+    }
+}
+
+#[test]
+fn test_tracing_a_transformed_field() {
+    let field = Shape::sdf(Sdf::cuboid(1.0, 1.0, 1.0))
+        .scale(2.0, 2.0, 2.0)
+        .translate(0.0, 0.0, 5.0);
+    let ray = Ray::new(Point::origin(), vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(ts(&field, &ray), vec![3.0, 7.0]);
+}
+
+#[test]
+fn test_tracing_a_repetition() {
+    let field = Shape::sdf(Sdf::sphere(1.0).repeat(4.0, 0.0, 0.0));
+    let ray = Ray::new(points::new(-2.0, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&field, &ray)[..4], [1.0, 3.0, 5.0, 7.0]);
+}
+
+#[test]
+fn test_tracing_step_limit() {
+    let field = DistanceField::new(Sdf::sphere(1.0)).max_steps(1).to_shape();
+
+    // the first step lands on the surface, but there are no more to find the exit
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert_eq!(ts(&field, &ray), vec![4.0]);
+
+    // the grazing ray needs more than one step
+    let ray = Ray::new(points::new(0.0, 0.5, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(field.intersect(&ray).is_empty());
+}
+
+#[test]
+fn test_tracing_precision_and_distance() {
+    // a looser epsilon stops the tracing short of the surface
+    let field = DistanceField::new(Sdf::sphere(1.0)).epsilon(0.1).to_shape();
+    let ray = Ray::new(points::new(0.0, 0.5, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let exact = 5.0 - 0.75_f64.sqrt();
+    let t = field.intersect(&ray)[0].t;
+    assert!(t < exact - 1e-3 && t > exact - 0.2);
+
+    // unbounded fields are only traced up to the maximum distance
+    let field = DistanceField::new(Sdf::sphere(1.0).repeat(4.0, 0.0, 0.0))
+        .max_distance(6.0)
+        .to_shape();
+    let ray = Ray::new(points::new(-2.0, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    assert_eq!(ts(&field, &ray), vec![1.0, 3.0, 5.0]);
+}
+
+#[test]
+fn test_estimated_normals() {
+    let field = Shape::sdf(Sdf::sphere(1.0)).translate(0.0, 1.0, 0.0);
+    let sphere = Shape::sphere().translate(0.0, 1.0, 0.0);
+    let value = math::two_sqrt_div_2();
+    let data = [
+        points::new(0.0, 2.0, 0.0),
+        points::new(1.0, 1.0, 0.0),
+        points::new(0.0, 1.0 + value, -value),
+    ];
+    for point in data {
+        assert_eq!(
+            field.default_normal_at(point),
+            sphere.default_normal_at(point)
+        );
+    }
+}
+
+#[test]
+fn test_fields_in_a_world() {
+    let mut world = World::default();
+    let blob = Sdf::sphere(1.0)
+        .smooth_union(Sdf::sphere(1.0).translate(1.5, 0.0, 0.0), 0.5)
+        .translate(0.0, 20.0, 0.0);
+    world.add_object(&Shape::sdf(blob));
+
+    let light_above = points::new(0.75, 30.0, 0.0);
+    assert!(world.is_shadowed(light_above, points::new(0.75, 10.0, 0.0)));
+    assert!(!world.is_shadowed(light_above, points::new(5.0, 10.0, 0.0)));
+}
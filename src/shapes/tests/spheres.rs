@@ -1,7 +1,7 @@
 use std::f64::consts::{FRAC_1_SQRT_2, PI};
 
 use crate::materials::Material;
-use crate::matrix::{rotation_z, scaling, translation, CanTransform, Matrix};
+use crate::matrix::{rotation_z, scaling, translation, CanTransform, Matrix44};
 use crate::rays::Ray;
 use crate::shapes::{spheres, Shape};
 use crate::tuples::points::Point;
@@ -69,7 +69,7 @@ fn test_object_of_intersection() {
 #[test]
 fn test_default_transformation() {
     let sphere = Shape::sphere();
-    assert_eq!(sphere.transformation, Matrix::id44());
+    assert_eq!(sphere.transformation, Matrix44::id());
 }
 
 #[test]
@@ -160,7 +160,7 @@ fn test_material_update() {
 #[test]
 fn test_glass_sphere() {
     let s = spheres::glass();
-    assert_eq!(s.transformation, Matrix::id44());
+    assert_eq!(s.transformation, Matrix44::id());
     assert_eq!(s.material.transparency, 1.0);
     assert_eq!(s.material.refractive_index, 1.5);
 }
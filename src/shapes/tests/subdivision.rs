@@ -0,0 +1,150 @@
+use crate::parsers;
+use crate::rays::{Intersection, Ray};
+use crate::shapes::subdivision::{PolyMesh, Subdivision};
+use crate::tuples::points::Point;
+use crate::tuples::{points, vectors};
+
+/// A cube with its faces wound like `Triangle::regular` expects,
+/// so that the normals point outwards
+fn cube() -> PolyMesh {
+    let vertices = (0..8)
+        .map(|i| {
+            let coordinate = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            points::new(coordinate(1), coordinate(2), coordinate(4))
+        })
+        .collect();
+    let faces = vec![
+        vec![0, 1, 3, 2],
+        vec![4, 6, 7, 5],
+        vec![0, 4, 5, 1],
+        vec![2, 3, 7, 6],
+        vec![0, 2, 6, 4],
+        vec![1, 5, 7, 3],
+    ];
+    PolyMesh::new(vertices, faces)
+}
+
+fn tetrahedron() -> PolyMesh {
+    PolyMesh::new(
+        vec![
+            points::new(1.0, 1.0, 1.0),
+            points::new(1.0, -1.0, -1.0),
+            points::new(-1.0, 1.0, -1.0),
+            points::new(-1.0, -1.0, 1.0),
+        ],
+        vec![vec![0, 1, 2], vec![0, 3, 1], vec![0, 2, 3], vec![1, 3, 2]],
+    )
+}
+
+#[test]
+fn test_catmull_clark_counts() {
+    let mesh = cube().subdivide(Subdivision::CatmullClark, 1);
+    assert_eq!(mesh.vertices.len(), 8 + 6 + 12);
+    assert_eq!(mesh.faces.len(), 24);
+    assert!(mesh.faces.iter().all(|face| face.len() == 4));
+
+    let mesh = cube().subdivide(Subdivision::CatmullClark, 2);
+    assert_eq!(mesh.faces.len(), 96);
+}
+
+#[test]
+fn test_catmull_clark_points() {
+    let mesh = cube().subdivide(Subdivision::CatmullClark, 1);
+
+    // the face points stay at the centers of the faces, and the edge
+    // and corner points are pulled inwards
+    assert!(mesh.vertices.contains(&points::new(0.0, 0.0, -1.0)));
+    assert!(mesh.vertices.contains(&points::new(0.75, 0.75, 0.0)));
+    let corner = 5.0 / 9.0;
+    assert_eq!(mesh.vertices[7], points::new(corner, corner, corner));
+}
+
+#[test]
+fn test_loop_subdivision() {
+    let mesh = tetrahedron().subdivide(Subdivision::Loop, 1);
+    assert_eq!(mesh.vertices.len(), 4 + 6);
+    assert_eq!(mesh.faces.len(), 16);
+
+    // the corners shrink towards the center, and the edge points too
+    assert_eq!(mesh.vertices[0], points::new(0.25, 0.25, 0.25));
+    assert!(mesh.vertices.contains(&points::new(0.5, 0.0, 0.0)));
+
+    // quads are split into triangles first
+    assert_eq!(cube().subdivide(Subdivision::Loop, 1).faces.len(), 48);
+}
+
+#[test]
+fn test_open_boundaries_are_kept() {
+    let quad = PolyMesh::new(
+        vec![
+            points::new(0.0, 0.0, 0.0),
+            points::new(2.0, 0.0, 0.0),
+            points::new(2.0, 0.0, 2.0),
+            points::new(0.0, 0.0, 2.0),
+        ],
+        vec![vec![0, 1, 2, 3]],
+    );
+    let mesh = quad.subdivide(Subdivision::CatmullClark, 1);
+    assert_eq!(mesh.vertices[0], points::new(0.25, 0.0, 0.25));
+    assert!(mesh.vertices.contains(&points::new(1.0, 0.0, 0.0)));
+    assert!(mesh.vertices.iter().all(|p| p.y == 0.0));
+}
+
+#[test]
+fn test_smooth_normals() {
+    let mesh = cube().subdivide(Subdivision::CatmullClark, 2);
+    for (vertex, normal) in mesh.vertices.iter().zip(mesh.vertex_normals()) {
+        assert!(normal.dot(vertex.to_vector()) > 0.0);
+    }
+
+    let group = mesh.to_group();
+    let ray = Ray::new(points::new(0.1, 0.2, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(group.intersect(&ray)).unwrap();
+    let normal = hit.object.normal_at(ray.position(hit.t), &hit);
+    assert!(normal.z < -0.9);
+}
+
+#[test]
+fn test_subdividing_an_obj() {
+    let file = b"
+        v -1 0 -1
+        v 1 0 -1
+        v 1 0 1
+        v -1 0 1
+        v 0 1 0
+        f 1 2 5
+        f 2 3 5
+        f 3 4 5
+        f 4 1 5" as &[u8];
+    let mesh = parsers::parse_obj(file).to_mesh();
+    assert_eq!(mesh.vertices[4], points::new(0.0, 1.0, 0.0));
+    assert_eq!(mesh.faces[1], vec![1, 2, 4]);
+
+    let smooth = mesh.subdivide(Subdivision::Loop, 2);
+    assert_eq!(smooth.faces.len(), 4 * 16);
+    assert!(smooth.vertices.iter().all(|p| p.y >= 0.0));
+    assert!(!smooth.vertices.contains(&Point::origin()));
+}
+
+#[test]
+fn test_degenerate_faces_are_left_out() {
+    let file = b"
+        v 0 0 0
+        v 1 0 0
+        v 0 1 0
+        v 0 0 1
+        f 1 2 3
+        f 1 3 4
+        f 1 4 2
+        f 2 4 3
+        f 1 2 1
+        f 3 3 3 3" as &[u8];
+    let mesh = parsers::parse_obj(file).to_mesh();
+    assert_eq!(mesh.faces.len(), 4);
+    assert_eq!(mesh.subdivide(Subdivision::Loop, 1).faces.len(), 16);
+
+    let mesh = PolyMesh::new(mesh.vertices, vec![vec![], vec![0, 1, 2]]);
+    assert_eq!(mesh.faces, vec![vec![0, 1, 2]]);
+    assert_eq!(mesh.subdivide(Subdivision::Loop, 1).faces.len(), 4);
+    assert_eq!(mesh.subdivide(Subdivision::CatmullClark, 1).faces.len(), 3);
+}
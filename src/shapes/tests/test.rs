@@ -1,5 +1,5 @@
 use crate::materials::Material;
-use crate::matrix::{translation, CanTransform, Matrix};
+use crate::matrix::{scaling, translation, CanTransform, Matrix44, SingularMatrix};
 use crate::rays::Ray;
 use crate::shapes::{Geo, Shape};
 use crate::tuples::{points, vectors};
@@ -9,7 +9,7 @@ use std::f64::consts::FRAC_1_SQRT_2;
 #[test]
 fn test_default_transformation() {
     let shape = Shape::test();
-    assert_eq!(shape.transformation, Matrix::id44());
+    assert_eq!(shape.transformation, Matrix44::id());
 }
 
 #[test]
@@ -18,6 +18,21 @@ fn test_set_transformation() {
     assert_eq!(shape.transformation, translation(2.0, 3.0, 4.0));
 }
 
+#[test]
+fn test_singular_transformation() {
+    let result = Shape::test().try_transform(scaling(1.0, 0.0, 1.0));
+    assert_eq!(
+        result.err(),
+        Some(SingularMatrix(Box::new(scaling(1.0, 0.0, 1.0))))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Singular transformation")]
+fn test_singular_transformation_in_builder() {
+    Shape::test().scale(2.0, 0.0, 0.0);
+}
+
 #[test]
 fn test_default_material() {
     let shape = Shape::test();
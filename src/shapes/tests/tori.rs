@@ -0,0 +1,133 @@
+use crate::math;
+use crate::matrix::CanTransform;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::scene::World;
+use crate::shapes::bounds::Bounds;
+use crate::shapes::groups::GroupBuilder;
+use crate::shapes::tori::Torus;
+use crate::shapes::uvs::UvMapping;
+use crate::shapes::Shape;
+use crate::tuples::{points, vectors};
+
+fn assert_ts(xs: Vec<Intersection3D>, expected: &[math::Real]) {
+    let ts: Vec<_> = xs.iter().map(|x| math::round_to_5(x.t)).collect();
+    assert_eq!(ts, expected);
+}
+
+#[test]
+fn test_ray_passes_through_the_tube() {
+    let torus = Shape::torus(1.0, 0.25);
+    let ray = Ray::new(points::new(-5.0, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    assert_ts(torus.intersect(&ray), &[3.75, 4.25, 5.75, 6.25]);
+
+    // the ray origin is inside the tube
+    let ray = Ray::new(points::new(-1.0, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    assert_ts(torus.intersect(&ray), &[-0.25, 0.25, 1.75, 2.25]);
+}
+
+#[test]
+fn test_ray_misses_the_torus() {
+    let torus = Shape::torus(1.0, 0.25);
+    let data = [
+        // through the hole
+        (points::new(0.0, 5.0, 0.0), vectors::new(0.0, -1.0, 0.0)),
+        // above the torus
+        (points::new(-5.0, 0.5, 0.0), vectors::new(1.0, 0.0, 0.0)),
+        // misses the bounding box
+        (points::new(5.0, 5.0, 5.0), vectors::new(1.0, 0.0, 0.0)),
+    ];
+    for (origin, direction) in data {
+        assert!(torus.intersect(&Ray::new(origin, direction)).is_empty());
+    }
+}
+
+#[test]
+fn test_ray_hits_the_tube_from_above() {
+    let torus = Shape::torus(1.0, 0.25);
+    let ray = Ray::new(points::new(1.0, 5.0, 0.0), vectors::new(0.0, -1.0, 0.0));
+    assert_ts(torus.intersect(&ray), &[4.75, 5.25]);
+}
+
+/// Far away rays keep their precision, since the quartic is solved
+/// from the bounding box
+#[test]
+fn test_distant_ray() {
+    let torus = Shape::torus(1.0, 0.25);
+    let ray = Ray::new(points::new(-1e5, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0));
+    let xs = torus.intersect(&ray);
+    assert_eq!(xs.len(), 4);
+    assert!((xs[0].t - (1e5 - 1.25)).abs() < 1e-6);
+}
+
+#[test]
+fn test_torus_normals() {
+    let torus = Shape::torus(1.0, 0.25);
+    let data = [
+        (points::new(1.25, 0.0, 0.0), vectors::new(1.0, 0.0, 0.0)),
+        (points::new(0.75, 0.0, 0.0), vectors::new(-1.0, 0.0, 0.0)),
+        (points::new(0.0, 0.25, -1.0), vectors::new(0.0, 1.0, 0.0)),
+        (points::new(0.0, -0.25, 1.0), vectors::new(0.0, -1.0, 0.0)),
+        (points::new(0.0, 0.0, 0.75), vectors::new(0.0, 0.0, -1.0)),
+    ];
+    for (point, normal) in data {
+        assert_eq!(torus.default_normal_at(point), normal);
+    }
+}
+
+#[test]
+fn test_transformed_torus_normal() {
+    let torus = Shape::torus(1.0, 0.25).rotate_x(math::PI / 2.0);
+    let normal = torus.default_normal_at(points::new(0.0, 1.25, 0.0));
+    assert_eq!(normal, vectors::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_torus_bounds() {
+    assert_eq!(
+        Shape::torus(2.0, 0.5).bounds(),
+        Bounds::new(points::new(-2.5, -0.5, -2.5), points::new(2.5, 0.5, 2.5))
+    );
+}
+
+#[test]
+fn test_torus_uvs() {
+    let torus = Torus::new(1.0, 0.25);
+    let data = [
+        (points::new(0.0, 0.0, -1.25), (0.0, 0.5)),
+        (points::new(1.25, 0.0, 0.0), (0.25, 0.5)),
+        (points::new(0.0, 0.25, -1.0), (0.0, 0.25)),
+        (points::new(0.0, -0.25, -1.0), (0.0, 0.75)),
+    ];
+    for (point, expected) in data {
+        assert_eq!(torus.to_geo().uv_at(point), expected);
+    }
+}
+
+#[test]
+fn test_torus_in_a_group() {
+    let group = GroupBuilder::new()
+        .child(Shape::torus(1.0, 0.25).rotate_x(math::PI / 2.0))
+        .translate(0.0, 0.0, 5.0)
+        .build();
+
+    let ray = Ray::new(points::new(0.0, 0.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    assert!(group.intersect(&ray).is_empty());
+
+    let ray = Ray::new(points::new(0.0, 1.0, -5.0), vectors::new(0.0, 0.0, 1.0));
+    let hit = Intersection::hit(group.intersect(&ray)).unwrap();
+    assert_eq!(math::round_to_5(hit.t), 9.75);
+
+    let normal = hit.object.normal_at(ray.position(hit.t), &hit);
+    assert_eq!(normal, vectors::new(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn test_torus_casts_shadows() {
+    let mut world = World::default();
+    world.add_object(&Shape::torus(1.0, 0.25).translate(0.0, 20.0, 0.0));
+
+    // the original spheres don't cover this point
+    let light_above = points::new(1.0, 30.0, 0.0);
+    assert!(world.is_shadowed(light_above, points::new(1.0, 10.0, 0.0)));
+    assert!(!world.is_shadowed(light_above, points::new(0.0, 10.0, 0.0)));
+}
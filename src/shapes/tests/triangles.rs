@@ -149,7 +149,7 @@ fn test_intersection_stores_uv() {
     let xs = tri.intersect(&ray);
 
     for intersection in xs.iter() {
-        if let IntersectionKind::Triangle { u, v } = intersection.get_kind() {
+        if let IntersectionKind::Triangle { u, v, .. } = intersection.get_kind() {
             assert_eq!(math::round(u, 2), 0.45);
             assert_eq!(math::round(v, 2), 0.25);
         } else {
@@ -0,0 +1,107 @@
+use crate::math;
+use crate::math::polynomials;
+use crate::math::Real;
+use crate::rays::{Intersection, Intersection3D, Ray};
+use crate::shapes::bounds::Bounds;
+use crate::shapes::uvs::Uv;
+use crate::shapes::{cubes, Geo, Shape};
+use crate::tuples::points::Point;
+use crate::tuples::vectors::Vector;
+use crate::tuples::{points, vectors};
+use std::rc::Rc;
+
+/// A torus centered at the origin, lying on the xz plane
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+pub struct Torus {
+    /// Distance from the center of the torus to the center of the tube
+    pub major_radius: Real,
+
+    /// Radius of the tube
+    pub minor_radius: Real,
+}
+
+impl Torus {
+    pub fn new(major_radius: Real, minor_radius: Real) -> Torus {
+        Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn to_geo(self) -> Geo {
+        Geo::Torus(self)
+    }
+
+    pub fn to_shape(self) -> Shape {
+        Shape::new(self.to_geo())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let outer = self.major_radius + self.minor_radius;
+        Bounds::new(
+            points::new(-outer, -self.minor_radius, -outer),
+            points::new(outer, self.minor_radius, outer),
+        )
+    }
+
+    /// Substitutes the ray into the implicit equation of the torus,
+    /// `(|p|^2 - R^2 - r^2)^2 + 4R^2 (y^2 - r^2) = 0`, and solves the
+    /// resulting quartic.
+    pub fn intersect(&self, torus: &Shape, ray: &Ray) -> Vec<Intersection3D> {
+        // cheap rejection for rays that miss the bounding box
+        let t_start = match cubes::intersect(torus, ray).first() {
+            Some(hit) => hit.t,
+            None => return vec![],
+        };
+
+        // the quartic loses precision when the origin is far from the torus,
+        // so the ray is started at the bounding box instead
+        let Ray { origin, direction } = ray;
+        let origin = (*origin + *direction * t_start).to_vector();
+        let sq_major = self.major_radius * self.major_radius;
+        let sq_minor = self.minor_radius * self.minor_radius;
+
+        let dd = direction.dot(*direction);
+        let od = origin.dot(*direction);
+        let k = origin.dot(origin) - sq_major - sq_minor;
+
+        let coefficients = [
+            k * k + 4.0 * sq_major * (origin.y * origin.y - sq_minor),
+            4.0 * od * k + 8.0 * sq_major * origin.y * direction.y,
+            4.0 * od * od + 2.0 * dd * k + 4.0 * sq_major * direction.y * direction.y,
+            4.0 * dd * od,
+            dd * dd,
+        ];
+
+        let mut ts = polynomials::solve_quartic(coefficients);
+        ts.sort_by(|a, b| math::order_reals(*a, *b));
+
+        let torus = Rc::new(torus.clone());
+        ts.into_iter()
+            .map(|t| Intersection::new(t + t_start, Rc::clone(&torus)))
+            .collect()
+    }
+
+    /// The normal points away from the nearest point on the tube's center circle
+    pub fn normal_at(&self, point: Point) -> Vector {
+        let distance = (point.x * point.x + point.z * point.z).sqrt();
+        if distance == 0.0 {
+            return vectors::new(0.0, point.y.signum(), 0.0);
+        }
+        let scale = self.major_radius / distance;
+        vectors::new(
+            point.x - point.x * scale,
+            point.y,
+            point.z - point.z * scale,
+        )
+    }
+
+    /// Wraps u around the y axis, and v around the tube
+    pub fn uv_at(&self, point: Point) -> Uv {
+        let theta = point.x.atan2(point.z);
+        let distance = (point.x * point.x + point.z * point.z).sqrt();
+        let phi = point.y.atan2(distance - self.major_radius);
+        let to_unit = |angle: Real| 1.0 - (angle / (2.0 * math::PI) + 0.5);
+        (to_unit(theta), to_unit(phi))
+    }
+}
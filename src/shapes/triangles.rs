@@ -1,6 +1,8 @@
 use crate::math;
+use crate::math::Real;
 use crate::rays::{Intersection, Intersection3D, IntersectionKind, Ray};
 use crate::shapes::bounds::Bounds;
+use crate::shapes::uvs::Uv;
 use crate::shapes::Shape;
 use crate::tuples::points::Point;
 use crate::tuples::vectors::Vector;
@@ -14,6 +16,7 @@ pub struct Triangle {
     edge1: Vector,
     edge2: Vector,
     pub kind: TriangleKind,
+    uvs: Option<[Uv; 3]>,
 }
 
 impl Triangle {
@@ -30,6 +33,7 @@ impl Triangle {
             edge1,
             edge2,
             kind: kind_f(edge1, edge2),
+            uvs: None,
         }
     }
 
@@ -43,6 +47,39 @@ impl Triangle {
         Triangle::new(p1, p2, p3, |_, _| TriangleKind::smooth(n1, n2, n3))
     }
 
+    /// Assigns texture coordinates to the three corners
+    pub fn with_uvs(mut self, uvs: [Uv; 3]) -> Triangle {
+        self.uvs = Some(uvs);
+        self
+    }
+
+    pub fn get_uvs(&self) -> Option<[Uv; 3]> {
+        self.uvs
+    }
+
+    /// Computes the barycentric coordinates of a point on the triangle.
+    /// The result is the weight of `p2` and the weight of `p3`, which
+    /// matches the `u` and `v` values found by `intersect`.
+    pub fn barycentric(&self, point: Point) -> (Real, Real) {
+        barycentric(self.p1, self.edge1, self.edge2, point)
+    }
+
+    /// Interpolates the texture coordinates of the corners. Triangles
+    /// without texture coordinates use the barycentric coordinates instead.
+    pub fn uv_at(&self, point: Point) -> Uv {
+        let (u, v) = self.barycentric(point);
+        match self.uvs {
+            None => (u, v),
+            Some([t1, t2, t3]) => {
+                let w = 1.0 - u - v;
+                (
+                    t1.0 * w + t2.0 * u + t3.0 * v,
+                    t1.1 * w + t2.1 * u + t3.1 * v,
+                )
+            }
+        }
+    }
+
     pub fn get_edge1(&self) -> Vector {
         self.edge1
     }
@@ -55,7 +92,7 @@ impl Triangle {
         match self.kind {
             TriangleKind::Regular { normal } => normal,
             TriangleKind::Smooth(Smooth { n1, n2, n3 }) => {
-                if let IntersectionKind::Triangle { u, v } = hit.get_kind() {
+                if let IntersectionKind::Triangle { u, v, .. } = hit.get_kind() {
                     n2 * u + n3 * v + n1 * (1.0 - u - v)
                 } else {
                     n1 // actually, could have `panic!` here as well
@@ -83,32 +120,11 @@ impl Triangle {
     /// Computes the ray-triangle intersection based on the Moller-Trumbore
     /// algorithm
     pub fn intersect(&self, shape: &Shape, ray: &Ray) -> Vec<Intersection3D> {
-        let dir_cross_e2 = ray.direction.cross(self.get_edge2());
-        let determinant = self.get_edge1().dot(dir_cross_e2);
-
-        if determinant.abs() < math::EPSILON {
-            return vec![];
-        }
-
-        let f = 1.0 / determinant;
-        let p1_to_origin = ray.origin - self.p1;
-        let u = f * p1_to_origin.dot(dir_cross_e2);
-
-        if u < 0.0 || u > 1.0 {
-            return vec![]; // the ray misses
-        }
-
-        let origin_cross_e1 = p1_to_origin.to_vector().cross(self.edge1);
-        let v = f * ray.direction.dot(origin_cross_e1);
-
-        if v < 0.0 || (u + v) > 1.0 {
-            return vec![];
+        match moller_trumbore(ray, self.p1, self.edge1, self.edge2) {
+            // u and v are needed for smooth triangles
+            Some((t, u, v)) => vec![Intersection::new_with_uv(t, Rc::new(shape.clone()), u, v)],
+            None => vec![],
         }
-
-        let t = f * self.edge2.dot(origin_cross_e1);
-
-        // u and v are needed for smooth triangles
-        vec![Intersection::new_with_uv(t, Rc::new(shape.clone()), u, v)]
     }
 
     pub fn bounds(&self) -> Bounds {
@@ -117,6 +133,52 @@ impl Triangle {
     }
 }
 
+/// Finds where the ray crosses the triangle with the Moller-Trumbore
+/// algorithm. Returns `t`, and the weights `u` of `p2` and `v` of `p3`.
+pub(crate) fn moller_trumbore(
+    ray: &Ray,
+    p1: Point,
+    edge1: Vector,
+    edge2: Vector,
+) -> Option<(Real, Real, Real)> {
+    let dir_cross_e2 = ray.direction.cross(edge2);
+    let determinant = edge1.dot(dir_cross_e2);
+    if determinant.abs() < math::EPSILON {
+        return None; // the ray is parallel to the triangle
+    }
+
+    let f = 1.0 / determinant;
+    let p1_to_origin = (ray.origin - p1).to_vector();
+    let u = f * p1_to_origin.dot(dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None; // the ray misses
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(edge1);
+    let v = f * ray.direction.dot(origin_cross_e1);
+    if v < 0.0 || (u + v) > 1.0 {
+        return None;
+    }
+
+    Some((f * edge2.dot(origin_cross_e1), u, v))
+}
+
+/// The barycentric coordinates of a point on the triangle: the weight
+/// of `p1 + edge1` and the weight of `p1 + edge2`
+pub(crate) fn barycentric(p1: Point, edge1: Vector, edge2: Vector, point: Point) -> (Real, Real) {
+    let to_point = (point - p1).to_vector();
+    let d00 = edge1.dot(edge1);
+    let d01 = edge1.dot(edge2);
+    let d11 = edge2.dot(edge2);
+    let d20 = to_point.dot(edge1);
+    let d21 = to_point.dot(edge2);
+    let denominator = d00 * d11 - d01 * d01;
+    (
+        (d11 * d20 - d01 * d21) / denominator,
+        (d00 * d21 - d01 * d20) / denominator,
+    )
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TriangleKind {
     Regular { normal: Vector },
@@ -0,0 +1,116 @@
+use crate::math;
+use crate::math::Real;
+use crate::shapes::Geo;
+use crate::tuples::points::Point;
+
+/// Texture coordinates, with both components usually in the range 0..1
+pub type Uv = (Real, Real);
+
+pub trait UvMapping {
+    /// Maps a point in object space to texture coordinates
+    fn uv_at(&self, local_point: Point) -> Uv;
+}
+
+impl UvMapping for Geo {
+    fn uv_at(&self, local_point: Point) -> Uv {
+        match self {
+            Geo::Sphere => spherical(local_point),
+            Geo::Cube => cubic(local_point),
+            Geo::Cylinder(_) => cylindrical(local_point),
+            Geo::Triangle(tri) => tri.uv_at(local_point),
+            Geo::Torus(torus) => torus.uv_at(local_point),
+            Geo::Disk(disk) => disk.uv_at(local_point),
+            Geo::Rect(rect) => rect.uv_at(local_point),
+            Geo::HeightField(field) => field.uv_at(local_point),
+            Geo::Mesh(mesh) => mesh.uv_at(local_point),
+            Geo::DistanceField(_) | Geo::Blob(_) => spherical(local_point),
+            Geo::Plane | Geo::Group(_) | Geo::Instance(_) | Geo::TestShape => planar(local_point),
+        }
+    }
+}
+
+/// Maps the unit sphere by longitude (u) and latitude (v)
+pub fn spherical(point: Point) -> Uv {
+    let theta = point.x.atan2(point.z);
+    let radius = point.to_vector().magnitude();
+    let phi = (point.y / radius).acos();
+    let raw_u = theta / (2.0 * math::PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / math::PI)
+}
+
+/// Tiles the xz plane with unit squares
+pub fn planar(point: Point) -> Uv {
+    (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0))
+}
+
+/// Wraps around the y axis, repeating every unit of height
+pub fn cylindrical(point: Point) -> Uv {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2.0 * math::PI);
+    (1.0 - (raw_u + 0.5), point.y.rem_euclid(1.0))
+}
+
+/// Maps each face of the unit cube onto the whole texture
+pub fn cubic(point: Point) -> Uv {
+    let Point { x, y, z, .. } = point;
+    let coord = |value: Real| (value.rem_euclid(2.0)) / 2.0;
+    let max = Real::max(x.abs(), Real::max(y.abs(), z.abs()));
+
+    if max == x {
+        (coord(1.0 - z), coord(y + 1.0))
+    } else if max == -x {
+        (coord(z + 1.0), coord(y + 1.0))
+    } else if max == y {
+        (coord(x + 1.0), coord(1.0 - z))
+    } else if max == -y {
+        (coord(x + 1.0), coord(z + 1.0))
+    } else if max == z {
+        (coord(x + 1.0), coord(y + 1.0))
+    } else {
+        (coord(1.0 - x), coord(y + 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::uvs::{cubic, cylindrical, planar, spherical};
+    use crate::tuples::points;
+
+    #[test]
+    fn test_spherical_mapping() {
+        let data = [
+            (points::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (points::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (points::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (points::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (points::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (points::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+        ];
+        for (point, uv) in data {
+            assert_eq!(spherical(point), uv);
+        }
+    }
+
+    #[test]
+    fn test_planar_mapping() {
+        assert_eq!(planar(points::new(0.25, 0.0, 0.5)), (0.25, 0.5));
+        assert_eq!(planar(points::new(1.25, 0.0, -0.25)), (0.25, 0.75));
+    }
+
+    #[test]
+    fn test_cylindrical_mapping() {
+        assert_eq!(cylindrical(points::new(0.0, 0.0, -1.0)), (0.0, 0.0));
+        assert_eq!(cylindrical(points::new(0.0, 0.5, -1.0)), (0.0, 0.5));
+        assert_eq!(cylindrical(points::new(1.0, 1.25, 0.0)), (0.25, 0.25));
+    }
+
+    #[test]
+    fn test_cubic_mapping() {
+        // front face (+z)
+        assert_eq!(cubic(points::new(-0.5, 0.5, 1.0)), (0.25, 0.75));
+        // right face (+x)
+        assert_eq!(cubic(points::new(1.0, 0.5, -0.5)), (0.75, 0.75));
+        // up face (+y)
+        assert_eq!(cubic(points::new(-0.5, 1.0, -0.5)), (0.25, 0.75));
+    }
+}